@@ -4,6 +4,12 @@ import { SerialPort as PolyfillSerialPort } from 'web-serial-polyfill';
 // Initialize WASM (serial helpers auto-exposed to window!)
 await init();
 
+// Enable the connect button now that the module is loaded
+const toggleConnect = document.getElementById('toggle-connect');
+if (toggleConnect) {
+  toggleConnect.disabled = false;
+}
+
 import("../pkg/index.js").catch(console.error);
 
 // Expose WASM functions to window
@@ -33,7 +33,13 @@ export async function requestSerialPort(forceNew = false) {
     throw new Error('WebSerial not available on this browser. Please use Chrome, Edge, or Opera.');
   }
 
-  const port = await navigator.serial.requestPort();
+  // Workers cannot show the port picker: reuse a port the page already granted
+  const port = typeof navigator.serial.requestPort === 'function'
+    ? await navigator.serial.requestPort()
+    : (await navigator.serial.getPorts())[0];
+  if (!port) {
+    throw new Error('No serial port granted. Select one from the page first.');
+  }
   await port.open({ baudRate: 1000000 });
   cachedPort = port;
   return port;
@@ -76,11 +82,9 @@ if (!pkgIndexContent.includes('AUTO-INJECTED HELPERS')) {
 // AUTO-INJECTED HELPERS - Do not edit manually
 import { requestSerialPort as _requestSerialPort, closeSerialPort as _closeSerialPort } from './helpers.js';
 
-// Auto-expose helpers to window for WASM to use
-if (typeof window !== 'undefined') {
-  window.requestSerialPort = _requestSerialPort;
-  window.closeSerialPort = _closeSerialPort;
-}
+// Auto-expose helpers on the global object (window or worker) for WASM to use
+globalThis.requestSerialPort = _requestSerialPort;
+globalThis.closeSerialPort = _closeSerialPort;
 `;
 
   pkgIndexContent += setupCode;
@@ -0,0 +1,161 @@
+//! # reachy-mini-bridge
+//!
+//! Exposes a robot plugged into this machine to the web app over the LAN by
+//! serving the daemon's raw bus WebSocket endpoint from a local serial port.
+//!
+//! ```text
+//! reachy-mini-bridge --bind 0.0.0.0:8000 --token s3cret
+//! ```
+//!
+//! The web app then connects to `ws://<host>:8000/api/move/ws/raw/write?token=s3cret`.
+
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use reachy_mini::error::{Error, Result};
+use reachy_mini::serial::{available_ports, SerialPort};
+use reachy_mini::server::{Server, ServerOptions, DEFAULT_BIND, RAW_WRITE_PATH};
+
+const USAGE: &str = "\
+Usage: reachy-mini-bridge [OPTIONS]
+
+Serve a local serial port as the raw bus WebSocket endpoint.
+
+Options:
+  -p, --port PATH     Serial device (default: first /dev/ttyUSB* or /dev/ttyACM*)
+  -b, --bind ADDR     Address to listen on (default: 127.0.0.1:8000)
+      --token TOKEN   Require clients to pass ?token=TOKEN (default: $REACHY_MINI_TOKEN)
+      --timeout MS    Time to wait for motor answers (default: 20)
+  -h, --help          Print this help
+";
+
+/// Environment variable read when `--token` is not given
+const TOKEN_VAR: &str = "REACHY_MINI_TOKEN";
+
+/// Parsed command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Args {
+    port: Option<PathBuf>,
+    bind: Option<String>,
+    token: Option<String>,
+    timeout: Option<Duration>,
+    help: bool,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = match parse(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        print!("{}", USAGE);
+        return;
+    }
+    if args.token.is_none() {
+        args.token = std::env::var(TOKEN_VAR)
+            .ok()
+            .filter(|token| !token.is_empty());
+    }
+
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Open the serial port and serve it until the process is stopped.
+fn run(args: &Args) -> Result<()> {
+    let path = match &args.port {
+        Some(path) => path.clone(),
+        None => available_ports().into_iter().next().ok_or_else(|| {
+            Error::InvalidArgument(
+                "No serial adapter found (/dev/ttyUSB*, /dev/ttyACM*); use --port".to_string(),
+            )
+        })?,
+    };
+    let port = SerialPort::open(&path)?;
+
+    let mut options = ServerOptions {
+        token: args.token.clone(),
+        ..ServerOptions::default()
+    };
+    if let Some(timeout) = args.timeout {
+        options.timeout = timeout;
+    }
+    let server = Server::bind(args.bind.as_deref().unwrap_or(DEFAULT_BIND), options)?;
+
+    eprintln!(
+        "Serving {} on ws://{}{}{}",
+        path.display(),
+        server.local_addr()?,
+        RAW_WRITE_PATH,
+        if args.token.is_some() {
+            " (token required)"
+        } else {
+            ""
+        }
+    );
+    server.serve(port)
+}
+
+/// Parse the arguments following the program name.
+fn parse(args: &[String]) -> std::result::Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "-p" | "--port" => parsed.port = Some(PathBuf::from(value(arg)?)),
+            "-b" | "--bind" => parsed.bind = Some(value(arg)?),
+            "--token" => parsed.token = Some(value(arg)?),
+            "--timeout" => {
+                let ms = value(arg)?;
+                parsed.timeout = Some(
+                    ms.parse::<u64>()
+                        .ok()
+                        .filter(|ms| *ms > 0)
+                        .map(Duration::from_millis)
+                        .ok_or("--timeout expects a positive number of milliseconds")?,
+                );
+            }
+            "-h" | "--help" => parsed.help = true,
+            other => return Err(format!("unexpected argument '{}'", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse(&args("--bind 0.0.0.0:9000 --token abc -p /dev/ttyACM0")).unwrap();
+        assert_eq!(parsed.bind.as_deref(), Some("0.0.0.0:9000"));
+        assert_eq!(parsed.token.as_deref(), Some("abc"));
+        assert_eq!(parsed.port, Some(PathBuf::from("/dev/ttyACM0")));
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+
+        assert!(parse(&args("--timeout 0")).is_err());
+        assert!(parse(&args("--token")).is_err());
+        assert!(parse(&args("serve")).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! # reachy-mini-sim
+//!
+//! Serves the daemon's raw bus WebSocket endpoint from a simulated robot, so
+//! web apps can be demoed and tested in CI without hardware. `connect()`
+//! reaches it at `ws://localhost:8000/api/move/ws/raw/write` exactly as it
+//! would a real robot.
+//!
+//! ```text
+//! reachy-mini-sim
+//! reachy-mini-sim --drop 12 --crc-errors 0.01 --overheat l_antenna
+//! ```
+
+use std::process;
+use std::time::Duration;
+
+use reachy_mini::config::RobotConfig;
+use reachy_mini::dynamixel::hardware_error;
+use reachy_mini::error::{Error, Result};
+use reachy_mini::port::{SimulatedPort, BUS_BAUD_RATE};
+use reachy_mini::server::{Server, ServerOptions, DEFAULT_BIND, RAW_WRITE_PATH};
+use reachy_mini::simulator::SimulatedBus;
+
+const USAGE: &str = "\
+Usage: reachy-mini-sim [OPTIONS]
+
+Serve a simulated robot as the raw bus WebSocket endpoint.
+
+Options:
+  -b, --bind ADDR          Address to listen on (default: 127.0.0.1:8000)
+  -c, --config PROFILE     lite, wireless, or RobotConfig JSON (default: lite)
+      --instant            Answer immediately instead of at 1 Mbps bus timing
+      --timeout MS         Time to wait for motor answers (default: 20)
+      --token TOKEN        Require clients to pass ?token=TOKEN
+
+Fault injection (MOTOR is a bus ID or a joint name; options repeat):
+      --drop MOTOR         Unplug a motor: it never answers
+      --overheat MOTOR     Latch an overheating alert (cleared by a reboot)
+      --overload MOTOR     Latch an overload alert (cleared by a reboot)
+      --crc-errors RATE    Corrupt this fraction of status packets (0 to 1)
+  -h, --help               Print this help
+";
+
+/// Parsed command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Args {
+    bind: Option<String>,
+    config: Option<String>,
+    instant: bool,
+    timeout: Option<Duration>,
+    token: Option<String>,
+    drop: Vec<String>,
+    overheat: Vec<String>,
+    overload: Vec<String>,
+    crc_errors: f32,
+    help: bool,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Build the simulated bus and serve it until the process is stopped.
+fn run(args: &Args) -> Result<()> {
+    let config = match &args.config {
+        Some(profile) => RobotConfig::resolve(profile)?,
+        None => RobotConfig::default(),
+    };
+    let bus = build_bus(&config, args)?;
+    let mut port = SimulatedPort::new(bus);
+    if !args.instant {
+        port = port.with_timing(BUS_BAUD_RATE);
+    }
+
+    let mut options = ServerOptions {
+        token: args.token.clone(),
+        ..ServerOptions::default()
+    };
+    if let Some(timeout) = args.timeout {
+        options.timeout = timeout;
+    }
+    let server = Server::bind(args.bind.as_deref().unwrap_or(DEFAULT_BIND), options)?;
+
+    eprintln!(
+        "Simulating {} motors on ws://{}{}",
+        config.motor_ids().len(),
+        server.local_addr()?,
+        RAW_WRITE_PATH
+    );
+    server.serve(port)
+}
+
+/// One motor per joint of `config`, with the requested faults injected.
+fn build_bus(config: &RobotConfig, args: &Args) -> Result<SimulatedBus> {
+    let mut bus = SimulatedBus::new(&config.motor_ids());
+    bus.set_crc_error_rate(args.crc_errors);
+
+    let faults = [
+        (&args.drop, None),
+        (&args.overheat, Some(hardware_error::OVERHEATING)),
+        (&args.overload, Some(hardware_error::OVERLOAD)),
+    ];
+    for (motors, fault) in faults.iter() {
+        for motor in motors.iter() {
+            let id = motor_id(config, motor)?;
+            let motor = bus.motor_mut(id).expect("configured motors are simulated");
+            match fault {
+                Some(bits) => motor.raise_hardware_error(*bits),
+                None => motor.set_connected(false),
+            }
+        }
+    }
+    Ok(bus)
+}
+
+/// Resolve a bus ID or joint name against the configuration.
+fn motor_id(config: &RobotConfig, motor: &str) -> Result<u8> {
+    let id = match motor.parse::<u8>() {
+        Ok(id) => id,
+        Err(_) => config.joint_table().get(motor)?.id,
+    };
+    if config.motor_ids().contains(&id) {
+        Ok(id)
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "No motor with ID {} in this configuration",
+            id
+        )))
+    }
+}
+
+/// Parse the arguments following the program name.
+fn parse(args: &[String]) -> std::result::Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "-b" | "--bind" => parsed.bind = Some(value(arg)?),
+            "-c" | "--config" => parsed.config = Some(value(arg)?),
+            "--instant" => parsed.instant = true,
+            "--timeout" => {
+                let ms = value(arg)?;
+                parsed.timeout = Some(
+                    ms.parse::<u64>()
+                        .ok()
+                        .filter(|ms| *ms > 0)
+                        .map(Duration::from_millis)
+                        .ok_or("--timeout expects a positive number of milliseconds")?,
+                );
+            }
+            "--token" => parsed.token = Some(value(arg)?),
+            "--drop" => parsed.drop.push(value(arg)?),
+            "--overheat" => parsed.overheat.push(value(arg)?),
+            "--overload" => parsed.overload.push(value(arg)?),
+            "--crc-errors" => {
+                parsed.crc_errors = value(arg)?
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|rate| (0.0..=1.0).contains(rate))
+                    .ok_or("--crc-errors expects a rate between 0 and 1")?;
+            }
+            "-h" | "--help" => parsed.help = true,
+            other => return Err(format!("unexpected argument '{}'", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reachy_mini::dynamixel::address;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse(&args(
+            "--drop 12 --drop l_antenna --crc-errors 0.1 --instant",
+        ))
+        .unwrap();
+        assert_eq!(parsed.drop, vec!["12", "l_antenna"]);
+        assert_eq!(parsed.crc_errors, 0.1);
+        assert!(parsed.instant);
+
+        assert!(parse(&args("--crc-errors 2")).is_err());
+        assert!(parse(&args("--overheat")).is_err());
+    }
+
+    #[test]
+    fn test_faults_applied() {
+        let config = RobotConfig::lite();
+        let parsed = parse(&args("--drop 12 --overheat r_antenna --overload 13")).unwrap();
+        let bus = build_bus(&config, &parsed).unwrap();
+
+        assert!(!bus.motor(12).unwrap().connected());
+        assert!(bus.motor(11).unwrap().connected());
+        let r_antenna = config.joint_table().get("r_antenna").unwrap().id;
+        let status = |id| {
+            bus.motor(id)
+                .unwrap()
+                .read(address::HARDWARE_ERROR_STATUS, 1)
+                .unwrap()[0]
+        };
+        assert_eq!(status(r_antenna), hardware_error::OVERHEATING);
+        assert_eq!(status(13), hardware_error::OVERLOAD);
+
+        let parsed = parse(&args("--drop 42")).unwrap();
+        assert!(build_bus(&config, &parsed).is_err());
+        let parsed = parse(&args("--drop nose")).unwrap();
+        assert!(build_bus(&config, &parsed).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! # Command-Line Arguments
+//!
+//! Global options may appear anywhere; the first other word is the
+//! subcommand. Negative numbers are values, not options, so
+//! `pose 0 0 -5 0 0 0` parses as expected.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reachy_mini::client::DEFAULT_PERIOD;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Options shared by every subcommand.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    /// Serial device; the first USB adapter found if omitted
+    pub port: Option<PathBuf>,
+    /// `"lite"`, `"wireless"`, a JSON `RobotConfig` or a path to one
+    pub config: Option<String>,
+    /// Print JSON instead of tables
+    pub json: bool,
+    /// Drive an in-process simulated robot instead of a serial port
+    pub simulated: bool,
+    /// Time to wait for status packets
+    pub timeout: Option<Duration>,
+}
+
+/// A subcommand and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Scan,
+    Status,
+    Torque {
+        enable: bool,
+        /// Joint names; every motor if empty
+        joints: Vec<String>,
+    },
+    /// Print the head pose, or move to `[x, y, z, roll, pitch, yaw]`
+    Pose(Option<[f32; 6]>),
+    /// Print every joint, or command `(name, degrees)` targets
+    Joints(Vec<(String, f32)>),
+    Record {
+        duration: Duration,
+        period: Duration,
+        output: Option<PathBuf>,
+    },
+    Replay(PathBuf),
+    /// Reboot the named joints' motors; every motor if empty
+    Reboot(Vec<String>),
+    ConfigBackup(Option<PathBuf>),
+    ConfigRestore(PathBuf),
+    /// Sample the reachable head poses offline
+    Workspace {
+        grid: WorkspaceKind,
+        /// Grid spacing (mm or degrees); the kind's default if omitted
+        step: Option<f32>,
+        /// `.ply` for a point cloud, JSON otherwise; stdout if omitted
+        output: Option<PathBuf>,
+    },
+    /// Fit the geometry to head joint readings at known poses, offline
+    Calibrate {
+        /// JSON list of `{ "pose": [...], "joints_deg": [...] }`
+        samples: PathBuf,
+        /// Calibrated configuration; stdout if omitted
+        output: Option<PathBuf>,
+    },
+    Help,
+}
+
+/// Axes sampled by [`Command::Workspace`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkspaceKind {
+    /// x, y, z with the head level
+    Translation,
+    /// roll, pitch, yaw with the head centered
+    Orientation,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse the arguments following the program name.
+///
+/// # Errors
+/// Returns a message suitable for printing above the usage text.
+pub fn parse(args: &[String]) -> Result<(Options, Command), String> {
+    let mut options = Options::default();
+    let mut words = Vec::new();
+    let mut flags = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "-p" | "--port" => options.port = Some(PathBuf::from(value(arg)?)),
+            "-c" | "--config" => options.config = Some(value(arg)?),
+            "--json" => options.json = true,
+            "--simulated" => options.simulated = true,
+            "--timeout" => options.timeout = Some(millis(&value(arg)?, arg)?),
+            "-h" | "--help" => return Ok((options, Command::Help)),
+            // Subcommand options, interpreted below
+            "-d" | "--duration" | "--period" | "--step" | "-o" | "--output" => {
+                flags.push((arg.clone(), value(arg)?))
+            }
+            _ if arg.starts_with('-') && arg.parse::<f32>().is_err() => {
+                return Err(format!("unknown option '{}'", arg))
+            }
+            _ => words.push(arg.clone()),
+        }
+    }
+
+    let command = match words.first() {
+        Some(name) => command(name, &words[1..], &flags)?,
+        None => Command::Help,
+    };
+    Ok((options, command))
+}
+
+fn command(name: &str, args: &[String], flags: &[(String, String)]) -> Result<Command, String> {
+    let flag = |names: &[&str]| {
+        flags
+            .iter()
+            .find(|(flag, _)| names.contains(&flag.as_str()))
+            .map(|(_, value)| value.as_str())
+    };
+
+    Ok(match name {
+        "scan" => no_args(name, args, Command::Scan)?,
+        "status" => no_args(name, args, Command::Status)?,
+        "torque" => match args.split_first() {
+            Some((state, joints)) => Command::Torque {
+                enable: match state.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(format!("torque expects on or off, got '{}'", other)),
+                },
+                joints: joints.to_vec(),
+            },
+            None => return Err("torque expects on or off".to_string()),
+        },
+        "pose" => match args.len() {
+            0 => Command::Pose(None),
+            6 => {
+                let mut pose = [0.0; 6];
+                for (value, arg) in pose.iter_mut().zip(args) {
+                    *value = number(arg)?;
+                }
+                Command::Pose(Some(pose))
+            }
+            _ => return Err("pose expects x y z roll pitch yaw (mm, degrees)".to_string()),
+        },
+        "joints" => Command::Joints(
+            args.iter()
+                .map(|arg| target(arg))
+                .collect::<Result<_, _>>()?,
+        ),
+        "record" => {
+            let duration = flag(&["-d", "--duration"])
+                .ok_or("record needs --duration SECONDS")?
+                .parse::<f64>()
+                .ok()
+                .filter(|secs| *secs > 0.0)
+                .ok_or("--duration expects a positive number of seconds")?;
+            Command::Record {
+                duration: Duration::from_secs_f64(duration),
+                period: match flag(&["--period"]) {
+                    Some(period) => millis(period, "--period")?,
+                    None => DEFAULT_PERIOD,
+                },
+                output: flag(&["-o", "--output"]).map(PathBuf::from),
+            }
+        }
+        "replay" => match args {
+            [file] => Command::Replay(PathBuf::from(file)),
+            _ => return Err("replay expects a recording file".to_string()),
+        },
+        "reboot" => Command::Reboot(args.to_vec()),
+        "config" => match args {
+            [action] if action == "backup" => Command::ConfigBackup(None),
+            [action, file] if action == "backup" => {
+                Command::ConfigBackup(Some(PathBuf::from(file)))
+            }
+            [action, file] if action == "restore" => Command::ConfigRestore(PathBuf::from(file)),
+            _ => return Err("config expects 'backup [FILE]' or 'restore FILE'".to_string()),
+        },
+        "workspace" => Command::Workspace {
+            grid: match args {
+                [] => WorkspaceKind::Translation,
+                [kind] if kind == "translation" => WorkspaceKind::Translation,
+                [kind] if kind == "orientation" => WorkspaceKind::Orientation,
+                _ => return Err("workspace expects 'translation' or 'orientation'".to_string()),
+            },
+            step: match flag(&["--step"]) {
+                Some(step) => Some(
+                    step.parse::<f32>()
+                        .ok()
+                        .filter(|step| *step > 0.0)
+                        .ok_or("--step expects a positive number")?,
+                ),
+                None => None,
+            },
+            output: flag(&["-o", "--output"]).map(PathBuf::from),
+        },
+        "calibrate" => match args {
+            [file] => Command::Calibrate {
+                samples: PathBuf::from(file),
+                output: flag(&["-o", "--output"]).map(PathBuf::from),
+            },
+            _ => return Err("calibrate expects a samples file".to_string()),
+        },
+        "help" => Command::Help,
+        other => return Err(format!("unknown command '{}'", other)),
+    })
+}
+
+fn no_args(name: &str, args: &[String], command: Command) -> Result<Command, String> {
+    if args.is_empty() {
+        Ok(command)
+    } else {
+        Err(format!("{} takes no arguments", name))
+    }
+}
+
+fn number(arg: &str) -> Result<f32, String> {
+    arg.parse()
+        .map_err(|_| format!("'{}' is not a number", arg))
+}
+
+/// A `name=degrees` joint target.
+fn target(arg: &str) -> Result<(String, f32), String> {
+    match arg.split_once('=') {
+        Some((name, angle)) if !name.is_empty() => Ok((name.to_string(), number(angle)?)),
+        _ => Err(format!("expected NAME=DEGREES, got '{}'", arg)),
+    }
+}
+
+fn millis(arg: &str, name: &str) -> Result<Duration, String> {
+    arg.parse::<u64>()
+        .ok()
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis)
+        .ok_or_else(|| format!("{} expects a positive number of milliseconds", name))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_options_anywhere() {
+        let (options, command) = parse(&args("--json status -p /dev/ttyUSB1")).unwrap();
+        assert!(options.json);
+        assert_eq!(options.port, Some(PathBuf::from("/dev/ttyUSB1")));
+        assert_eq!(command, Command::Status);
+        assert_eq!(parse(&[]).unwrap().1, Command::Help);
+        assert!(parse(&args("status --bogus")).is_err());
+    }
+
+    #[test]
+    fn test_pose_and_joints() {
+        let (_, command) = parse(&args("pose 0 0 -5 10 0 -15.5")).unwrap();
+        assert_eq!(
+            command,
+            Command::Pose(Some([0.0, 0.0, -5.0, 10.0, 0.0, -15.5]))
+        );
+        let (_, command) = parse(&args("joints l_antenna=30 r_antenna=-30")).unwrap();
+        assert_eq!(
+            command,
+            Command::Joints(vec![
+                ("l_antenna".to_string(), 30.0),
+                ("r_antenna".to_string(), -30.0)
+            ])
+        );
+        assert!(parse(&args("pose 1 2 3")).is_err());
+        assert!(parse(&args("joints l_antenna")).is_err());
+    }
+
+    #[test]
+    fn test_record_and_config() {
+        let (_, command) = parse(&args("record --duration 2.5 -o take.json")).unwrap();
+        assert_eq!(
+            command,
+            Command::Record {
+                duration: Duration::from_millis(2500),
+                period: DEFAULT_PERIOD,
+                output: Some(PathBuf::from("take.json")),
+            }
+        );
+        assert!(parse(&args("record")).is_err());
+        assert_eq!(
+            parse(&args("config restore motors.json")).unwrap().1,
+            Command::ConfigRestore(PathBuf::from("motors.json"))
+        );
+        assert!(parse(&args("torque maybe")).is_err());
+    }
+
+    #[test]
+    fn test_workspace() {
+        let (_, command) = parse(&args("workspace orientation --step 10 -o reach.ply")).unwrap();
+        assert_eq!(
+            command,
+            Command::Workspace {
+                grid: WorkspaceKind::Orientation,
+                step: Some(10.0),
+                output: Some(PathBuf::from("reach.ply")),
+            }
+        );
+        assert!(parse(&args("workspace --step -1")).is_err());
+        assert!(parse(&args("workspace sideways")).is_err());
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let (_, command) = parse(&args("calibrate jig.json -o calibrated.json")).unwrap();
+        assert_eq!(
+            command,
+            Command::Calibrate {
+                samples: PathBuf::from("jig.json"),
+                output: Some(PathBuf::from("calibrated.json")),
+            }
+        );
+        assert!(parse(&args("calibrate")).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+//! # reachy-mini
+//!
+//! Command-line tool for field checks of a Reachy Mini over its USB serial
+//! adapter: scan the bus, read motor health, move the head and joints, record
+//! and replay motions, reboot motors and back up motor settings.
+//!
+//! ```text
+//! reachy-mini scan
+//! reachy-mini status --json
+//! reachy-mini pose 0 0 10 0 0 15
+//! reachy-mini record --duration 5 -o wave.json
+//! reachy-mini workspace --step 5 -o reach.ply
+//! ```
+
+mod args;
+mod table;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+use serde_json::{json, Map, Value};
+
+use reachy_mini::client::{Recording, Robot, SettingsBackup};
+use reachy_mini::config::{PoseSample, RobotConfig};
+use reachy_mini::error::{Error, Result};
+use reachy_mini::port::Port;
+use reachy_mini::serial::{available_ports, SerialPort};
+use reachy_mini::workspace::WorkspaceGrid;
+
+use crate::args::{Command, Options, WorkspaceKind};
+use crate::table::Table;
+
+const USAGE: &str = "\
+Usage: reachy-mini [OPTIONS] <COMMAND>
+
+Commands:
+  scan                          List the motors answering on the bus
+  status                        Temperature, load, voltage and errors per motor
+  torque on|off [JOINT...]      Switch torque (every motor by default)
+  pose [X Y Z ROLL PITCH YAW]   Print or set the head pose (mm, degrees)
+  joints [NAME=DEG...]          Print or set joint angles
+  record --duration SECONDS     Record every joint [--period MS] [-o FILE]
+  replay FILE                   Play a recording back
+  reboot [JOINT...]             Reboot motors (every motor by default)
+  config backup [FILE]          Save motor EEPROM settings
+  config restore FILE           Write saved motor settings back
+  workspace [translation|orientation]
+                                Reachable head poses, offline [--step N] [-o FILE]
+                                (.ply point cloud, JSON otherwise)
+  calibrate FILE                Fit the geometry and head zeros to joint readings
+                                at known poses, offline [-o FILE]
+
+Options:
+  -p, --port PATH       Serial device (default: first /dev/ttyUSB* or /dev/ttyACM*)
+  -c, --config PROFILE  lite, wireless, or a RobotConfig JSON file (default: lite)
+      --json            Print JSON instead of tables
+      --simulated       Use the built-in simulated robot
+      --timeout MS      Time to wait for motor answers (default: 20)
+  -h, --help            Print this help
+";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (options, command) = match args::parse(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if command == Command::Help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = run(&options, &command) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Open the robot selected by `options` and run `command` on it.
+fn run(options: &Options, command: &Command) -> Result<()> {
+    let config = load_config(options.config.as_deref())?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if let Command::Workspace { grid, step, output } = command {
+        return workspace(&config, *grid, *step, output.as_deref(), &mut out);
+    }
+    if let Command::Calibrate { samples, output } = command {
+        return calibrate(&config, samples, output.as_deref(), &mut out);
+    }
+    if options.simulated {
+        let mut robot = Robot::simulated(config);
+        return execute(&mut robot, options, command, &mut out);
+    }
+
+    let path = match &options.port {
+        Some(path) => path.clone(),
+        None => available_ports().into_iter().next().ok_or_else(|| {
+            Error::InvalidArgument(
+                "No serial adapter found (/dev/ttyUSB*, /dev/ttyACM*); use --port or --simulated"
+                    .to_string(),
+            )
+        })?,
+    };
+    let mut robot = Robot::new(SerialPort::open(&path)?, config);
+    execute(&mut robot, options, command, &mut out)
+}
+
+/// Resolve `--config`: a profile name, inline JSON or a JSON file.
+fn load_config(config: Option<&str>) -> Result<RobotConfig> {
+    match config {
+        None => Ok(RobotConfig::default()),
+        Some(value) if Path::new(value).is_file() => {
+            Ok(RobotConfig::from_json(&fs::read_to_string(value)?)?)
+        }
+        Some(value) => Ok(RobotConfig::resolve(value)?),
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Run `command`, writing its report to `out`.
+fn execute<P: Port, W: Write>(
+    robot: &mut Robot<P>,
+    options: &Options,
+    command: &Command,
+    out: &mut W,
+) -> Result<()> {
+    if let Some(timeout) = options.timeout {
+        robot.set_timeout(timeout);
+    }
+    let json = options.json;
+
+    match command {
+        Command::Scan => {
+            let motors = robot.scan()?;
+            if json {
+                return print_json(out, &json!(motors));
+            }
+            let mut table = Table::new(&["ID", "JOINT", "MODEL", "FIRMWARE"]);
+            for motor in motors {
+                table.row(vec![
+                    motor.id.to_string(),
+                    motor.name.unwrap_or_else(|| "-".to_string()),
+                    motor.model.to_string(),
+                    motor.firmware.to_string(),
+                ]);
+            }
+            write!(out, "{}", table.render())?;
+        }
+        Command::Status => {
+            let motors = robot.get_diagnostics()?;
+            if json {
+                let motors: Vec<Value> = motors
+                    .iter()
+                    .map(|motor| {
+                        let mut value = json!(motor);
+                        value["errors"] = json!(motor.errors());
+                        value
+                    })
+                    .collect();
+                return print_json(out, &json!(motors));
+            }
+            let mut table = Table::new(&["JOINT", "ID", "TEMP °C", "LOAD", "VOLTAGE", "ERRORS"]);
+            for motor in motors {
+                if !motor.responding {
+                    table.row(vec![motor.name, motor.id.to_string(), "no response".into()]);
+                    continue;
+                }
+                let errors = motor.errors();
+                table.row(vec![
+                    motor.name.clone(),
+                    motor.id.to_string(),
+                    motor.temperature.to_string(),
+                    motor.load.to_string(),
+                    format!("{:.1} V", motor.voltage),
+                    if errors.is_empty() {
+                        "-".to_string()
+                    } else {
+                        errors.join(", ")
+                    },
+                ]);
+            }
+            write!(out, "{}", table.render())?;
+        }
+        Command::Torque { enable, joints } => {
+            if joints.is_empty() {
+                robot.set_torque(*enable)?;
+            } else {
+                robot.set_joint_torque(joints, *enable)?;
+            }
+        }
+        Command::Pose(Some(pose)) => {
+            if let Some(condition_number) = robot.set_head_pose(*pose)? {
+                warn_singularity("the head pose is", condition_number);
+            }
+        }
+        Command::Pose(None) => {
+            let pose = robot.get_head_pose()?;
+            let names = ["x", "y", "z", "roll", "pitch", "yaw"];
+            if json {
+                let pose: Map<String, Value> = names
+                    .iter()
+                    .zip(pose.iter())
+                    .map(|(name, value)| (name.to_string(), json!(value)))
+                    .collect();
+                return print_json(out, &Value::Object(pose));
+            }
+            let mut table = Table::new(&["X mm", "Y mm", "Z mm", "ROLL °", "PITCH °", "YAW °"]);
+            table.row(pose.iter().map(|value| format!("{:.1}", value)).collect());
+            write!(out, "{}", table.render())?;
+        }
+        Command::Joints(targets) if !targets.is_empty() => robot.set_joints(targets)?,
+        Command::Joints(_) => {
+            let joints = robot.get_joints()?;
+            if json {
+                let joints: Map<String, Value> = joints
+                    .into_iter()
+                    .map(|(name, angle)| (name, json!(angle)))
+                    .collect();
+                return print_json(out, &Value::Object(joints));
+            }
+            let mut table = Table::new(&["JOINT", "ANGLE °"]);
+            for (name, angle) in joints {
+                table.row(vec![name, format!("{:.1}", angle)]);
+            }
+            write!(out, "{}", table.render())?;
+        }
+        Command::Record {
+            duration,
+            period,
+            output,
+        } => {
+            let recording = robot.record(*duration, *period)?;
+            match output {
+                Some(path) => {
+                    fs::write(path, recording.to_json())?;
+                    eprintln!(
+                        "Recorded {} frames to {}",
+                        recording.frames.len(),
+                        path.display()
+                    );
+                }
+                None => writeln!(out, "{}", recording.to_json())?,
+            }
+        }
+        Command::Replay(path) => {
+            let recording = Recording::from_json(&fs::read_to_string(path)?)?;
+            if let Some(condition_number) = robot.replay(&recording)? {
+                warn_singularity("the recording passes", condition_number);
+            }
+        }
+        Command::Reboot(joints) if joints.is_empty() => robot.reboot_all()?,
+        Command::Reboot(joints) => {
+            for name in joints {
+                robot.reboot(name)?;
+            }
+        }
+        Command::ConfigBackup(path) => {
+            let backup = robot.backup_settings()?;
+            match path {
+                Some(path) => fs::write(path, backup.to_json())?,
+                None => writeln!(out, "{}", backup.to_json())?,
+            }
+        }
+        Command::ConfigRestore(path) => {
+            let backup = SettingsBackup::from_json(&fs::read_to_string(path)?)?;
+            robot.restore_settings(&backup)?;
+        }
+        Command::Workspace { grid, step, output } => {
+            workspace(robot.config(), *grid, *step, output.as_deref(), out)?
+        }
+        Command::Calibrate { samples, output } => {
+            calibrate(robot.config(), samples, output.as_deref(), out)?
+        }
+        Command::Help => write!(out, "{}", USAGE)?,
+    }
+    Ok(())
+}
+
+/// Sample the head workspace of `config` and export it.
+fn workspace<W: Write>(
+    config: &RobotConfig,
+    kind: WorkspaceKind,
+    step: Option<f32>,
+    output: Option<&Path>,
+    out: &mut W,
+) -> Result<()> {
+    let grid = match kind {
+        WorkspaceKind::Translation => WorkspaceGrid::translation(step.unwrap_or(5.0)),
+        WorkspaceKind::Orientation => WorkspaceGrid::orientation(step.unwrap_or(5.0)),
+    };
+    let workspace = config.workspace(&grid);
+    match output {
+        Some(path) => {
+            let ply = path.extension().is_some_and(|ext| ext == "ply");
+            let data = if ply {
+                workspace.to_ply()
+            } else {
+                workspace.to_json()
+            };
+            fs::write(path, data)?;
+            eprintln!(
+                "{} of {} poses reachable ({:.0} %), written to {}",
+                workspace.reachable_poses().len(),
+                grid.len(),
+                workspace.coverage() * 100.0,
+                path.display()
+            );
+        }
+        None => writeln!(out, "{}", workspace.to_json())?,
+    }
+    Ok(())
+}
+
+/// Fit `config` to the samples in `path` and export the calibrated
+/// configuration.
+fn calibrate<W: Write>(
+    config: &RobotConfig,
+    path: &Path,
+    output: Option<&Path>,
+    out: &mut W,
+) -> Result<()> {
+    let samples: Vec<PoseSample> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| Error::InvalidArgument(format!("{}: {}", path.display(), e)))?;
+    let (calibrated, calibration) = config.calibrate(&samples)?;
+
+    let mut table = Table::new(&["JOINT", "ARM mm", "ROD mm", "ANCHOR mm", "ZERO °"]);
+    for (joint, branch) in config
+        .joint_table()
+        .head()
+        .iter()
+        .zip(&calibration.branches)
+    {
+        let anchor = branch.anchor_offset * 1000.0;
+        table.row(vec![
+            joint.name.clone(),
+            format!("{:.2}", branch.motor_arm_length * 1000.0),
+            format!("{:.2}", branch.rod_length * 1000.0),
+            format!("{:+.2} {:+.2} {:+.2}", anchor.x, anchor.y, anchor.z),
+            format!("{:+.2}", branch.zero_offset.to_degrees()),
+        ]);
+    }
+    eprint!("{}", table.render());
+    eprintln!(
+        "RMS rod error {:.3} mm -> {:.3} mm over {} samples",
+        calibration.rms_before * 1000.0,
+        calibration.rms_after * 1000.0,
+        samples.len()
+    );
+    match output {
+        Some(path) => fs::write(path, calibrated.to_json())?,
+        None => writeln!(out, "{}", calibrated.to_json())?,
+    }
+    Ok(())
+}
+
+/// Warn on stderr about a pose near a singularity; `what` is the subject,
+/// e.g. "the head pose is".
+fn warn_singularity(what: &str, condition_number: f32) {
+    eprintln!(
+        "warning: {} near a singularity (condition number {:.0})",
+        what, condition_number
+    );
+}
+
+fn print_json<W: Write>(out: &mut W, value: &Value) -> Result<()> {
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string_pretty(value).expect("JSON values serialize")
+    )?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reachy_mini::port::SimulatedPort;
+
+    /// Run a command line against a fresh simulated robot.
+    fn run_simulated(robot: &mut Robot<SimulatedPort>, line: &str) -> String {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        let (options, command) = args::parse(&args).unwrap();
+        let mut out = Vec::new();
+        execute(robot, &options, &command, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_status_table_and_json() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        let table = run_simulated(&mut robot, "status");
+        assert!(table.starts_with("JOINT"));
+        assert!(table.contains("l_antenna  17"));
+        assert!(table.contains("5.0 V"));
+
+        let status: Value =
+            serde_json::from_str(&run_simulated(&mut robot, "status --json")).unwrap();
+        assert_eq!(status.as_array().unwrap().len(), 8);
+        assert_eq!(status[0]["temperature"], 32);
+        assert_eq!(status[0]["errors"], json!([]));
+    }
+
+    #[test]
+    fn test_joints_roundtrip() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        run_simulated(&mut robot, "torque on");
+        assert_eq!(run_simulated(&mut robot, "joints l_antenna=20"), "");
+        std::thread::sleep(std::time::Duration::from_millis(400));
+
+        let joints: Value =
+            serde_json::from_str(&run_simulated(&mut robot, "joints --json")).unwrap();
+        assert!((joints["l_antenna"].as_f64().unwrap() - 20.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_scan_and_backup() {
+        let mut robot = Robot::simulated(RobotConfig::wireless());
+        // The wireless profile adds the body yaw motor (ID 10)
+        let scan = run_simulated(&mut robot, "scan");
+        assert_eq!(scan.lines().count(), 1 + 9);
+
+        let backup = run_simulated(&mut robot, "config backup");
+        let backup = SettingsBackup::from_json(&backup).unwrap();
+        assert_eq!(backup.motors[0].name, "body_yaw");
+    }
+
+    #[test]
+    fn test_workspace_export() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        let workspace: Value =
+            serde_json::from_str(&run_simulated(&mut robot, "workspace --step 20")).unwrap();
+        assert!(workspace["reachable"].as_array().unwrap().len() > 1);
+        assert!(workspace["limits"]["z"].is_array());
+    }
+}
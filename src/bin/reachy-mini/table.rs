@@ -0,0 +1,64 @@
+//! # Table Output
+//!
+//! Left-aligned, space-separated columns sized to their widest cell.
+
+/// A text table with a header row.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; missing cells are left blank.
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        for row in std::iter::once(&self.headers).chain(&self.rows) {
+            let line: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, width)| {
+                    let cell = row.get(i).map(String::as_str).unwrap_or("");
+                    let pad = width - cell.chars().count();
+                    format!("{}{}", cell, " ".repeat(pad))
+                })
+                .collect();
+            out.push_str(line.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_aligns_columns() {
+        let mut table = Table::new(&["JOINT", "TEMP °C"]);
+        table.row(vec!["l_antenna".into(), "35".into()]);
+        table.row(vec!["stewart_1".into()]);
+        assert_eq!(
+            table.render(),
+            "JOINT      TEMP °C\nl_antenna  35\nstewart_1\n"
+        );
+    }
+}
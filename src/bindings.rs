@@ -0,0 +1,1866 @@
+//! # JavaScript API
+//!
+//! Free functions exported to JavaScript, the JS helper bindings and the
+//! browser transports behind `GenericPort`. Built with the `wasm` feature.
+//!
+//! The free functions drive a default [`ReachyMini`] instance.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::capture::{Capture, Direction, ReplayPlayer};
+use crate::config::Transport;
+use crate::dynamixel::{build_sync_current_position, parse_position_packets, raw_to_radians};
+use crate::platform::{global_function, GlobalScope};
+use crate::robot::ReachyMini;
+use crate::scheduler::{BusScheduler, Priority};
+use crate::simulator::SimulatedBus;
+use crate::stats::{round_trip, ConnectionStats};
+use crate::types::{
+    AntennaState, HeadJacobian, HeadPose, HeadPoseProjection, HeadPoseSolution, JointState,
+    MotorDiagnostics,
+};
+
+use futures_util::lock::Mutex;
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
+use gloo::net::websocket::futures::WebSocket;
+use gloo::net::websocket::Message;
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    console, ReadableStream, ReadableStreamDefaultReader, WritableStream,
+    WritableStreamDefaultWriter,
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Shortest duration of a write/read transaction in milliseconds
+pub(crate) const DEFAULT_WAIT_MS: u32 = 10;
+
+/// Period of the FK stream loop in milliseconds
+pub(crate) const FK_STREAM_PERIOD_MS: f64 = 20.0;
+
+/// Period between replayed frames in milliseconds
+pub(crate) const REPLAY_PERIOD_MS: f64 = 20.0;
+
+/// Times a read is resent when no motor answered
+const MAX_READ_RETRIES: u32 = 1;
+
+// ============================================================================
+// Default Instance
+// ============================================================================
+
+thread_local! {
+    /// Robot instance the free functions operate on
+    static DEFAULT_ROBOT: Rc<ReachyMini> = Rc::new(ReachyMini::new());
+}
+
+// ============================================================================
+// External JavaScript Bindings
+// ============================================================================
+
+/// Request a serial port through the `requestSerialPort()` JS helper.
+///
+/// The helper is looked up on the global object, so it can be installed on
+/// `window` or, in a worker, on `self`.
+async fn request_serial_port() -> Result<JsValue, JsValue> {
+    let helper = global_function("requestSerialPort").ok_or_else(|| {
+        JsValue::from_str("requestSerialPort() helper not found on the global object")
+    })?;
+    let promise: Promise = helper.call0(&JsValue::NULL)?.dyn_into()?;
+    JsFuture::from(promise).await
+}
+
+/// Close the current serial port through the `closeSerialPort()` JS helper, if installed.
+pub(crate) async fn close_serial_port() {
+    if let Some(helper) = global_function("closeSerialPort") {
+        if let Ok(promise) = helper.call0(&JsValue::NULL) {
+            let _ = JsFuture::from(Promise::resolve(&promise)).await;
+        }
+    }
+}
+
+/// Publish a head pose to the UI.
+///
+/// Calls the global `updatePose(x, y, z, roll, pitch, yaw)` if the page defines
+/// one. Inside a worker without such a function, the pose is posted to the page
+/// as `{ type: "pose", pose: [x, y, z, roll, pitch, yaw] }`.
+///
+/// # Arguments
+/// * `pose` - `[x, y, z, roll, pitch, yaw]` in mm (0 = minimum height) and degrees
+pub(crate) fn update_pose(pose: [f32; 6]) {
+    if let Some(update) = global_function("updatePose") {
+        let args: js_sys::Array = pose.iter().map(|&v| JsValue::from(v)).collect();
+        let _ = update.apply(&JsValue::NULL, &args);
+        return;
+    }
+
+    let message = js_sys::Object::new();
+    let pose: js_sys::Array = pose.iter().map(|&v| JsValue::from(v)).collect();
+    let _ = js_sys::Reflect::set(&message, &"type".into(), &"pose".into());
+    let _ = js_sys::Reflect::set(&message, &"pose".into(), &pose);
+    let _ = GlobalScope::current().post_to_page(&message);
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Asynchronous sleep function for WASM environment.
+///
+/// Uses the timer of the current global scope, so it also works in a Web Worker.
+///
+/// # Arguments
+/// * `ms` - Duration to sleep in milliseconds
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(JsValue)` if the timeout fails to set
+pub async fn sleep(ms: u32) -> Result<(), JsValue> {
+    let scope = GlobalScope::current();
+    let mut scheduled = Ok(());
+    let promise = Promise::new(&mut |resolve, _| {
+        scheduled = scope.set_timeout(&resolve, ms as i32);
+    });
+    scheduled?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Check whether the module is running inside a Web Worker.
+///
+/// # Example
+/// ```javascript
+/// if (is_worker()) {
+///     console.log("Control loop running off the UI thread");
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn is_worker() -> bool {
+    GlobalScope::current().is_worker()
+}
+
+// ============================================================================
+// WASM Entry Point
+// ============================================================================
+
+/// WASM module entry point. Called automatically when the module loads.
+///
+/// Initializes panic hooks for better error messages. The module never
+/// touches the DOM, so it loads the same on a page and in a Web Worker.
+/// Pages enable their own UI once `init()` resolves.
+#[wasm_bindgen(start)]
+pub fn main_js() -> Result<(), JsValue> {
+    #[cfg(debug_assertions)]
+    console_error_panic_hook::set_once();
+
+    console::log_1(&JsValue::from_str("Reachy Mini WASM module loaded"));
+
+    Ok(())
+}
+
+// ============================================================================
+// Connection Management
+// ============================================================================
+
+/// Connect to the Reachy Mini robot.
+///
+/// With the default transport, attempts to establish a connection in the
+/// following order:
+/// 1. WebSocket connection to `ws://localhost:8000/api/move/ws/raw/write`
+/// 2. WebSerial connection (prompts user to select a serial port)
+///
+/// # Arguments
+/// * `config` - Robot profile: `"lite"` (default), `"wireless"` or a JSON
+///   `RobotConfig`, whose `transport` overrides the order above
+///
+/// # Returns
+/// * `Ok(true)` - Successfully connected
+/// * `Err(JsValue)` - Invalid configuration or connection failed
+///
+/// # Example
+/// ```javascript
+/// await connect();
+/// await connect("wireless");
+/// ```
+#[wasm_bindgen]
+pub async fn connect(config: Option<String>) -> Result<bool, JsValue> {
+    default_robot().connect(config).await
+}
+
+/// Connect to a simulated Reachy Mini.
+///
+/// The simulator models the XL330 motors of the profile in-process (control
+/// table, status packets, torque and position tracking), so every API works
+/// without a robot or browser serial port.
+///
+/// # Arguments
+/// * `config` - Robot profile: `"lite"` (default), `"wireless"` or a JSON
+///   `RobotConfig`
+///
+/// # Example
+/// ```javascript
+/// await connect_simulated();
+/// await enable_torque();
+/// await set_head_pose(0, 0, 10, 0, 0, 0);
+/// ```
+#[wasm_bindgen]
+pub async fn connect_simulated(config: Option<String>) -> Result<bool, JsValue> {
+    default_robot().connect_simulated(config).await
+}
+
+/// Load a robot profile without connecting.
+///
+/// Replaces the joint table (IDs, limits, directions, offsets), the kinematic
+/// geometry and the default transport.
+///
+/// # Arguments
+/// * `config` - `"lite"`, `"wireless"` or a JSON `RobotConfig`
+///
+/// # Errors
+/// * Returns error if the profile is unknown or the JSON is invalid
+#[wasm_bindgen]
+pub fn load_config(config: String) -> Result<(), JsValue> {
+    default_robot().load_config(config)
+}
+
+/// Get the active robot configuration as JSON.
+///
+/// # Example
+/// ```javascript
+/// const config = JSON.parse(get_config());
+/// config.joints.find(j => j.name === "l_antenna").limits_deg = [-90, 90];
+/// load_config(JSON.stringify(config));
+/// ```
+#[wasm_bindgen]
+pub fn get_config() -> String {
+    default_robot().get_config()
+}
+
+/// Replace the kinematic geometry used for head poses, e.g. for a hardware
+/// revision with other arm lengths, keeping joints and limits.
+///
+/// # Arguments
+/// * `json` - A `Geometry` object: `motor_arm_length` and `rod_length`
+///   (metres), `head_z_offset_mm`, 6 `branches` and optional `interference`
+///
+/// # Errors
+/// * Returns error if the JSON is invalid, there is not one branch per head
+///   joint, or the neutral pose is unreachable with this geometry
+///
+/// # Example
+/// ```javascript
+/// const geometry = JSON.parse(get_kinematic_model());
+/// geometry.motor_arm_length = 0.04;
+/// set_kinematic_model(JSON.stringify(geometry));
+/// ```
+#[wasm_bindgen]
+pub fn set_kinematic_model(json: String) -> Result<(), JsValue> {
+    default_robot().set_kinematic_model(json)
+}
+
+/// Get the active kinematic geometry as JSON.
+#[wasm_bindgen]
+pub fn get_kinematic_model() -> String {
+    default_robot().get_kinematic_model()
+}
+
+/// Connect through the daemon's high-level JSON API.
+///
+/// Use this when the daemon does not expose raw bus access. Head pose, antenna
+/// and global torque calls are mapped onto the daemon endpoints; calls that need
+/// raw Dynamixel access (joints, diagnostics, reboot) return an error.
+///
+/// # Arguments
+/// * `url` - Daemon base URL (default `http://localhost:8000`)
+///
+/// # Example
+/// ```javascript
+/// await connect_daemon("http://reachy-mini.local:8000");
+/// await set_head_pose(0, 0, 10, 0, 0, 0);
+/// ```
+#[wasm_bindgen]
+pub async fn connect_daemon(url: Option<String>) -> Result<bool, JsValue> {
+    default_robot().connect_daemon(url).await
+}
+
+/// Disconnect from the Reachy Mini robot.
+///
+/// Releases all locks and closes the connection.
+///
+/// # Returns
+/// * `Ok(())` on success
+#[wasm_bindgen]
+pub async fn disconnect() -> Result<(), JsValue> {
+    default_robot().disconnect().await
+}
+
+/// Check if currently connected to the robot.
+///
+/// # Returns
+/// * `true` if connected
+/// * `false` if not connected
+#[wasm_bindgen]
+pub fn is_connected() -> bool {
+    default_robot().is_connected()
+}
+
+// ============================================================================
+// Head Pose API (Cartesian Space)
+// ============================================================================
+
+/// Get the current head pose in Cartesian coordinates.
+///
+/// Returns the current position and orientation of the head end-effector
+/// computed via forward kinematics from the current motor positions.
+///
+/// # Returns
+/// A vector of 7 floats: `[x, y, z, roll, pitch, yaw, body_yaw]`
+/// - `x`, `y`, `z`: Position in millimeters
+/// - `roll`, `pitch`, `yaw`: Orientation in degrees, in the world frame:
+///   roll about the world X axis, then pitch about Y, then yaw about Z
+///   (`R = Rz(yaw) · Ry(pitch) · Rx(roll)`); roll and yaw are in
+///   [-180, 180], pitch in [-90, 90]
+/// - `body_yaw`: Body rotation in degrees (0 without a body yaw motor)
+///
+/// # Errors
+/// * Returns error if not connected to the robot
+/// * Returns error if communication fails
+///
+/// # Example
+/// ```javascript
+/// const pose = await get_head_pose();
+/// console.log(`Position: (${pose[0]}, ${pose[1]}, ${pose[2]}) mm`);
+/// console.log(`Orientation: (${pose[3]}, ${pose[4]}, ${pose[5]}) deg`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_head_pose() -> Result<Vec<f32>, JsValue> {
+    default_robot().get_head_pose().await
+}
+
+/// Set the head pose in Cartesian coordinates.
+///
+/// Computes inverse kinematics and commands the head motors to achieve
+/// the specified pose.
+///
+/// # Arguments
+/// * `x` - X position in millimeters
+/// * `y` - Y position in millimeters
+/// * `z` - Z position in millimeters (0 = minimum height)
+/// * `roll` - Roll angle in degrees
+/// * `pitch` - Pitch angle in degrees
+/// * `yaw` - Yaw angle in degrees
+/// * `body_yaw` - Body rotation in degrees (default 0); the body follows a
+///   head yawed further than `set_body_yaw_limits()` allows
+///
+/// # Errors
+/// * Returns error if not connected
+/// * Returns error for a non-zero body yaw on a robot without a body yaw motor
+/// * Returns error naming the head joints that cannot reach the pose; nothing
+///   is sent. See `project_head_pose()` to move to the closest reachable pose
+/// * Returns error if the mechanism would collide (arm on the base, rod past
+///   its ball joint range, head on the body)
+/// * Poses near a singularity log a console warning, or return an error if
+///   `ReachyMini.set_singularity_policy("refuse")` was set
+///
+/// # Example
+/// ```javascript
+/// // Move head to center position, looking straight
+/// await set_head_pose(0, 0, 50, 0, 0, 0);
+/// // Look 30° left with the body turned 20° (wireless version)
+/// await set_head_pose(0, 0, 0, 0, 0, 30, 20);
+/// ```
+#[wasm_bindgen]
+pub async fn set_head_pose(
+    x: f32,
+    y: f32,
+    z: f32,
+    roll: f32,
+    pitch: f32,
+    yaw: f32,
+    body_yaw: Option<f32>,
+) -> Result<(), JsValue> {
+    default_robot()
+        .set_head_pose(x, y, z, roll, pitch, yaw, body_yaw)
+        .await
+}
+
+/// Get the current head pose written as a quaternion, matrix or axis-angle.
+///
+/// # Arguments
+/// * `format` - One of:
+///   - `"xyzrpy"`: `[x, y, z, roll, pitch, yaw]`, as `get_head_pose()`
+///   - `"quaternion"`: `[x, y, z, qx, qy, qz, qw]`, scalar last, `qw >= 0`
+///   - `"matrix"`: the 4x4 homogeneous transform, 16 values row by row
+///   - `"axis_angle"`: `[x, y, z, ax, ay, az, angle]`, a unit axis and an
+///     angle in [0, 180] degrees
+///
+/// Positions are in millimeters with `z = 0` at the neutral head height,
+/// in every format, and orientations are in the world frame.
+///
+/// # Returns
+/// The pose values; passed to `set_head_pose_as()` with the same format
+/// they give the same pose. The body yaw is not included.
+///
+/// # Errors
+/// * Returns error for an unknown format
+/// * Returns error if not connected or communication fails
+///
+/// # Example
+/// ```javascript
+/// const [x, y, z, qx, qy, qz, qw] = await get_head_pose_as("quaternion");
+/// const matrix = await get_head_pose_as("matrix");
+/// ```
+#[wasm_bindgen]
+pub async fn get_head_pose_as(format: String) -> Result<Vec<f32>, JsValue> {
+    default_robot().get_head_pose_as(format).await
+}
+
+/// Set the head pose written as a quaternion, matrix or axis-angle.
+///
+/// # Arguments
+/// * `format` - `"xyzrpy"`, `"quaternion"`, `"matrix"` or `"axis_angle"`,
+///   see `get_head_pose_as()`
+/// * `values` - The pose; quaternions and axes need not have unit length
+/// * `body_yaw` - Body rotation in degrees (default 0)
+///
+/// # Errors
+/// * Returns error for an unknown format, the wrong number of values, a zero
+///   quaternion or axis, or a matrix that is not a rigid transform
+/// * Otherwise as `set_head_pose()`
+///
+/// # Example
+/// ```javascript
+/// // Look 30° left: a rotation about the vertical axis
+/// await set_head_pose_as("axis_angle", [0, 0, 0, 0, 0, 1, 30]);
+/// ```
+#[wasm_bindgen]
+pub async fn set_head_pose_as(
+    format: String,
+    values: Vec<f32>,
+    body_yaw: Option<f32>,
+) -> Result<(), JsValue> {
+    default_robot()
+        .set_head_pose_as(format, values, body_yaw)
+        .await
+}
+
+/// Rewrite a head pose from one format to another without moving the robot.
+///
+/// # Arguments
+/// * `values` - The pose in `from` format
+/// * `from`, `to` - Formats, see `get_head_pose_as()`
+///
+/// # Errors
+/// * Returns error for an unknown format or invalid pose values
+///
+/// # Example
+/// ```javascript
+/// const rpy = convert_head_pose([0, 0, 10, 0, 0, 0, 1], "quaternion", "xyzrpy");
+/// ```
+#[wasm_bindgen]
+pub fn convert_head_pose(values: Vec<f32>, from: String, to: String) -> Result<Vec<f32>, JsValue> {
+    default_robot().convert_head_pose(values, from, to)
+}
+
+/// Set how far the body turns with head poses (wireless version).
+///
+/// # Arguments
+/// * `max_relative_yaw_deg` - Largest head yaw relative to the body (default
+///   65); the body follows a head turned further
+/// * `max_body_yaw_deg` - Largest body yaw (default 160)
+///
+/// # Errors
+/// * Returns error if a limit is not positive
+#[wasm_bindgen]
+pub fn set_body_yaw_limits(
+    max_relative_yaw_deg: f32,
+    max_body_yaw_deg: f32,
+) -> Result<(), JsValue> {
+    default_robot().set_body_yaw_limits(max_relative_yaw_deg, max_body_yaw_deg)
+}
+
+// ============================================================================
+// Joint Position API (Joint Space)
+// ============================================================================
+
+/// Get current positions of all head joints.
+///
+/// Returns the raw joint angles for all 6 head motors (11-16).
+///
+/// # Returns
+/// A vector of 6 floats representing joint angles in degrees.
+///
+/// # Example
+/// ```javascript
+/// const joints = await get_head_joints();
+/// console.log(`Joint angles: ${joints.map(j => j.toFixed(2)).join(', ')} deg`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_head_joints() -> Result<Vec<f32>, JsValue> {
+    default_robot().get_head_joints().await
+}
+
+/// Set positions of all head joints.
+///
+/// Directly commands all 6 head motors to the specified angles.
+///
+/// # Arguments
+/// * `angles_deg` - Vector of 6 joint angles in degrees
+///
+/// # Errors
+/// * Returns error if `angles_deg` length is not 6
+/// * Returns error if not connected
+///
+/// # Example
+/// ```javascript
+/// // Set all head joints to zero position
+/// await set_head_joints([0, 0, 0, 0, 0, 0]);
+/// ```
+#[wasm_bindgen]
+pub async fn set_head_joints(angles_deg: Vec<f32>) -> Result<(), JsValue> {
+    default_robot().set_head_joints(angles_deg).await
+}
+
+/// Get positions of all motors (head + antennas).
+///
+/// Returns joint angles for all 8 motors (11-18).
+///
+/// # Returns
+/// A vector of 8 floats representing joint angles in degrees:
+/// - Index 0-5: Head motors (11-16)
+/// - Index 6: Left antenna (17)
+/// - Index 7: Right antenna (18)
+///
+/// # Example
+/// ```javascript
+/// const allJoints = await get_all_joints();
+/// const headJoints = allJoints.slice(0, 6);
+/// const leftAntenna = allJoints[6];
+/// const rightAntenna = allJoints[7];
+/// ```
+#[wasm_bindgen]
+pub async fn get_all_joints() -> Result<Vec<f32>, JsValue> {
+    default_robot().get_all_joints().await
+}
+
+/// Set positions of all motors (head + antennas).
+///
+/// Directly commands all 8 motors to the specified angles.
+///
+/// # Arguments
+/// * `angles_deg` - Vector of 8 joint angles in degrees
+///
+/// # Errors
+/// * Returns error if `angles_deg` length is not 8
+///
+/// # Example
+/// ```javascript
+/// // Set all joints including antennas
+/// await set_all_joints([0, 0, 0, 0, 0, 0, 45, -45]);
+/// ```
+#[wasm_bindgen]
+pub async fn set_all_joints(angles_deg: Vec<f32>) -> Result<(), JsValue> {
+    default_robot().set_all_joints(angles_deg).await
+}
+
+// ============================================================================
+// Named Joints API
+// ============================================================================
+
+/// Get the angle of one joint by name.
+///
+/// # Arguments
+/// * `name` - Joint name (`stewart_1` ... `stewart_6`, `l_antenna`, `r_antenna`)
+///
+/// # Returns
+/// Joint angle in degrees
+///
+/// # Errors
+/// * Returns error if no joint has this name
+///
+/// # Example
+/// ```javascript
+/// const angle = await get_joint("l_antenna");
+/// ```
+#[wasm_bindgen]
+pub async fn get_joint(name: String) -> Result<f32, JsValue> {
+    default_robot().get_joint(name).await
+}
+
+/// Get the angles of all joints keyed by name.
+///
+/// # Returns
+/// Object mapping each joint name to its angle in degrees
+///
+/// # Example
+/// ```javascript
+/// const joints = await get_joints();
+/// console.log(joints.stewart_1, joints.r_antenna);
+/// ```
+#[wasm_bindgen]
+pub async fn get_joints() -> Result<JsValue, JsValue> {
+    default_robot().get_joints().await
+}
+
+/// Command any subset of joints by name.
+///
+/// All targets are sent in one sync write. Nothing moves if a name is unknown
+/// or an angle exceeds its joint limits.
+///
+/// # Arguments
+/// * `targets` - Object mapping joint names to angles in degrees
+///
+/// # Example
+/// ```javascript
+/// await set_joints({ l_antenna: 30, r_antenna: -30 });
+/// ```
+#[wasm_bindgen]
+pub async fn set_joints(targets: JsValue) -> Result<(), JsValue> {
+    default_robot().set_joints(targets).await
+}
+
+// ============================================================================
+// Antenna API
+// ============================================================================
+
+/// Get the current position of the left antenna.
+///
+/// # Returns
+/// Antenna angle in degrees
+///
+/// # Example
+/// ```javascript
+/// const leftAngle = await get_left_antenna();
+/// ```
+#[wasm_bindgen]
+pub async fn get_left_antenna() -> Result<f32, JsValue> {
+    default_robot().get_left_antenna().await
+}
+
+/// Set the position of the left antenna.
+///
+/// # Arguments
+/// * `angle_deg` - Target angle in degrees
+///
+/// # Example
+/// ```javascript
+/// await set_left_antenna(45);  // Raise left antenna
+/// ```
+#[wasm_bindgen]
+pub async fn set_left_antenna(angle_deg: f32) -> Result<(), JsValue> {
+    default_robot().set_left_antenna(angle_deg).await
+}
+
+/// Get the current position of the right antenna.
+///
+/// # Returns
+/// Antenna angle in degrees
+///
+/// # Example
+/// ```javascript
+/// const rightAngle = await get_right_antenna();
+/// ```
+#[wasm_bindgen]
+pub async fn get_right_antenna() -> Result<f32, JsValue> {
+    default_robot().get_right_antenna().await
+}
+
+/// Set the position of the right antenna.
+///
+/// # Arguments
+/// * `angle_deg` - Target angle in degrees
+///
+/// # Example
+/// ```javascript
+/// await set_right_antenna(-45);  // Raise right antenna
+/// ```
+#[wasm_bindgen]
+pub async fn set_right_antenna(angle_deg: f32) -> Result<(), JsValue> {
+    default_robot().set_right_antenna(angle_deg).await
+}
+
+/// Get positions of both antennas.
+///
+/// # Returns
+/// Vector of 2 floats: `[left_angle, right_angle]` in degrees
+///
+/// # Example
+/// ```javascript
+/// const [left, right] = await get_antennas();
+/// ```
+#[wasm_bindgen]
+pub async fn get_antennas() -> Result<Vec<f32>, JsValue> {
+    default_robot().get_antennas().await
+}
+
+/// Set positions of both antennas.
+///
+/// # Arguments
+/// * `left_deg` - Left antenna angle in degrees
+/// * `right_deg` - Right antenna angle in degrees
+///
+/// # Example
+/// ```javascript
+/// await set_antennas(45, -45);  // Both antennas up (mirrored)
+/// ```
+#[wasm_bindgen]
+pub async fn set_antennas(left_deg: f32, right_deg: f32) -> Result<(), JsValue> {
+    default_robot().set_antennas(left_deg, right_deg).await
+}
+
+// ============================================================================
+// Torque Control API
+// ============================================================================
+
+/// Enable torque on all motors.
+///
+/// When torque is enabled, motors will actively hold their position
+/// and resist external forces.
+///
+/// # Example
+/// ```javascript
+/// await enable_torque();
+/// await set_head_pose(0, 0, 50, 0, 0, 0);  // Move head
+/// ```
+#[wasm_bindgen]
+pub async fn enable_torque() -> Result<(), JsValue> {
+    default_robot().enable_torque().await
+}
+
+/// Disable torque on all motors.
+///
+/// When torque is disabled, motors are free to move and the robot
+/// can be manually positioned (compliant mode).
+///
+/// # Safety
+/// The head may drop when torque is disabled. Ensure the robot
+/// is in a safe position before calling this function.
+///
+/// # Example
+/// ```javascript
+/// await disable_torque();  // Enable manual positioning
+/// ```
+#[wasm_bindgen]
+pub async fn disable_torque() -> Result<(), JsValue> {
+    default_robot().disable_torque().await
+}
+
+/// Enable torque on head motors only (11-16).
+///
+/// Antennas remain in their current torque state.
+#[wasm_bindgen]
+pub async fn enable_head_torque() -> Result<(), JsValue> {
+    default_robot().enable_head_torque().await
+}
+
+/// Disable torque on head motors only (11-16).
+///
+/// Antennas remain in their current torque state.
+#[wasm_bindgen]
+pub async fn disable_head_torque() -> Result<(), JsValue> {
+    default_robot().disable_head_torque().await
+}
+
+/// Enable torque on antenna motors only (17-18).
+#[wasm_bindgen]
+pub async fn enable_antenna_torque() -> Result<(), JsValue> {
+    default_robot().enable_antenna_torque().await
+}
+
+/// Disable torque on antenna motors only (17-18).
+#[wasm_bindgen]
+pub async fn disable_antenna_torque() -> Result<(), JsValue> {
+    default_robot().disable_antenna_torque().await
+}
+
+/// Enable torque on left antenna motor only (17).
+#[wasm_bindgen]
+pub async fn enable_left_antenna_torque() -> Result<(), JsValue> {
+    default_robot().enable_left_antenna_torque().await
+}
+
+/// Disable torque on left antenna motor only (17).
+#[wasm_bindgen]
+pub async fn disable_left_antenna_torque() -> Result<(), JsValue> {
+    default_robot().disable_left_antenna_torque().await
+}
+
+/// Enable torque on right antenna motor only (18).
+#[wasm_bindgen]
+pub async fn enable_right_antenna_torque() -> Result<(), JsValue> {
+    default_robot().enable_right_antenna_torque().await
+}
+
+/// Disable torque on right antenna motor only (18).
+#[wasm_bindgen]
+pub async fn disable_right_antenna_torque() -> Result<(), JsValue> {
+    default_robot().disable_right_antenna_torque().await
+}
+
+// ============================================================================
+// Motor Diagnostics API
+// ============================================================================
+
+/// Get the temperature of a specific motor.
+///
+/// # Arguments
+/// * `motor_id` - Motor ID (11-18)
+///
+/// # Returns
+/// Temperature in degrees Celsius
+///
+/// # Example
+/// ```javascript
+/// const temp = await get_motor_temperature(11);
+/// console.log(`Motor 11 temperature: ${temp}°C`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_motor_temperature(motor_id: u8) -> Result<u8, JsValue> {
+    default_robot().get_motor_temperature(motor_id).await
+}
+
+/// Get the current load of a specific motor.
+///
+/// Load represents the percentage of maximum torque currently being applied.
+///
+/// # Arguments
+/// * `motor_id` - Motor ID (11-18)
+///
+/// # Returns
+/// Load value from -1000 to 1000 (percentage of max torque × 10)
+/// - Positive values: Counter-clockwise load
+/// - Negative values: Clockwise load
+///
+/// # Example
+/// ```javascript
+/// const load = await get_motor_load(11);
+/// console.log(`Motor 11 load: ${load / 10}%`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_motor_load(motor_id: u8) -> Result<i16, JsValue> {
+    default_robot().get_motor_load(motor_id).await
+}
+
+/// Get temperatures of all motors using bulk read.
+///
+/// Uses resilient parsing - missing motor responses don't affect others.
+///
+/// # Returns
+/// Vector of 8 temperatures in °C for motors 11-18 (0 if motor didn't respond)
+///
+/// # Example
+/// ```javascript
+/// const temps = await get_all_motor_temperatures();
+/// temps.forEach((t, i) => console.log(`Motor ${11 + i}: ${t}°C`));
+/// ```
+#[wasm_bindgen]
+pub async fn get_all_motor_temperatures() -> Result<Vec<u8>, JsValue> {
+    default_robot().get_all_motor_temperatures().await
+}
+
+/// Get loads of all motors using bulk read.
+///
+/// Uses resilient parsing - missing motor responses don't affect others.
+///
+/// # Returns
+/// Vector of 8 load values for motors 11-18 (0 if motor didn't respond)
+///
+/// # Example
+/// ```javascript
+/// const loads = await get_all_motor_loads();
+/// loads.forEach((l, i) => console.log(`Motor ${11 + i}: ${l / 10}%`));
+/// ```
+#[wasm_bindgen]
+pub async fn get_all_motor_loads() -> Result<Vec<i16>, JsValue> {
+    default_robot().get_all_motor_loads().await
+}
+
+/// Get temperatures of head motors (11-16) using bulk read.
+///
+/// # Returns
+/// Vector of 6 temperatures in °C (0 if motor didn't respond)
+#[wasm_bindgen]
+pub async fn get_head_motor_temperatures() -> Result<Vec<u8>, JsValue> {
+    default_robot().get_head_motor_temperatures().await
+}
+
+/// Get loads of head motors (11-16) using bulk read.
+///
+/// # Returns
+/// Vector of 6 load values (0 if motor didn't respond)
+#[wasm_bindgen]
+pub async fn get_head_motor_loads() -> Result<Vec<i16>, JsValue> {
+    default_robot().get_head_motor_loads().await
+}
+
+/// Get the temperature of the left antenna motor (17).
+///
+/// # Returns
+/// Temperature in degrees Celsius
+#[wasm_bindgen]
+pub async fn get_left_antenna_temperature() -> Result<u8, JsValue> {
+    default_robot().get_left_antenna_temperature().await
+}
+
+/// Get the temperature of the right antenna motor (18).
+///
+/// # Returns
+/// Temperature in degrees Celsius
+#[wasm_bindgen]
+pub async fn get_right_antenna_temperature() -> Result<u8, JsValue> {
+    default_robot().get_right_antenna_temperature().await
+}
+
+/// Get temperatures of both antenna motors using bulk read.
+///
+/// # Returns
+/// Vector of 2 temperatures: `[left_temp, right_temp]` in °C (0 if motor didn't respond)
+#[wasm_bindgen]
+pub async fn get_antenna_temperatures() -> Result<Vec<u8>, JsValue> {
+    default_robot().get_antenna_temperatures().await
+}
+
+/// Get the load of the left antenna motor (17).
+///
+/// # Returns
+/// Load value from -1000 to 1000
+#[wasm_bindgen]
+pub async fn get_left_antenna_load() -> Result<i16, JsValue> {
+    default_robot().get_left_antenna_load().await
+}
+
+/// Get the load of the right antenna motor (18).
+///
+/// # Returns
+/// Load value from -1000 to 1000
+#[wasm_bindgen]
+pub async fn get_right_antenna_load() -> Result<i16, JsValue> {
+    default_robot().get_right_antenna_load().await
+}
+
+/// Get loads of both antenna motors using bulk read.
+///
+/// # Returns
+/// Vector of 2 loads: `[left_load, right_load]` (0 if motor didn't respond)
+#[wasm_bindgen]
+pub async fn get_antenna_loads() -> Result<Vec<i16>, JsValue> {
+    default_robot().get_antenna_loads().await
+}
+
+// ============================================================================
+// Motor Reboot API
+// ============================================================================
+
+/// Reboot a specific motor by ID.
+///
+/// This clears any hardware error status and reinitializes the motor.
+/// The motor will be unresponsive for approximately 500ms after reboot.
+///
+/// # Arguments
+/// * `motor_id` - Motor ID (11-18)
+///
+/// # Example
+/// ```javascript
+/// await reboot_motor(11);
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_motor(motor_id: u8) -> Result<(), JsValue> {
+    default_robot().reboot_motor(motor_id).await
+}
+
+/// Reboot the left antenna motor (17).
+///
+/// # Example
+/// ```javascript
+/// await reboot_left_antenna();
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_left_antenna() -> Result<(), JsValue> {
+    default_robot().reboot_left_antenna().await
+}
+
+/// Reboot the right antenna motor (18).
+///
+/// # Example
+/// ```javascript
+/// await reboot_right_antenna();
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_right_antenna() -> Result<(), JsValue> {
+    default_robot().reboot_right_antenna().await
+}
+
+/// Reboot both antenna motors.
+///
+/// # Example
+/// ```javascript
+/// await reboot_antennas();
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_antennas() -> Result<(), JsValue> {
+    default_robot().reboot_antennas().await
+}
+
+/// Reboot all head motors (11-16).
+///
+/// Reboots each head motor sequentially with appropriate delays.
+///
+/// # Warning
+/// This operation takes approximately 3 seconds.
+///
+/// # Example
+/// ```javascript
+/// await reboot_head_motors();
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_head_motors() -> Result<(), JsValue> {
+    default_robot().reboot_head_motors().await
+}
+
+/// Reboot all motors (head + antennas).
+///
+/// Reboots each motor sequentially with appropriate delays.
+/// This is useful for clearing hardware errors on all motors.
+///
+/// # Warning
+/// This operation takes approximately 4 seconds (500ms per motor × 8 motors).
+///
+/// # Example
+/// ```javascript
+/// console.log('Rebooting all motors...');
+/// await reboot_all_motors();
+/// console.log('All motors rebooted');
+/// ```
+#[wasm_bindgen]
+pub async fn reboot_all_motors() -> Result<(), JsValue> {
+    default_robot().reboot_all_motors().await
+}
+
+// ============================================================================
+// Typed State API
+// ============================================================================
+
+/// Get the current head pose as a `HeadPose` object.
+///
+/// Typed counterpart of `get_head_pose()`.
+///
+/// # Example
+/// ```javascript
+/// const pose = await get_pose();
+/// console.log(`Head at z=${pose.z} mm, yaw=${pose.yaw}°`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_pose() -> Result<HeadPose, JsValue> {
+    default_robot().get_pose().await
+}
+
+/// Get the current head pose with forward kinematics solver details.
+///
+/// The solver iterates until the rod length error is below tolerance,
+/// starting from the previous pose, so polling usually takes one step.
+///
+/// # Example
+/// ```javascript
+/// const { pose, residual_mm, iterations, converged } = await get_head_pose_solution();
+/// if (!converged) console.warn(`FK residual ${residual_mm} mm after ${iterations} steps`);
+/// ```
+#[wasm_bindgen]
+pub async fn get_head_pose_solution() -> Result<HeadPoseSolution, JsValue> {
+    default_robot().get_head_pose_solution().await
+}
+
+/// Move the head to a `HeadPose`.
+///
+/// Typed counterpart of `set_head_pose()`.
+///
+/// # Example
+/// ```javascript
+/// await set_pose(new HeadPose(0, 0, 20, 0, 10, 0));
+/// ```
+#[wasm_bindgen]
+pub async fn set_pose(pose: &HeadPose) -> Result<(), JsValue> {
+    default_robot().set_pose(pose).await
+}
+
+/// Get all joint angles as a `JointState` object.
+///
+/// Typed counterpart of `get_all_joints()`.
+///
+/// # Example
+/// ```javascript
+/// const joints = await get_joint_state();
+/// console.log(joints.head, joints.antennas.left, joints.antennas.right);
+/// ```
+#[wasm_bindgen]
+pub async fn get_joint_state() -> Result<JointState, JsValue> {
+    default_robot().get_joint_state().await
+}
+
+/// Command all joints from a `JointState`.
+///
+/// Typed counterpart of `set_all_joints()`.
+///
+/// # Example
+/// ```javascript
+/// const antennas = new AntennaState(45, -45);
+/// await set_joint_state(new JointState([0, 0, 0, 0, 0, 0], antennas));
+/// ```
+#[wasm_bindgen]
+pub async fn set_joint_state(joints: &JointState) -> Result<(), JsValue> {
+    default_robot().set_joint_state(joints).await
+}
+
+/// Get both antenna angles as an `AntennaState` object.
+///
+/// Typed counterpart of `get_antennas()`.
+///
+/// # Example
+/// ```javascript
+/// const { left, right } = await get_antenna_state();
+/// ```
+#[wasm_bindgen]
+pub async fn get_antenna_state() -> Result<AntennaState, JsValue> {
+    default_robot().get_antenna_state().await
+}
+
+/// Move both antennas from an `AntennaState`.
+///
+/// Typed counterpart of `set_antennas()`.
+///
+/// # Example
+/// ```javascript
+/// await set_antenna_state(new AntennaState(30, -30));
+/// ```
+#[wasm_bindgen]
+pub async fn set_antenna_state(antennas: &AntennaState) -> Result<(), JsValue> {
+    default_robot().set_antenna_state(antennas).await
+}
+
+/// Get temperature and load of every motor.
+///
+/// # Returns
+/// One `MotorDiagnostics` per motor (11-18) with `name`, `id`, `temperature` (°C),
+/// `load` (-1000 to 1000) and `responding`.
+///
+/// # Example
+/// ```javascript
+/// for (const m of await get_motor_diagnostics()) {
+///     if (!m.responding) console.warn(`Motor ${m.id} not responding`);
+///     else if (m.temperature > 60) console.warn(`Motor ${m.id} hot: ${m.temperature}°C`);
+/// }
+/// ```
+#[wasm_bindgen]
+pub async fn get_motor_diagnostics() -> Result<Vec<MotorDiagnostics>, JsValue> {
+    default_robot().get_motor_diagnostics().await
+}
+
+/// Get temperature and load of every motor keyed by joint name.
+///
+/// # Returns
+/// Object mapping each joint name to its `MotorDiagnostics`
+///
+/// # Example
+/// ```javascript
+/// const diagnostics = await get_joint_diagnostics();
+/// console.log(diagnostics.l_antenna.temperature);
+/// ```
+#[wasm_bindgen]
+pub async fn get_joint_diagnostics() -> Result<JsValue, JsValue> {
+    default_robot().get_joint_diagnostics().await
+}
+
+// ============================================================================
+// Kinematics Utilities (Pure Functions - No Hardware Access)
+// ============================================================================
+
+/// Compute forward kinematics from joint angles.
+///
+/// This is a pure computation function that does not communicate with hardware.
+/// Use this for trajectory planning or simulation.
+///
+/// # Arguments
+/// * `angles_deg` - Vector of 6 joint angles in degrees (or 8 if including antennas)
+///
+/// # Returns
+/// Vector of 6 floats: `[x, y, z, roll, pitch, yaw]`
+/// - Position in mm, orientation in degrees
+///
+/// # Example
+/// ```javascript
+/// const pose = forward_kinematics([0, 0, 0, 0, 0, 0]);
+/// console.log(`At zero position, head is at: ${pose}`);
+/// ```
+#[wasm_bindgen]
+pub fn forward_kinematics(angles_deg: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+    default_robot().forward_kinematics(angles_deg)
+}
+
+/// Compute inverse kinematics from Cartesian pose.
+///
+/// This is a pure computation function that does not communicate with hardware.
+/// Use this for trajectory planning or to preview joint angles before sending.
+///
+/// # Arguments
+/// * `xyzrpy` - Vector of 6 floats: `[x, y, z, roll, pitch, yaw]`
+///   - Position in mm, orientation in degrees (world frame)
+///   - An optional 7th value is the body yaw in degrees, so the output of
+///     `get_head_pose()` can be passed as is; the head joints are then
+///     solved with the body turned that far
+///
+/// # Returns
+/// Vector of 6 head joint angles in degrees
+///
+/// # Errors
+/// Returns error if the pose is unreachable
+///
+/// # Example
+/// ```javascript
+/// const joints = inverse_kinematics([0, 0, 50, 0, 15, 0]);
+/// console.log(`To look up 15°, set joints to: ${joints}`);
+/// ```
+#[wasm_bindgen]
+pub fn inverse_kinematics(xyzrpy: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+    default_robot().inverse_kinematics(xyzrpy)
+}
+
+/// Find the reachable head pose closest to a request.
+///
+/// Out-of-reach requests are moved back along the path from the neutral pose
+/// until every rod reaches. No hardware access.
+///
+/// # Returns
+/// `{ pose, translation_mm, rotation_deg, moved }`
+///
+/// # Example
+/// ```javascript
+/// const { pose, moved, translation_mm } = project_head_pose(0, 0, 80, 0, 0, 0);
+/// if (moved) console.log(`Clamped by ${translation_mm.toFixed(1)} mm`);
+/// await set_pose(pose);
+/// ```
+#[wasm_bindgen]
+pub fn project_head_pose(
+    x: f32,
+    y: f32,
+    z: f32,
+    roll: f32,
+    pitch: f32,
+    yaw: f32,
+) -> Result<HeadPoseProjection, JsValue> {
+    default_robot().project_head_pose(x, y, z, roll, pitch, yaw)
+}
+
+/// Velocity kinematics of the head at a pose.
+///
+/// Maps head joint velocities (degrees/s) to a head twist `[vx, vy, vz, wx,
+/// wy, wz]` (mm/s, degrees/s) and back, with the condition number and
+/// manipulability of the pose. No hardware access.
+///
+/// # Arguments
+/// * `xyzrpy` - `[x, y, z, roll, pitch, yaw]` (mm, degrees)
+///
+/// # Errors
+/// Returns error if the pose is unreachable
+///
+/// # Example
+/// ```javascript
+/// const jacobian = head_jacobian([0, 0, 10, 0, 0, 0]);
+/// // Joint speeds to rise at 20 mm/s
+/// const speeds = jacobian.joint_velocities([0, 0, 20, 0, 0, 0]);
+/// console.log(jacobian.condition_number, jacobian.manipulability);
+/// ```
+#[wasm_bindgen]
+pub fn head_jacobian(xyzrpy: Vec<f32>) -> Result<HeadJacobian, JsValue> {
+    default_robot().head_jacobian(xyzrpy)
+}
+
+/// Sample the reachable head workspace.
+///
+/// Every pose of the grid is checked against inverse kinematics and the head
+/// joint limits. No hardware access; fine grids take a while.
+///
+/// # Arguments
+/// * `grid` - JSON `{ axes: [{ min, max, step }, ...] }` for x, y, z (mm),
+///   roll, pitch, yaw (degrees); translations every 5 mm if omitted
+///
+/// # Returns
+/// JSON with the `reachable` poses and, per sampled axis, its `limits` for
+/// every combination of the other axes
+///
+/// # Example
+/// ```javascript
+/// const workspace = JSON.parse(compute_workspace());
+/// const z = workspace.limits.z.find(l => l.at[0] === 0 && l.at[1] === 0);
+/// console.log(`z from ${z.min} to ${z.max} mm above the center`);
+/// ```
+#[wasm_bindgen]
+pub fn compute_workspace(grid: Option<String>) -> Result<String, JsValue> {
+    default_robot().compute_workspace(grid)
+}
+
+// ============================================================================
+// Recording & Playback API
+// ============================================================================
+
+/// Start continuous forward kinematics reading and optionally record.
+///
+/// This function reads motor positions in a loop and updates the UI.
+/// If `duration` is provided, it records frames for that duration.
+///
+/// # Arguments
+/// * `duration` - Optional recording duration in milliseconds.
+///   If `None`, runs indefinitely until `stop()` is called.
+///
+/// # Example
+/// ```javascript
+/// // Start live FK display
+/// start_fk_stream();
+///
+/// // Record for 5 seconds
+/// await start_fk_stream(5000);
+/// ```
+///
+/// # Deprecated
+/// Consider using `get_head_pose()` in a JavaScript loop instead for more control.
+#[wasm_bindgen]
+#[allow(deprecated)]
+#[deprecated(note = "Use get_head_pose() in a JS loop for more control")]
+pub async fn start_fk_stream(duration: Option<f64>) -> Result<(), JsValue> {
+    fk(duration).await
+}
+
+/// Replay recorded motion.
+///
+/// Plays back frames that were recorded during a previous `start_fk_stream(duration)` call.
+/// Automatically enables torque before playback and disables after.
+///
+/// # Example
+/// ```javascript
+/// // Record motion
+/// await start_fk_stream(3000);  // Record for 3 seconds
+///
+/// // Replay it
+/// await replay_recording();
+/// ```
+#[wasm_bindgen]
+pub async fn replay_recording() -> Result<(), JsValue> {
+    default_robot().replay_recording().await
+}
+
+/// Stop any continuous operation (FK stream, replay, etc.).
+///
+/// # Example
+/// ```javascript
+/// start_fk_stream();  // Start streaming
+/// // ... some time later ...
+/// stop();  // Stop streaming
+/// ```
+#[wasm_bindgen]
+pub fn stop() {
+    default_robot().stop()
+}
+
+/// Clear recorded frames.
+#[wasm_bindgen]
+pub fn clear_recording() {
+    default_robot().clear_recording()
+}
+
+/// Get the number of recorded frames.
+#[wasm_bindgen]
+pub fn get_recording_length() -> usize {
+    default_robot().get_recording_length()
+}
+
+// ============================================================================
+// Bus Capture & Replay API
+// ============================================================================
+
+/// Start capturing all bus traffic of the current connection.
+///
+/// Every packet sent and received is recorded with a timestamp until
+/// `stop_capture()` is called. Starting a new capture discards the previous one.
+///
+/// # Example
+/// ```javascript
+/// start_capture();
+/// await get_head_pose();
+/// const capture = stop_capture();  // JSON string, save it to a file
+/// ```
+#[wasm_bindgen]
+pub fn start_capture() -> Result<(), JsValue> {
+    default_robot().start_capture()
+}
+
+/// Stop capturing and return the capture as a portable JSON document.
+///
+/// # Errors
+/// * Returns error if not connected or no capture is running
+#[wasm_bindgen]
+pub fn stop_capture() -> Result<String, JsValue> {
+    default_robot().stop_capture()
+}
+
+/// Connect to a replay of a previously captured session.
+///
+/// Recorded responses are fed back in order, so the same sequence of API calls
+/// reproduces the original session without a robot. Calls that send a different
+/// packet than was recorded fail with a "Replay diverged" error.
+///
+/// # Arguments
+/// * `capture_json` - Capture returned by `stop_capture()`
+///
+/// # Example
+/// ```javascript
+/// await connect_replay(captureJson);
+/// const pose = await get_head_pose();  // same answer as in the field
+/// ```
+#[wasm_bindgen]
+pub async fn connect_replay(capture_json: String) -> Result<bool, JsValue> {
+    default_robot().connect_replay(capture_json).await
+}
+
+/// Get the number of recorded events a replay has not consumed yet.
+///
+/// A regression test can check this is 0 at the end to make sure the replayed
+/// session issued exactly the recorded traffic.
+///
+/// # Errors
+/// * Returns error if not connected to a replay
+#[wasm_bindgen]
+pub fn get_replay_remaining() -> Result<usize, JsValue> {
+    default_robot().get_replay_remaining()
+}
+
+// ============================================================================
+// Connection Statistics API
+// ============================================================================
+
+/// Get bus health statistics since connecting or the last reset.
+///
+/// # Returns
+/// Object with `transactions`, `bytes_tx`, `bytes_rx`, `crc_failures`,
+/// `timeouts`, `retries`, `missing_motors` (count per motor ID),
+/// `latency_ms` (`min`, `max`, `mean` and histogram `buckets`),
+/// `loop_rate_hz` (FK stream / replay iteration rate) and `elapsed_ms`.
+///
+/// # Example
+/// ```javascript
+/// const stats = get_connection_stats();
+/// if (stats.crc_failures > 0 || Object.keys(stats.missing_motors).length) {
+///     console.warn("Bus degrading", stats);
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn get_connection_stats() -> Result<JsValue, JsValue> {
+    default_robot().get_connection_stats()
+}
+
+/// Reset all connection statistics to zero.
+///
+/// # Example
+/// ```javascript
+/// reset_connection_stats();
+/// await sleep(1000);
+/// console.log(get_connection_stats().transactions, "transactions/s");
+/// ```
+#[wasm_bindgen]
+pub fn reset_connection_stats() -> Result<(), JsValue> {
+    default_robot().reset_connection_stats()
+}
+
+// ============================================================================
+// Internal Helper Functions
+// ============================================================================
+
+/// Get the robot instance the free functions operate on.
+fn default_robot() -> Rc<ReachyMini> {
+    DEFAULT_ROBOT.with(Rc::clone)
+}
+
+/// Read motor positions from specified motor IDs.
+///
+/// Uses resilient parsing that scans for packet headers,
+/// so missing motor responses don't affect other results.
+pub(crate) async fn read_motor_positions(
+    port: &GenericPort,
+    motor_ids: &[u8],
+) -> Result<Vec<f32>, JsValue> {
+    let packet = build_sync_current_position(motor_ids);
+    let response = port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?;
+
+    // Parse all valid packets from response
+    let parsed = parse_position_packets(&response);
+
+    // Map results by motor ID, defaulting to 0.0 for missing motors
+    let mut positions = vec![0.0f32; motor_ids.len()];
+    for (id, raw_pos) in parsed {
+        // Find index of this motor in our request
+        if let Some(idx) = motor_ids.iter().position(|&m| m == id) {
+            positions[idx] = raw_to_radians(raw_pos);
+        }
+    }
+
+    Ok(positions)
+}
+
+// ============================================================================
+// Legacy Functions (Deprecated)
+// ============================================================================
+
+/// Legacy forward kinematics loop.
+///
+/// # Deprecated
+/// Use `start_fk_stream()` or `get_head_pose()` instead.
+#[deprecated(note = "Use start_fk_stream() or get_head_pose() instead")]
+pub async fn fk(duration: Option<f64>) -> Result<(), JsValue> {
+    default_robot().start_fk_stream(duration).await
+}
+
+/// Legacy torque on function.
+///
+/// # Deprecated
+/// Use `enable_torque()` instead.
+#[wasm_bindgen]
+#[allow(deprecated)]
+#[deprecated(note = "Use enable_torque() instead")]
+pub async fn torque_on() -> Result<(), JsValue> {
+    enable_torque().await
+}
+
+/// Legacy torque off function.
+///
+/// # Deprecated
+/// Use `disable_torque()` instead.
+#[wasm_bindgen]
+#[allow(deprecated)]
+#[deprecated(note = "Use disable_torque() instead")]
+pub async fn torque_off() -> Result<(), JsValue> {
+    disable_torque().await
+}
+
+/// Legacy replay function.
+///
+/// # Deprecated
+/// Use `replay_recording()` instead.
+#[wasm_bindgen]
+#[allow(deprecated)]
+#[deprecated(note = "Use replay_recording() instead")]
+pub async fn replay() -> Result<(), JsValue> {
+    replay_recording().await
+}
+
+// ============================================================================
+// Connection Infrastructure
+// ============================================================================
+
+/// Generic port wrapper supporting both WebSocket and WebSerial connections.
+///
+/// Every bus access goes through a [`BusScheduler`], so concurrent callers
+/// (e.g. a telemetry poll during `set_head_pose`) are queued instead of
+/// interleaving their packets or failing on a busy lock.
+pub struct GenericPort {
+    connection: Connection,
+    scheduler: BusScheduler,
+    /// Traffic capture, recorded while `Some`
+    capture: RefCell<Option<Capture>>,
+    /// Bus health counters
+    stats: RefCell<ConnectionStats>,
+}
+
+enum Connection {
+    WebSerial {
+        reader: ReadableStreamDefaultReader,
+        writer: WritableStreamDefaultWriter,
+    },
+    WebSocket {
+        sender: Mutex<futures_util::stream::SplitSink<WebSocket, Message>>,
+        receiver: Mutex<futures_util::stream::SplitStream<WebSocket>>,
+    },
+    Simulated {
+        bus: RefCell<SimulatedBus>,
+        /// Status packets produced by the last write, returned by the next read
+        pending: RefCell<Vec<u8>>,
+    },
+    Replay {
+        player: RefCell<ReplayPlayer>,
+    },
+}
+
+impl GenericPort {
+    /// Create a new connection, trying WebSocket first, then WebSerial.
+    pub async fn new() -> Result<Self, JsValue> {
+        let url = "ws://localhost:8000/api/move/ws/raw/write";
+
+        match Self::from_websocket(url).await {
+            Ok(ws) => Ok(ws),
+            Err(_) => {
+                console::log_1(&"WebSocket failed, trying WebSerial".into());
+                Self::from_webserial().await
+            }
+        }
+    }
+
+    /// Wrap a connection with a fresh bus scheduler.
+    fn from_connection(connection: Connection) -> Self {
+        Self {
+            connection,
+            scheduler: BusScheduler::new(),
+            capture: RefCell::new(None),
+            stats: RefCell::new(ConnectionStats::new(js_sys::Date::now())),
+        }
+    }
+
+    /// Open the transport described by a robot configuration.
+    pub async fn open(transport: &Transport, motor_ids: &[u8]) -> Result<Self, JsValue> {
+        match transport {
+            Transport::Auto => Self::new().await,
+            Transport::WebSocket { url } => Self::from_websocket(url).await,
+            Transport::WebSerial => Self::from_webserial().await,
+            Transport::Simulated => Ok(Self::simulated(motor_ids)),
+        }
+    }
+
+    /// Create a port backed by an in-process simulated bus with `motor_ids`.
+    pub fn simulated(motor_ids: &[u8]) -> Self {
+        Self::from_connection(Connection::Simulated {
+            bus: RefCell::new(SimulatedBus::new(motor_ids)),
+            pending: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Create a port that replays a recorded capture instead of talking to a bus.
+    pub fn replay(capture: Capture) -> Self {
+        Self::from_connection(Connection::Replay {
+            player: RefCell::new(ReplayPlayer::new(capture)),
+        })
+    }
+
+    /// Connect via WebSocket.
+    pub async fn from_websocket(url: &str) -> Result<Self, JsValue> {
+        let ws = WebSocket::open(url)
+            .map_err(|e| JsValue::from_str(&format!("WebSocket open failed: {:?}", e)))?;
+
+        // Wait for connection
+        loop {
+            match ws.state() {
+                gloo::net::websocket::State::Connecting => sleep(10).await?,
+                gloo::net::websocket::State::Open => break,
+                _ => return Err(JsValue::from_str("WebSocket connection failed")),
+            }
+        }
+
+        let (sender, receiver) = ws.split();
+        Ok(Self::from_connection(Connection::WebSocket {
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        }))
+    }
+
+    /// Connect via WebSerial.
+    pub async fn from_webserial() -> Result<Self, JsValue> {
+        let port = request_serial_port().await?;
+
+        let readable: ReadableStream =
+            js_sys::Reflect::get(&port, &"readable".into())?.dyn_into()?;
+        let writable: WritableStream =
+            js_sys::Reflect::get(&port, &"writable".into())?.dyn_into()?;
+
+        let reader: ReadableStreamDefaultReader = readable.get_reader().dyn_into()?;
+        let writer: WritableStreamDefaultWriter = writable.get_writer()?.dyn_into()?;
+
+        Ok(Self::from_connection(Connection::WebSerial {
+            reader,
+            writer,
+        }))
+    }
+
+    /// Read data from the connection.
+    pub async fn read(&self) -> Result<Vec<u8>, JsValue> {
+        let _bus = self.scheduler.acquire(Priority::Normal).await;
+        self.recv().await
+    }
+
+    /// Write data to the connection.
+    pub async fn write(&self, packet: &[u8]) -> Result<(), JsValue> {
+        self.write_with_priority(packet, Priority::Normal).await
+    }
+
+    /// Write data to the connection once the scheduler grants the bus.
+    pub async fn write_with_priority(
+        &self,
+        packet: &[u8],
+        priority: Priority,
+    ) -> Result<(), JsValue> {
+        let _bus = self.scheduler.acquire(priority).await;
+        self.send(packet).await
+    }
+
+    /// Write data and read response.
+    pub async fn write_read(&self, packet: &[u8], wait: Option<u32>) -> Result<Vec<u8>, JsValue> {
+        self.write_read_with_priority(packet, wait, Priority::Normal)
+            .await
+    }
+
+    /// Write data and read response as a single bus transaction.
+    ///
+    /// The bus stays reserved from the write until the response is read and
+    /// at least `wait` ms (default `DEFAULT_WAIT_MS`) have passed, so the
+    /// returned bytes always belong to this request. The latency recorded in
+    /// the connection statistics runs from the write to the response, without
+    /// that pause. A request that got no answer at all is resent up to
+    /// `MAX_READ_RETRIES` times.
+    pub async fn write_read_with_priority(
+        &self,
+        packet: &[u8],
+        wait: Option<u32>,
+        priority: Priority,
+    ) -> Result<Vec<u8>, JsValue> {
+        let _bus = self.scheduler.acquire(priority).await;
+        let mut attempt = 0;
+        loop {
+            let (response, latency_ms) =
+                round_trip(self.send(packet), self.recv(), js_sys::Date::now).await?;
+            let pause_ms = wait.unwrap_or(DEFAULT_WAIT_MS) as f64 - latency_ms;
+            if pause_ms > 0.0 {
+                sleep(pause_ms as u32).await?;
+            }
+
+            let timed_out = self
+                .stats
+                .borrow_mut()
+                .record_transaction(packet, &response, latency_ms);
+            if !timed_out || attempt == MAX_READ_RETRIES {
+                return Ok(response);
+            }
+            attempt += 1;
+            self.stats.borrow_mut().record_retry();
+        }
+    }
+
+    /// Release stream locks (for WebSerial cleanup).
+    pub fn release_lock(&self) -> Result<(), JsValue> {
+        if let Connection::WebSerial { reader, writer, .. } = &self.connection {
+            reader.release_lock();
+            writer.release_lock();
+        }
+        Ok(())
+    }
+
+    /// Start recording all bus traffic, discarding any previous capture.
+    pub fn start_capture(&self) {
+        *self.capture.borrow_mut() = Some(Capture::new(js_sys::Date::now()));
+    }
+
+    /// Stop recording and return the capture, if one was running.
+    pub fn stop_capture(&self) -> Option<Capture> {
+        self.capture.borrow_mut().take()
+    }
+
+    /// Serialize the connection statistics as JSON.
+    pub fn stats_json(&self) -> String {
+        self.stats.borrow().to_json(js_sys::Date::now())
+    }
+
+    /// Reset the connection statistics.
+    pub fn reset_stats(&self) {
+        *self.stats.borrow_mut() = ConnectionStats::new(js_sys::Date::now());
+    }
+
+    /// Mark one iteration of a streaming loop (FK stream, replay).
+    pub fn record_loop(&self) {
+        self.stats.borrow_mut().record_loop(js_sys::Date::now());
+    }
+
+    /// Number of recorded events a replay connection has not consumed yet.
+    pub fn replay_remaining(&self) -> Option<usize> {
+        match &self.connection {
+            Connection::Replay { player } => Some(player.borrow().remaining()),
+            _ => None,
+        }
+    }
+
+    /// Append a chunk of traffic to the running capture.
+    fn record(&self, dir: Direction, data: &[u8]) {
+        if let Some(capture) = self.capture.borrow_mut().as_mut() {
+            capture.record(dir, data, js_sys::Date::now());
+        }
+    }
+
+    /// Receive raw bytes. Callers must hold the bus.
+    async fn recv(&self) -> Result<Vec<u8>, JsValue> {
+        let data = self.recv_raw().await?;
+        self.stats.borrow_mut().record_rx(data.len());
+        self.record(Direction::Rx, &data);
+        Ok(data)
+    }
+
+    /// Send raw bytes. Callers must hold the bus.
+    async fn send(&self, packet: &[u8]) -> Result<(), JsValue> {
+        self.send_raw(packet).await?;
+        self.stats.borrow_mut().record_tx(packet.len());
+        self.record(Direction::Tx, packet);
+        Ok(())
+    }
+
+    async fn recv_raw(&self) -> Result<Vec<u8>, JsValue> {
+        match &self.connection {
+            Connection::WebSerial { reader, .. } => {
+                let result = JsFuture::from(reader.read()).await?;
+                let value = js_sys::Reflect::get(&result, &"value".into())?;
+                let data = js_sys::Uint8Array::from(value);
+                Ok(data.to_vec())
+            }
+            Connection::WebSocket { receiver, .. } => {
+                let mut rx = receiver.lock().await;
+
+                if let Some(msg) = rx
+                    .try_next()
+                    .await
+                    .map_err(|e| JsValue::from_str(&format!("Read failed: {:?}", e)))?
+                {
+                    match msg {
+                        Message::Bytes(bytes) => Ok(bytes),
+                        _ => Err(JsValue::from_str("Unexpected message type")),
+                    }
+                } else {
+                    Err(JsValue::from_str("WebSocket closed"))
+                }
+            }
+            Connection::Simulated { bus, pending } => {
+                // Let the motors move during the time the caller waited
+                bus.borrow_mut().advance_to(js_sys::Date::now());
+                Ok(pending.take())
+            }
+            Connection::Replay { player } => Ok(player.borrow_mut().recv()),
+        }
+    }
+
+    async fn send_raw(&self, packet: &[u8]) -> Result<(), JsValue> {
+        match &self.connection {
+            Connection::WebSerial { writer, .. } => {
+                let chunk = js_sys::Uint8Array::from(packet);
+                JsFuture::from(writer.write_with_chunk(&chunk)).await?;
+                Ok(())
+            }
+            Connection::WebSocket { sender, .. } => {
+                sender
+                    .lock()
+                    .await
+                    .send(Message::Bytes(packet.to_vec()))
+                    .await
+                    .map_err(|e| JsValue::from_str(&format!("Send failed: {:?}", e)))?;
+                Ok(())
+            }
+            Connection::Simulated { bus, pending } => {
+                let response = bus.borrow_mut().process(packet, js_sys::Date::now());
+                pending.borrow_mut().extend(response);
+                Ok(())
+            }
+            Connection::Replay { player } => Ok(player.borrow_mut().send(packet)?),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! # Bus Traffic Capture & Replay
+//!
+//! Records every packet sent to and received from the bus, with timestamps, in a
+//! portable JSON document. A [`ReplayPlayer`] feeds the recorded responses back
+//! to a fresh session so a failing field session can be re-run deterministically.
+//!
+//! ## File Format
+//!
+//! ```json
+//! {
+//!   "format": "reachy-mini-capture",
+//!   "version": 1,
+//!   "start_ms": 1718000000000.0,
+//!   "events": [
+//!     { "t_ms": 0.0,  "dir": "tx", "data": "FFFFFD00FE..." },
+//!     { "t_ms": 10.4, "dir": "rx", "data": "FFFFFD000B..." }
+//!   ]
+//! }
+//! ```
+//!
+//! `t_ms` is relative to `start_ms`, `data` is upper-case hex.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Value of the `format` field identifying a capture file
+const CAPTURE_FORMAT: &str = "reachy-mini-capture";
+
+/// Current capture file version
+const CAPTURE_VERSION: u32 = 1;
+
+// ============================================================================
+// Capture
+// ============================================================================
+
+/// Direction of a captured packet, seen from the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Host to motors
+    Tx,
+    /// Motors to host
+    Rx,
+}
+
+/// A single captured chunk of bus traffic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureEvent {
+    /// Milliseconds since the start of the capture
+    pub t_ms: f64,
+    pub dir: Direction,
+    #[serde(with = "hex")]
+    pub data: Vec<u8>,
+}
+
+/// A recorded session of bus traffic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    format: String,
+    version: u32,
+    /// Wall-clock time (ms since epoch) when the capture started
+    pub start_ms: f64,
+    pub events: Vec<CaptureEvent>,
+}
+
+impl Capture {
+    /// Start an empty capture at wall-clock time `start_ms`.
+    pub fn new(start_ms: f64) -> Self {
+        Self {
+            format: CAPTURE_FORMAT.to_string(),
+            version: CAPTURE_VERSION,
+            start_ms,
+            events: Vec::new(),
+        }
+    }
+
+    /// Record `data` travelling in `dir` at wall-clock time `now_ms`.
+    pub fn record(&mut self, dir: Direction, data: &[u8], now_ms: f64) {
+        self.events.push(CaptureEvent {
+            t_ms: now_ms - self.start_ms,
+            dir,
+            data: data.to_vec(),
+        });
+    }
+
+    /// Serialize to the portable JSON format.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("capture is always serializable")
+    }
+
+    /// Parse a capture file, checking its format and version.
+    pub fn from_json(json: &str) -> Result<Self, CaptureError> {
+        let capture: Capture =
+            serde_json::from_str(json).map_err(|e| CaptureError::Invalid(e.to_string()))?;
+        if capture.format != CAPTURE_FORMAT {
+            return Err(CaptureError::Invalid(format!(
+                "unknown format '{}'",
+                capture.format
+            )));
+        }
+        if capture.version > CAPTURE_VERSION {
+            return Err(CaptureError::Invalid(format!(
+                "unsupported version {}",
+                capture.version
+            )));
+        }
+        Ok(capture)
+    }
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// Plays a [`Capture`] back as if it were the bus.
+///
+/// Each packet sent must match the next recorded TX event; reads return the RX
+/// events recorded after it, in order.
+pub struct ReplayPlayer {
+    events: Vec<CaptureEvent>,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    /// Create a player positioned at the first event.
+    pub fn new(capture: Capture) -> Self {
+        Self {
+            events: capture.events,
+            cursor: 0,
+        }
+    }
+
+    /// Consume the next TX event, checking it matches `packet`.
+    pub fn send(&mut self, packet: &[u8]) -> Result<(), CaptureError> {
+        // Responses the session never read are skipped, as on a real bus
+        while self.peek_dir() == Some(Direction::Rx) {
+            self.cursor += 1;
+        }
+
+        let index = self.cursor;
+        match self.events.get(index) {
+            None => Err(CaptureError::Exhausted),
+            Some(event) if event.data != packet => Err(CaptureError::Diverged {
+                index,
+                expected: event.data.clone(),
+                actual: packet.to_vec(),
+            }),
+            Some(_) => {
+                self.cursor += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Return the next recorded RX chunk, or nothing if the capture recorded
+    /// no response before the next TX (e.g. a SYNC_WRITE or a timeout).
+    pub fn recv(&mut self) -> Vec<u8> {
+        if self.peek_dir() == Some(Direction::Rx) {
+            self.cursor += 1;
+            self.events[self.cursor - 1].data.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Number of events not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.events.len() - self.cursor
+    }
+
+    fn peek_dir(&self) -> Option<Direction> {
+        self.events.get(self.cursor).map(|e| e.dir)
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Capture loading or replay error
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureError {
+    /// The capture file could not be parsed
+    Invalid(String),
+    /// The session sent more packets than were recorded
+    Exhausted,
+    /// The session sent a different packet than the one recorded
+    Diverged {
+        index: usize,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            CaptureError::Invalid(reason) => format!("Invalid capture: {}", reason),
+            CaptureError::Exhausted => "Replay exhausted: no more recorded packets".to_string(),
+            CaptureError::Diverged {
+                index,
+                expected,
+                actual,
+            } => format!(
+                "Replay diverged at event {}: expected {}, got {}",
+                index,
+                hex::encode(expected),
+                hex::encode(actual)
+            ),
+        })
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+#[cfg(feature = "wasm")]
+impl From<CaptureError> for JsValue {
+    fn from(e: CaptureError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// ============================================================================
+// Hex Encoding
+// ============================================================================
+
+/// Upper-case hex (de)serialization for byte buffers.
+pub(crate) mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn encode(data: &[u8]) -> String {
+        data.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    pub fn decode(s: &str) -> Result<Vec<u8>, String> {
+        if !s.is_ascii() || !s.len().is_multiple_of(2) {
+            return Err(format!("invalid hex string '{}'", s));
+        }
+        (0..s.len())
+            .step_by(2)
+            .map(|i| {
+                u8::from_str_radix(&s[i..i + 2], 16)
+                    .map_err(|_| format!("invalid hex byte '{}'", &s[i..i + 2]))
+            })
+            .collect()
+    }
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        decode(&s).map_err(D::Error::custom)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{build_sync_current_position, build_sync_write_torque};
+    use crate::simulator::SimulatedBus;
+
+    /// Record a short session against the simulator.
+    fn record_session() -> Capture {
+        let mut bus = SimulatedBus::reachy_mini();
+        let mut capture = Capture::new(1000.0);
+
+        for (i, packet) in [
+            build_sync_write_torque(&[11, 12], true),
+            build_sync_current_position(&[11, 12]),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let now = 1000.0 + 20.0 * i as f64;
+            capture.record(Direction::Tx, packet, now);
+            let response = bus.process(packet, now);
+            if !response.is_empty() {
+                capture.record(Direction::Rx, &response, now + 10.0);
+            }
+        }
+        capture
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let capture = record_session();
+        let json = capture.to_json();
+        assert!(json.contains("\"dir\":\"tx\""));
+        assert!(json.contains("\"data\":\"FFFFFD00FE"));
+        assert_eq!(Capture::from_json(&json).unwrap(), capture);
+
+        assert!(matches!(
+            Capture::from_json(r#"{"format":"pcap","version":1,"start_ms":0,"events":[]}"#),
+            Err(CaptureError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_returns_recorded_responses() {
+        let capture = record_session();
+        let expected = capture.events[2].data.clone();
+        let mut player = ReplayPlayer::new(capture);
+
+        player
+            .send(&build_sync_write_torque(&[11, 12], true))
+            .unwrap();
+        assert!(player.recv().is_empty());
+
+        player
+            .send(&build_sync_current_position(&[11, 12]))
+            .unwrap();
+        assert_eq!(player.recv(), expected);
+        assert_eq!(player.remaining(), 0);
+        assert_eq!(
+            player.send(&build_sync_current_position(&[11])),
+            Err(CaptureError::Exhausted)
+        );
+    }
+
+    #[test]
+    fn test_replay_detects_divergence() {
+        let mut player = ReplayPlayer::new(record_session());
+        let result = player.send(&build_sync_write_torque(&[11, 12], false));
+        assert!(matches!(
+            result,
+            Err(CaptureError::Diverged { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,1320 @@
+//! # Native Robot
+//!
+//! [`Robot`] is the blocking counterpart of the JavaScript `ReachyMini`, for
+//! programs running next to the robot (e.g. on a Raspberry Pi): head pose,
+//! named joints, antennas, torque, diagnostics, reboot, recording and replay
+//! over any [`Port`].
+//!
+//! ```no_run
+//! use reachy_mini::client::Robot;
+//! use reachy_mini::config::RobotConfig;
+//!
+//! let mut robot = Robot::open("/dev/ttyACM0", RobotConfig::lite())?;
+//! robot.set_torque(true)?;
+//! robot.set_head_pose([0.0, 0.0, 10.0, 0.0, 0.0, 15.0])?;
+//! println!("{:?}", robot.get_head_pose()?);
+//! # Ok::<(), reachy_mini::error::Error>(())
+//! ```
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nalgebra::Matrix4;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BodyYawLimits, Geometry, RobotConfig};
+use crate::dynamixel::{
+    address, build_ping_packet, build_read_packet, build_reboot_packet,
+    build_sync_current_position, build_sync_read, build_sync_write_position_radians,
+    build_sync_write_torque, build_write_packet, hardware_error, parse_1byte_packets,
+    parse_2byte_signed_packets, parse_position_packets, parse_status_packets, raw_to_radians,
+    status_error, ParseError, BROADCAST_ID,
+};
+use crate::error::{Error, Result};
+use crate::joints::{ids, Joint, JointKind, JointTable};
+use crate::kinematics::{
+    in_body_frame, pose_to_transform, transform_to_pose, transform_to_xyzrpy, xyzrpy_to_transform,
+    ForwardSolution, Kinematics, PlatformJacobian, PoseFormat, Projection, SingularityPolicy,
+};
+use crate::port::{Port, SimulatedPort, DEFAULT_TIMEOUT};
+#[cfg(target_os = "linux")]
+use crate::serial::SerialPort;
+use crate::simulator::SimulatedBus;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default recording and replay period (ms)
+pub const DEFAULT_PERIOD: Duration = Duration::from_millis(20);
+
+/// Time to collect answers to a broadcast ping
+const SCAN_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Value of the `format` field identifying a settings backup
+const SETTINGS_FORMAT: &str = "reachy-mini-settings";
+
+/// Current settings backup version
+const SETTINGS_VERSION: u32 = 1;
+
+/// Size of the EEPROM area saved by a backup (addresses 0-63)
+const EEPROM_SIZE: u16 = address::TORQUE_ENABLE;
+
+/// `(address, length)` of the EEPROM fields written back on restore: return
+/// delay to secondary ID, homing offset and moving threshold, temperature to
+/// current limits, velocity and position limits, startup to shutdown.
+/// ID, baud rate and protocol are never rewritten so the bus stays reachable.
+const RESTORED_FIELDS: [(u16, u16); 5] = [(9, 4), (20, 8), (31, 9), (44, 12), (60, 4)];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Health readings of one motor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotorStatus {
+    /// Joint name, e.g. `"l_antenna"`
+    pub name: String,
+    /// Dynamixel ID
+    pub id: u8,
+    /// Temperature in °C
+    pub temperature: u8,
+    /// Load from -1000 to 1000 (percentage of max torque × 10)
+    pub load: i16,
+    /// Supply voltage in V
+    pub voltage: f32,
+    /// Hardware Error Status bits, see [`hardware_error`]
+    pub hardware_error: u8,
+    /// Whether the motor answered every read
+    pub responding: bool,
+}
+
+impl MotorStatus {
+    /// Names of the hardware errors latched by the motor.
+    pub fn errors(&self) -> Vec<&'static str> {
+        hardware_error::names(self.hardware_error)
+    }
+}
+
+/// A motor answering a bus scan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotorInfo {
+    /// Dynamixel ID
+    pub id: u8,
+    /// Joint name, if the ID belongs to the robot configuration
+    pub name: Option<String>,
+    /// Model number (1200 for the XL330-M288)
+    pub model: u16,
+    pub firmware: u8,
+}
+
+/// EEPROM of one motor, as saved by [`Robot::backup_settings`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotorSettings {
+    pub name: String,
+    pub id: u8,
+    /// Control table bytes 0-63
+    #[serde(with = "crate::capture::hex")]
+    pub eeprom: Vec<u8>,
+}
+
+/// EEPROM settings of every motor of a robot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsBackup {
+    format: String,
+    version: u32,
+    pub motors: Vec<MotorSettings>,
+}
+
+impl SettingsBackup {
+    /// Wrap per-motor settings in a backup document.
+    pub fn new(motors: Vec<MotorSettings>) -> Self {
+        Self {
+            format: SETTINGS_FORMAT.to_string(),
+            version: SETTINGS_VERSION,
+            motors,
+        }
+    }
+
+    /// Parse a backup saved with [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self> {
+        let backup: Self =
+            serde_json::from_str(json).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        if backup.format != SETTINGS_FORMAT || backup.version != SETTINGS_VERSION {
+            return Err(Error::InvalidArgument(format!(
+                "Not a settings backup (format '{}', version {})",
+                backup.format, backup.version
+            )));
+        }
+        Ok(backup)
+    }
+
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("settings backups always serialize")
+    }
+}
+
+/// Joint angles sampled at a fixed period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// Time between frames (ms)
+    pub period_ms: f64,
+    /// Joint names, in frame order
+    pub joints: Vec<String>,
+    /// One angle per joint (degrees) per frame
+    pub frames: Vec<Vec<f32>>,
+}
+
+impl Recording {
+    /// Parse a recording saved with [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidArgument(e.to_string()))
+    }
+
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("recordings always serialize")
+    }
+
+    /// Recorded duration.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.period_ms * self.frames.len() as f64 / 1000.0)
+    }
+}
+
+// ============================================================================
+// Robot
+// ============================================================================
+
+/// A Reachy Mini driven over a blocking [`Port`].
+pub struct Robot<P: Port> {
+    port: P,
+    config: RobotConfig,
+    /// Joint names, motor IDs, directions and limits
+    joints: JointTable,
+    /// Kinematics solver, kept between calls so FK is warm-started
+    kinematics: Kinematics,
+    /// Time to wait for status packets
+    timeout: Duration,
+}
+
+#[cfg(target_os = "linux")]
+impl Robot<SerialPort> {
+    /// Open the serial adapter at `path` for a robot described by `config`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use reachy_mini::{client::Robot, config::RobotConfig};
+    /// let robot = Robot::open("/dev/ttyUSB0", RobotConfig::wireless())?;
+    /// # Ok::<(), reachy_mini::error::Error>(())
+    /// ```
+    pub fn open<T: AsRef<std::path::Path>>(path: T, config: RobotConfig) -> Result<Self> {
+        Ok(Self::new(SerialPort::open(path)?, config))
+    }
+}
+
+impl Robot<SimulatedPort> {
+    /// An in-process simulated robot with the motors of `config`.
+    pub fn simulated(config: RobotConfig) -> Self {
+        let bus = SimulatedBus::new(&config.motor_ids());
+        Self::new(SimulatedPort::new(bus), config)
+    }
+}
+
+impl<P: Port> Robot<P> {
+    /// Drive the robot described by `config` over `port`.
+    pub fn new(port: P, config: RobotConfig) -> Self {
+        Self {
+            port,
+            joints: config.joint_table(),
+            kinematics: config.kinematics(),
+            config,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Configuration
+    // ------------------------------------------------------------------------
+
+    /// The active robot configuration.
+    pub fn config(&self) -> &RobotConfig {
+        &self.config
+    }
+
+    /// The joint table, with the limits currently enforced.
+    pub fn joints(&self) -> &JointTable {
+        &self.joints
+    }
+
+    /// The joint table, e.g. to tighten limits.
+    pub fn joints_mut(&mut self) -> &mut JointTable {
+        &mut self.joints
+    }
+
+    /// Replace the kinematic geometry, keeping joints, limits, the
+    /// singularity policy and the forward kinematics limits; the solver is
+    /// rebuilt once here.
+    ///
+    /// # Errors
+    /// Returns [`Error::Config`] if the geometry is invalid; the current
+    /// model stays active.
+    pub fn set_geometry(&mut self, geometry: Geometry) -> Result<()> {
+        self.config.set_geometry(geometry)?;
+        let mut kinematics = self.config.kinematics();
+        kinematics.copy_settings_from(&self.kinematics);
+        self.kinematics = kinematics;
+        Ok(())
+    }
+
+    /// The underlying port.
+    pub fn port_mut(&mut self) -> &mut P {
+        &mut self.port
+    }
+
+    /// Set how long reads wait for status packets (default 20 ms).
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    // ------------------------------------------------------------------------
+    // Head Pose
+    // ------------------------------------------------------------------------
+
+    /// Current head pose as `[x, y, z, roll, pitch, yaw]` (mm, degrees), in
+    /// the world frame: the body rotation is included on robots with a body
+    /// yaw joint.
+    pub fn get_head_pose(&mut self) -> Result<[f32; 6]> {
+        Ok(self.get_head_pose_solution()?.0)
+    }
+
+    /// Current head pose and body yaw (degrees, 0 without a body yaw joint).
+    pub fn get_head_pose_and_body_yaw(&mut self) -> Result<([f32; 6], f32)> {
+        let (pose, body_yaw, _) = self.read_head_pose()?;
+        Ok((pose, body_yaw))
+    }
+
+    /// Current head pose written in `format`, in the world frame. Setting it
+    /// back with [`set_head_pose_as`](Self::set_head_pose_as) gives the same
+    /// transform.
+    pub fn get_head_pose_as(&mut self, format: PoseFormat) -> Result<Vec<f32>> {
+        let (_, _, solution) = self.read_head_pose()?;
+        Ok(transform_to_pose(
+            format,
+            &solution.t_world_platform,
+            self.head_z_offset_mm(),
+        ))
+    }
+
+    /// Current head pose with the forward kinematics solve that produced it
+    /// (residual, iterations, convergence).
+    ///
+    /// The solver warm-starts from the previous pose, so polling converges
+    /// in a step or two.
+    pub fn get_head_pose_solution(&mut self) -> Result<([f32; 6], ForwardSolution)> {
+        let (pose, _, solution) = self.read_head_pose()?;
+        Ok((pose, solution))
+    }
+
+    /// Velocity kinematics of the head at its current joint angles, to map
+    /// joint velocities to a head twist and back.
+    pub fn get_head_jacobian(&mut self) -> Result<PlatformJacobian> {
+        let joint_deg = self.read_joints(&self.joints.head())?;
+        let joint_rad: Vec<f32> = joint_deg.iter().map(|d| d.to_radians()).collect();
+        let solution = self.kinematics.solve_forward_kinematics(&joint_rad, None);
+        Ok(self
+            .kinematics
+            .platform_jacobian(&joint_rad, &solution.t_world_platform))
+    }
+
+    /// Velocity kinematics of the head at `[x, y, z, roll, pitch, yaw]` (mm,
+    /// degrees). Nothing is sent.
+    ///
+    /// # Errors
+    /// Returns [`Error::Kinematics`] if the pose is out of reach.
+    pub fn head_jacobian(&mut self, pose: [f32; 6]) -> Result<PlatformJacobian> {
+        let t = xyzrpy_to_transform(pose, self.head_z_offset_mm());
+        let joint_rad = self.kinematics.inverse_kinematics(t, None)?;
+        Ok(self.kinematics.platform_jacobian(&joint_rad, &t))
+    }
+
+    /// Move the head to `[x, y, z, roll, pitch, yaw]` (mm, degrees) through
+    /// inverse kinematics.
+    ///
+    /// Poses near a singularity are reported or refused, see
+    /// [`set_singularity_policy`](Self::set_singularity_policy).
+    ///
+    /// # Returns
+    /// The platform Jacobian condition number if the pose is near a
+    /// singularity and the policy is to warn, `None` otherwise.
+    ///
+    /// # Errors
+    /// Returns [`Error::Kinematics`] if the pose is out of reach (or refused
+    /// near a singularity), or [`Error::Joint`] if a solution exceeds the head
+    /// joint limits; nothing is sent in either case.
+    pub fn set_head_pose(&mut self, pose: [f32; 6]) -> Result<Option<f32>> {
+        self.set_head_pose_and_body_yaw(pose, 0.0)
+    }
+
+    /// Move the head to `pose` (world frame) with the body turned to
+    /// `body_yaw_deg`, in one write.
+    ///
+    /// The body follows a head yawed further from it than the configured
+    /// [`BodyYawLimits`] allow, and stops at its own limit. Without a body
+    /// yaw joint, `body_yaw_deg` must be 0.
+    ///
+    /// # Returns
+    /// As [`set_head_pose`](Self::set_head_pose).
+    ///
+    /// # Errors
+    /// As [`set_head_pose`](Self::set_head_pose), or
+    /// [`Error::InvalidArgument`] for a body yaw on a robot without one.
+    pub fn set_head_pose_and_body_yaw(
+        &mut self,
+        pose: [f32; 6],
+        body_yaw_deg: f32,
+    ) -> Result<Option<f32>> {
+        self.set_head_transform(
+            xyzrpy_to_transform(pose, self.head_z_offset_mm()),
+            body_yaw_deg,
+        )
+    }
+
+    /// Move the head to a pose written in `format` (world frame, see
+    /// [`PoseFormat`]) with the body turned to `body_yaw_deg`.
+    ///
+    /// # Returns
+    /// As [`set_head_pose`](Self::set_head_pose).
+    ///
+    /// # Errors
+    /// As [`set_head_pose_and_body_yaw`](Self::set_head_pose_and_body_yaw), or
+    /// [`Error::Kinematics`] if `values` are not a pose in `format`.
+    pub fn set_head_pose_as(
+        &mut self,
+        format: PoseFormat,
+        values: &[f32],
+        body_yaw_deg: f32,
+    ) -> Result<Option<f32>> {
+        let t = pose_to_transform(format, values, self.head_z_offset_mm())?;
+        self.set_head_transform(t, body_yaw_deg)
+    }
+
+    /// Solve and write the joints for the world-frame head transform `t`,
+    /// returning the condition number of a pose near a singularity.
+    ///
+    /// With a body yaw joint, the pose is solved by
+    /// [`Kinematics::inverse_kinematics_safe`], so the body written may differ
+    /// from `body_yaw_deg` where the limits make it follow the head. The
+    /// singularity policy applies to the head joints at the platform pose
+    /// relative to that body: turning the body is a rigid rotation of the
+    /// whole platform and leaves its Jacobian unchanged.
+    fn set_head_transform(&mut self, t: Matrix4<f32>, body_yaw_deg: f32) -> Result<Option<f32>> {
+        let (body, joint_rad) = match self.joints.body_yaw() {
+            Some(body) => {
+                let limits = self.config.body_yaw_limits;
+                let joint_rad = self.kinematics.inverse_kinematics_safe(
+                    t,
+                    Some(body_yaw_deg.to_radians()),
+                    Some(limits.max_relative_yaw_deg.to_radians()),
+                    Some(limits.max_body_yaw_deg.to_radians()),
+                )?;
+                (vec![body], joint_rad)
+            }
+            None if body_yaw_deg == 0.0 => {
+                (Vec::new(), self.kinematics.inverse_kinematics(t, None)?)
+            }
+            None => {
+                return Err(Error::InvalidArgument(
+                    "This robot has no body yaw joint".to_string(),
+                ))
+            }
+        };
+
+        let body_yaw = if body.is_empty() { 0.0 } else { joint_rad[0] };
+        let t_body = in_body_frame(&t, body_yaw);
+        let head_rad = &joint_rad[body.len()..];
+        let warning = self.kinematics.check_singularity(head_rad, &t_body)?;
+        let joints: Vec<Joint> = body.into_iter().chain(self.joints.head()).collect();
+        let joint_deg: Vec<f32> = joint_rad.iter().map(|r| r.to_degrees()).collect();
+        self.write_joints(&joints, &joint_deg)?;
+        Ok(warning)
+    }
+
+    /// Set how far the body turns with head poses, on robots with a body yaw
+    /// joint.
+    pub fn set_body_yaw_limits(&mut self, limits: BodyYawLimits) {
+        self.config.body_yaw_limits = limits;
+    }
+
+    /// Set how head poses and replayed trajectories near a singularity are
+    /// handled: ignored, reported to the caller (the default) or refused,
+    /// when the platform Jacobian condition number exceeds
+    /// `max_condition_number`.
+    pub fn set_singularity_policy(&mut self, policy: SingularityPolicy, max_condition_number: f32) {
+        self.kinematics
+            .set_singularity_policy(policy, max_condition_number);
+    }
+
+    /// The reachable pose closest to `pose` on the path from the neutral pose,
+    /// with how far it was moved. Nothing is sent; pass the pose to
+    /// [`set_head_pose`](Self::set_head_pose).
+    pub fn project_head_pose(&mut self, pose: [f32; 6]) -> Result<([f32; 6], Projection)> {
+        let offset = self.head_z_offset_mm();
+        let projection = self.kinematics.project_inverse_kinematics(
+            xyzrpy_to_transform(pose, offset),
+            xyzrpy_to_transform([0.0; 6], offset),
+            None,
+        )?;
+        let reachable = if projection.moved() {
+            transform_to_xyzrpy(&projection.t_world_platform, offset)
+        } else {
+            pose
+        };
+        Ok((reachable, projection))
+    }
+
+    // ------------------------------------------------------------------------
+    // Joint Positions
+    // ------------------------------------------------------------------------
+
+    /// Angles of the 6 head motors in degrees.
+    pub fn get_head_joints(&mut self) -> Result<Vec<f32>> {
+        self.read_joints(&self.joints.head())
+    }
+
+    /// Command the 6 head motors (degrees).
+    pub fn set_head_joints(&mut self, angles_deg: &[f32]) -> Result<()> {
+        self.write_joints(&self.joints.head(), angles_deg)
+    }
+
+    /// Angles of the 6 head motors and both antennas, in degrees.
+    pub fn get_all_joints(&mut self) -> Result<Vec<f32>> {
+        self.read_joints(&self.joints.head_and_antennas())
+    }
+
+    /// Command the 6 head motors and both antennas (degrees).
+    pub fn set_all_joints(&mut self, angles_deg: &[f32]) -> Result<()> {
+        self.write_joints(&self.joints.head_and_antennas(), angles_deg)
+    }
+
+    // ------------------------------------------------------------------------
+    // Named Joints
+    // ------------------------------------------------------------------------
+
+    /// Angle of the joint called `name`, in degrees.
+    pub fn get_joint(&mut self, name: &str) -> Result<f32> {
+        let joint = self.joints.get(name)?.clone();
+        Ok(self.read_joints(&[joint])?[0])
+    }
+
+    /// `(name, degrees)` of every joint, in bus order.
+    pub fn get_joints(&mut self) -> Result<Vec<(String, f32)>> {
+        let joints = self.joints.all().to_vec();
+        let angles = self.read_joints(&joints)?;
+        Ok(joints
+            .into_iter()
+            .map(|joint| joint.name)
+            .zip(angles)
+            .collect())
+    }
+
+    /// Command several joints by name in a single bus write.
+    ///
+    /// # Errors
+    /// Returns [`Error::Joint`] for unknown names or angles outside the
+    /// limits; nothing is sent in that case.
+    pub fn set_joints(&mut self, targets: &[(String, f32)]) -> Result<()> {
+        let (joints, angles_deg): (Vec<Joint>, Vec<f32>) =
+            self.joints.resolve(targets)?.into_iter().unzip();
+        self.write_joints(&joints, &angles_deg)
+    }
+
+    // ------------------------------------------------------------------------
+    // Antennas
+    // ------------------------------------------------------------------------
+
+    /// Antenna angles `[left, right]` in degrees.
+    pub fn get_antennas(&mut self) -> Result<[f32; 2]> {
+        let angles = self.read_joints(&self.joints.antennas())?;
+        match angles[..] {
+            [left, right] => Ok([left, right]),
+            _ => Err(Error::InvalidArgument("This robot has no antennas".into())),
+        }
+    }
+
+    /// Command both antennas (degrees).
+    pub fn set_antennas(&mut self, left_deg: f32, right_deg: f32) -> Result<()> {
+        self.write_joints(&self.joints.antennas(), &[left_deg, right_deg])
+    }
+
+    // ------------------------------------------------------------------------
+    // Torque & Maintenance
+    // ------------------------------------------------------------------------
+
+    /// Enable or disable torque on every motor.
+    pub fn set_torque(&mut self, enable: bool) -> Result<()> {
+        let motor_ids = ids(self.joints.all());
+        self.port
+            .transact(&build_sync_write_torque(&motor_ids, enable), self.timeout)?;
+        Ok(())
+    }
+
+    /// Enable or disable torque on the joints of `kind`.
+    pub fn set_torque_of(&mut self, kind: JointKind, enable: bool) -> Result<()> {
+        let motor_ids = ids(&self.joints.of_kind(kind));
+        self.port
+            .transact(&build_sync_write_torque(&motor_ids, enable), self.timeout)?;
+        Ok(())
+    }
+
+    /// Enable or disable torque on the joints called `names`.
+    pub fn set_joint_torque(&mut self, names: &[String], enable: bool) -> Result<()> {
+        let motor_ids = names
+            .iter()
+            .map(|name| Ok(self.joints.get(name)?.id))
+            .collect::<Result<Vec<u8>>>()?;
+        self.port
+            .transact(&build_sync_write_torque(&motor_ids, enable), self.timeout)?;
+        Ok(())
+    }
+
+    /// Temperature, load, voltage and hardware errors of every motor, in bus
+    /// order. Motors that miss a read report zeros and `responding: false`.
+    pub fn get_diagnostics(&mut self) -> Result<Vec<MotorStatus>> {
+        let motor_ids = ids(self.joints.all());
+        let temperatures =
+            parse_1byte_packets(&self.sync_read(&motor_ids, address::PRESENT_TEMPERATURE, 1)?);
+        let loads =
+            parse_2byte_signed_packets(&self.sync_read(&motor_ids, address::PRESENT_LOAD, 2)?);
+        let voltages = parse_2byte_signed_packets(&self.sync_read(
+            &motor_ids,
+            address::PRESENT_INPUT_VOLTAGE,
+            2,
+        )?);
+        let errors =
+            parse_1byte_packets(&self.sync_read(&motor_ids, address::HARDWARE_ERROR_STATUS, 1)?);
+
+        Ok(self
+            .joints
+            .all()
+            .iter()
+            .map(|joint| {
+                let temperature = find(&temperatures, joint.id);
+                let load = find(&loads, joint.id);
+                let voltage = find(&voltages, joint.id);
+                let error = find(&errors, joint.id);
+                MotorStatus {
+                    name: joint.name.clone(),
+                    id: joint.id,
+                    temperature: temperature.unwrap_or(0),
+                    load: load.unwrap_or(0),
+                    voltage: voltage.unwrap_or(0) as f32 / 10.0,
+                    hardware_error: error.unwrap_or(0),
+                    responding: temperature.is_some()
+                        && load.is_some()
+                        && voltage.is_some()
+                        && error.is_some(),
+                }
+            })
+            .collect())
+    }
+
+    /// Ping every ID on the bus and list the motors that answer, by ID.
+    pub fn scan(&mut self) -> Result<Vec<MotorInfo>> {
+        let response = self.port.transact(
+            &build_ping_packet(BROADCAST_ID),
+            self.timeout.max(SCAN_TIMEOUT),
+        )?;
+        let mut motors: Vec<MotorInfo> = parse_status_packets(&response)
+            .into_iter()
+            .filter(|(_, _, data)| data.len() == 3)
+            .map(|(id, _, data)| MotorInfo {
+                id,
+                name: self
+                    .joints
+                    .all()
+                    .iter()
+                    .find(|j| j.id == id)
+                    .map(|j| j.name.clone()),
+                model: u16::from_le_bytes([data[0], data[1]]),
+                firmware: data[2],
+            })
+            .collect();
+        motors.sort_by_key(|motor| motor.id);
+        motors.dedup_by_key(|motor| motor.id);
+        Ok(motors)
+    }
+
+    /// Read the EEPROM settings (limits, offsets, modes) of every motor.
+    ///
+    /// # Errors
+    /// Returns [`Error::Timeout`] if a motor does not answer.
+    pub fn backup_settings(&mut self) -> Result<SettingsBackup> {
+        let joints = self.joints.all().to_vec();
+        let mut motors = Vec::with_capacity(joints.len());
+        for joint in joints {
+            let request = build_read_packet(joint.id, 0, EEPROM_SIZE);
+            let eeprom = self.expect_status(joint.id, &request)?;
+            if eeprom.len() != EEPROM_SIZE as usize {
+                return Err(ParseError::InvalidLength.into());
+            }
+            motors.push(MotorSettings {
+                name: joint.name,
+                id: joint.id,
+                eeprom,
+            });
+        }
+        Ok(SettingsBackup::new(motors))
+    }
+
+    /// Write settings saved by [`backup_settings`](Self::backup_settings)
+    /// back to the motors. Torque is turned off on every restored motor.
+    ///
+    /// ID, baud rate and protocol are left untouched.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidArgument`] if the backup names a joint this
+    /// robot lacks or a different motor ID; nothing is written in that case.
+    pub fn restore_settings(&mut self, backup: &SettingsBackup) -> Result<()> {
+        for motor in &backup.motors {
+            let joint = self.joints.get(&motor.name)?;
+            if joint.id != motor.id || motor.eeprom.len() != EEPROM_SIZE as usize {
+                return Err(Error::InvalidArgument(format!(
+                    "Backup of {} (ID {}) does not match this robot (ID {})",
+                    motor.name, motor.id, joint.id
+                )));
+            }
+        }
+
+        for motor in &backup.motors {
+            // EEPROM is locked while torque is on
+            self.port
+                .transact(&build_sync_write_torque(&[motor.id], false), self.timeout)?;
+            for &(addr, len) in RESTORED_FIELDS.iter() {
+                let data = &motor.eeprom[addr as usize..(addr + len) as usize];
+                self.expect_status(motor.id, &build_write_packet(motor.id, addr, data))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reboot the motor of the joint called `name`, clearing hardware errors.
+    ///
+    /// Torque is off after a reboot.
+    pub fn reboot(&mut self, name: &str) -> Result<()> {
+        let id = self.joints.get(name)?.id;
+        self.port.transact(&build_reboot_packet(id), self.timeout)?;
+        Ok(())
+    }
+
+    /// Reboot every motor, one after the other.
+    pub fn reboot_all(&mut self) -> Result<()> {
+        for id in ids(self.joints.all()) {
+            self.port.transact(&build_reboot_packet(id), self.timeout)?;
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Recording & Playback
+    // ------------------------------------------------------------------------
+
+    /// Sample every joint each `period` for `duration`.
+    ///
+    /// Torque is left as is: disable it first to record by moving the robot
+    /// by hand.
+    pub fn record(&mut self, duration: Duration, period: Duration) -> Result<Recording> {
+        let joints = self.joints.all().to_vec();
+        let mut recording = Recording {
+            period_ms: period.as_secs_f64() * 1000.0,
+            joints: joints.iter().map(|joint| joint.name.clone()).collect(),
+            frames: Vec::new(),
+        };
+
+        let start = Instant::now();
+        let mut deadline = start;
+        while deadline - start < duration {
+            recording.frames.push(self.read_joints(&joints)?);
+            deadline += period;
+            sleep_until(deadline);
+        }
+        Ok(recording)
+    }
+
+    /// Play `recording` back at its period with torque on, then turn torque
+    /// off like `replay_recording()` does in the browser.
+    ///
+    /// If the recording moves the head, it is checked against the
+    /// singularity policy before anything moves.
+    ///
+    /// # Returns
+    /// The largest platform Jacobian condition number above the limit if the
+    /// head passes near a singularity and the policy is to warn.
+    ///
+    /// # Errors
+    /// Returns [`Error::Kinematics`] if the head passes near a singularity
+    /// and such poses are refused; nothing is played. Returns
+    /// [`Error::Joint`] if the recording names an unknown joint or a frame
+    /// exceeds the joint limits; frames before it have been played.
+    pub fn replay(&mut self, recording: &Recording) -> Result<Option<f32>> {
+        let joints = recording
+            .joints
+            .iter()
+            .map(|name| Ok(self.joints.get(name)?.clone()))
+            .collect::<Result<Vec<Joint>>>()?;
+        let period = Duration::from_secs_f64(recording.period_ms / 1000.0);
+        let warning = self.check_head_trajectory(recording)?;
+
+        self.set_torque(true)?;
+        let mut deadline = Instant::now();
+        for frame in &recording.frames {
+            self.write_joints(&joints, frame)?;
+            deadline += period;
+            sleep_until(deadline);
+        }
+        self.set_torque(false)?;
+        Ok(warning)
+    }
+
+    // ------------------------------------------------------------------------
+    // Internal Helpers
+    // ------------------------------------------------------------------------
+
+    /// Apply the singularity policy to the head motion of `recording`, if
+    /// every head joint is recorded, returning the worst condition number
+    /// to warn about.
+    fn check_head_trajectory(&mut self, recording: &Recording) -> Result<Option<f32>> {
+        let columns: Option<Vec<usize>> = self
+            .joints
+            .head()
+            .iter()
+            .map(|joint| {
+                recording
+                    .joints
+                    .iter()
+                    .position(|name| self.joints.get(name).is_ok_and(|j| j.id == joint.id))
+            })
+            .collect();
+        let columns = match columns {
+            Some(columns) => columns,
+            None => return Ok(None),
+        };
+        let samples: Vec<Vec<f32>> = recording
+            .frames
+            .iter()
+            .map(|frame| columns.iter().map(|&i| frame[i].to_radians()).collect())
+            .collect();
+        Ok(self.kinematics.check_trajectory(&samples)?)
+    }
+
+    /// Read the head (and body yaw) joints and solve forward kinematics:
+    /// the world-frame pose, the body yaw (degrees) and the solve.
+    fn read_head_pose(&mut self) -> Result<([f32; 6], f32, ForwardSolution)> {
+        let body = self.joints.body_yaw();
+        let joints: Vec<Joint> = self.joints.head().into_iter().chain(body.clone()).collect();
+        let mut joint_deg = self.read_joints(&joints)?;
+        let body_yaw_deg = match body {
+            Some(_) => joint_deg.pop(),
+            None => None,
+        };
+
+        let joint_rad: Vec<f32> = joint_deg.iter().map(|d| d.to_radians()).collect();
+        let solution = self
+            .kinematics
+            .solve_forward_kinematics(&joint_rad, body_yaw_deg.map(f32::to_radians));
+        let pose = transform_to_xyzrpy(&solution.t_world_platform, self.head_z_offset_mm());
+        Ok((pose, body_yaw_deg.unwrap_or(0.0), solution))
+    }
+
+    /// Head height (mm) reported as `z = 0`.
+    fn head_z_offset_mm(&self) -> f32 {
+        self.config.geometry.head_z_offset_mm
+    }
+
+    /// Sync-read `length` bytes at `addr` from `motor_ids`.
+    fn sync_read(&mut self, motor_ids: &[u8], addr: u16, length: u16) -> Result<Vec<u8>> {
+        self.port
+            .transact(&build_sync_read(motor_ids, addr, length), self.timeout)
+    }
+
+    /// Send `request` to motor `id` and return the data of its status packet.
+    ///
+    /// # Errors
+    /// Returns [`Error::Timeout`] if the motor does not answer and
+    /// [`Error::Parse`] if it reports an error other than the alert bit.
+    fn expect_status(&mut self, id: u8, request: &[u8]) -> Result<Vec<u8>> {
+        let response = self.port.transact(request, self.timeout)?;
+        match parse_status_packets(&response)
+            .into_iter()
+            .find(|(motor, _, _)| *motor == id)
+        {
+            Some((_, error, _)) if error & !status_error::ALERT != 0 => {
+                Err(ParseError::MotorError(error).into())
+            }
+            Some((_, _, data)) => Ok(data),
+            None => Err(Error::Timeout(vec![id])),
+        }
+    }
+
+    /// Read joint angles (degrees), in `joints` order.
+    ///
+    /// # Errors
+    /// Returns [`Error::Timeout`] listing the motors that did not answer.
+    fn read_joints(&mut self, joints: &[Joint]) -> Result<Vec<f32>> {
+        let motor_ids = ids(joints);
+        let response = self
+            .port
+            .transact(&build_sync_current_position(&motor_ids), self.timeout)?;
+        let positions = parse_position_packets(&response);
+
+        let mut missing = Vec::new();
+        let angles = joints
+            .iter()
+            .map(
+                |joint| match positions.iter().find(|(id, _)| *id == joint.id) {
+                    Some(&(_, raw)) => joint.joint_degrees(raw_to_radians(raw)),
+                    None => {
+                        missing.push(joint.id);
+                        0.0
+                    }
+                },
+            )
+            .collect();
+        if missing.is_empty() {
+            Ok(angles)
+        } else {
+            Err(Error::Timeout(missing))
+        }
+    }
+
+    /// Command joint angles (degrees) after checking every joint's limits.
+    fn write_joints(&mut self, joints: &[Joint], angles_deg: &[f32]) -> Result<()> {
+        if angles_deg.len() != joints.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Expected {} joint angles, got {}",
+                joints.len(),
+                angles_deg.len()
+            )));
+        }
+        for (joint, &angle_deg) in joints.iter().zip(angles_deg) {
+            joint.check_limits(angle_deg)?;
+        }
+
+        let angles_rad: Vec<f32> = joints
+            .iter()
+            .zip(angles_deg)
+            .map(|(joint, &angle_deg)| joint.motor_radians(angle_deg))
+            .collect();
+        let packet = build_sync_write_position_radians(&ids(joints), &angles_rad);
+        self.port.transact(&packet, self.timeout)?;
+        Ok(())
+    }
+}
+
+/// Value reported by motor `id` in parsed `(id, value)` pairs.
+fn find<T: Copy>(parsed: &[(u8, T)], id: u8) -> Option<T> {
+    parsed
+        .iter()
+        .find(|(m, _)| *m == id)
+        .map(|&(_, value)| value)
+}
+
+/// Sleep until `deadline`, returning at once if it has passed.
+fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now {
+        thread::sleep(deadline - now);
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kinematics::KinematicsError;
+
+    /// Let the simulated motors settle on their goals (50 ms time constant).
+    fn settle() {
+        thread::sleep(Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_named_joints_and_antennas() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.set_torque(true).unwrap();
+        robot
+            .set_joints(&[
+                ("l_antenna".to_string(), 30.0),
+                ("r_antenna".to_string(), -20.0),
+            ])
+            .unwrap();
+        settle();
+
+        let [left, right] = robot.get_antennas().unwrap();
+        assert!((left - 30.0).abs() < 0.5, "left antenna at {}", left);
+        assert!((right + 20.0).abs() < 0.5, "right antenna at {}", right);
+        assert_eq!(robot.get_joints().unwrap().len(), 8);
+        assert!(robot.get_joint("neck").is_err());
+    }
+
+    #[test]
+    fn test_limits_block_writes() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.joints_mut().get_mut("l_antenna").unwrap().limits_deg = [-10.0, 10.0];
+        assert!(matches!(
+            robot.set_antennas(45.0, 0.0),
+            Err(Error::Joint(_))
+        ));
+        let goal = robot.port_mut().bus().motor(17).unwrap().goal_position();
+        assert_eq!(goal, 2048);
+        assert!(matches!(
+            robot.set_head_joints(&[0.0; 3]),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_motor_times_out() {
+        let config = RobotConfig::lite();
+        let bus = SimulatedBus::new(&config.motor_ids()[..7]);
+        let mut robot = Robot::new(SimulatedPort::new(bus), config);
+        match robot.get_antennas() {
+            Err(Error::Timeout(ids)) => assert_eq!(ids, vec![18]),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+
+        let diagnostics = robot.get_diagnostics().unwrap();
+        assert!(diagnostics[0].responding);
+        assert!(!diagnostics[7].responding);
+    }
+
+    #[test]
+    fn test_diagnostics_and_scan() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot
+            .port_mut()
+            .bus_mut()
+            .motor_mut(12)
+            .unwrap()
+            .write(address::HARDWARE_ERROR_STATUS, &[hardware_error::OVERLOAD]);
+
+        let diagnostics = robot.get_diagnostics().unwrap();
+        assert!(diagnostics.iter().all(|m| m.responding));
+        assert_eq!(diagnostics[0].voltage, 5.0);
+        assert_eq!(diagnostics[1].errors(), vec!["overload"]);
+
+        let motors = robot.scan().unwrap();
+        assert_eq!(motors.len(), 8);
+        assert_eq!(motors[6].name.as_deref(), Some("l_antenna"));
+        assert_eq!(motors[0].model, 1200);
+    }
+
+    #[test]
+    fn test_settings_backup_restore() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        let mut backup = robot.backup_settings().unwrap();
+        assert_eq!(backup.motors.len(), 8);
+        assert_eq!(backup.motors[0].eeprom[address::ID as usize], 11);
+
+        // Max position limit (address 48) of stewart_1
+        backup.motors[0].eeprom[48] = 0xAB;
+        let backup = SettingsBackup::from_json(&backup.to_json()).unwrap();
+        robot.restore_settings(&backup).unwrap();
+        let motor = robot.port_mut().bus().motor(11).unwrap();
+        assert_eq!(motor.read(48, 1).unwrap(), &[0xAB]);
+
+        let mut wrong = backup.clone();
+        wrong.motors[0].id = 42;
+        assert!(matches!(
+            robot.restore_settings(&wrong),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_recording_roundtrip() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        let recording = robot
+            .record(Duration::from_millis(100), DEFAULT_PERIOD)
+            .unwrap();
+        assert_eq!(recording.frames.len(), 5);
+        assert_eq!(recording.joints[6], "l_antenna");
+        assert_eq!(
+            Recording::from_json(&recording.to_json()).unwrap(),
+            recording
+        );
+        robot.replay(&recording).unwrap();
+    }
+
+    #[test]
+    fn test_unreachable_head_pose() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        let too_high = [0.0, 0.0, 100.0, 0.0, 0.0, 0.0];
+        match robot.set_head_pose(too_high) {
+            Err(Error::Kinematics(KinematicsError::Unreachable(joints))) => {
+                assert!(joints.iter().all(|name| name.starts_with("stewart_")))
+            }
+            other => panic!("expected an unreachable error, got {:?}", other),
+        }
+        // Nothing reached the motors
+        let goal = robot.port_mut().bus().motor(11).unwrap().goal_position();
+        assert_eq!(goal, 2048);
+
+        let (pose, projection) = robot.project_head_pose(too_high).unwrap();
+        assert!(projection.moved());
+        assert!(pose[2] > 0.0 && pose[2] < 100.0, "z = {}", pose[2]);
+        robot.set_head_pose(pose).unwrap();
+    }
+
+    #[test]
+    fn test_head_pose_solution_warm_start() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.set_torque(true).unwrap();
+        robot
+            .set_head_pose([0.0, 5.0, 8.0, 0.0, -10.0, 0.0])
+            .unwrap();
+        settle();
+
+        let (pose, solution) = robot.get_head_pose_solution().unwrap();
+        assert!(solution.converged, "residual {}", solution.residual);
+        assert!((pose[4] + 10.0).abs() < 1.0, "pitch = {}", pose[4]);
+        // The motors have settled, so the next read starts at the answer
+        let (_, again) = robot.get_head_pose_solution().unwrap();
+        assert!(again.iterations <= 1, "{} iterations", again.iterations);
+    }
+
+    #[test]
+    fn test_head_jacobian() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.set_torque(true).unwrap();
+        let pose = [0.0, 0.0, 5.0, 0.0, 10.0, 0.0];
+        robot.set_head_pose(pose).unwrap();
+        settle();
+
+        // Rising at 10 mm/s needs the same joint speeds measured or planned
+        let twist = [0.0, 0.0, 0.01, 0.0, 0.0, 0.0];
+        let planned = robot.head_jacobian(pose).unwrap();
+        let measured = robot.get_head_jacobian().unwrap();
+        let planned = planned.joint_velocities(twist).unwrap();
+        let measured = measured.joint_velocities(twist).unwrap();
+        for (a, b) in planned.iter().zip(&measured) {
+            assert!((a - b).abs() < 0.05 * a.abs().max(0.01), "{} vs {}", a, b);
+        }
+        assert!(robot
+            .head_jacobian([0.0, 0.0, 100.0, 0.0, 0.0, 0.0])
+            .is_err());
+    }
+
+    #[test]
+    fn test_head_pose_formats() {
+        let mut robot = Robot::simulated(RobotConfig::wireless());
+        robot.set_torque(true).unwrap();
+
+        // Yaw 30 degrees about Z, 10 mm up, with the body turned by 10
+        let half = 15f32.to_radians();
+        let quaternion = [0.0, 0.0, 10.0, 0.0, 0.0, half.sin(), half.cos()];
+        robot
+            .set_head_pose_as(PoseFormat::Quaternion, &quaternion, 10.0)
+            .unwrap();
+        settle();
+        let measured = robot.get_head_pose_as(PoseFormat::Quaternion).unwrap();
+        assert!(
+            measured[..3]
+                .iter()
+                .zip(&quaternion)
+                .all(|(a, b)| (a - b).abs() < 1.0)
+                && measured[3..]
+                    .iter()
+                    .zip(&quaternion[3..])
+                    .all(|(a, b)| (a - b).abs() < 0.01),
+            "{:?}",
+            measured
+        );
+        let pose = robot.get_head_pose().unwrap();
+        assert!((pose[5] - 30.0).abs() < 1.0, "{:?}", pose);
+
+        // Reading back and setting again does not move the head
+        let matrix = robot.get_head_pose_as(PoseFormat::Matrix).unwrap();
+        robot
+            .set_head_pose_as(PoseFormat::Matrix, &matrix, 10.0)
+            .unwrap();
+        settle();
+        let again = robot.get_head_pose().unwrap();
+        assert!(
+            again.iter().zip(&pose).all(|(a, b)| (a - b).abs() < 1.0),
+            "{:?}",
+            again
+        );
+
+        assert!(matches!(
+            robot.set_head_pose_as(PoseFormat::AxisAngle, &[0.0; 6], 0.0),
+            Err(Error::Kinematics(_))
+        ));
+    }
+
+    #[test]
+    fn test_head_pose_with_body_yaw() {
+        let mut robot = Robot::simulated(RobotConfig::wireless());
+        robot.set_torque(true).unwrap();
+        let close = |a: [f32; 6], b: [f32; 6]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1.0);
+
+        // The body turns and the head pose stays in the world frame
+        let pose = [5.0, 0.0, 10.0, 0.0, 5.0, 30.0];
+        robot.set_head_pose_and_body_yaw(pose, 20.0).unwrap();
+        settle();
+        let (measured, body_yaw) = robot.get_head_pose_and_body_yaw().unwrap();
+        assert!(close(measured, pose), "{:?}", measured);
+        assert!((body_yaw - 20.0).abs() < 1.0, "{}", body_yaw);
+
+        // The body follows a head turned past the relative limit
+        robot.set_body_yaw_limits(BodyYawLimits {
+            max_relative_yaw_deg: 40.0,
+            max_body_yaw_deg: 90.0,
+        });
+        robot
+            .set_head_pose([0.0, 0.0, 0.0, 0.0, 0.0, 60.0])
+            .unwrap();
+        settle();
+        let (measured, body_yaw) = robot.get_head_pose_and_body_yaw().unwrap();
+        assert!((measured[5] - 60.0).abs() < 1.0, "{:?}", measured);
+        assert!((body_yaw - 20.0).abs() < 1.0, "{}", body_yaw);
+
+        // The Lite has no body to turn
+        let mut lite = Robot::simulated(RobotConfig::lite());
+        lite.set_torque(true).unwrap();
+        assert!(matches!(
+            lite.set_head_pose_and_body_yaw(pose, 20.0),
+            Err(Error::InvalidArgument(_))
+        ));
+        lite.set_head_pose(pose).unwrap();
+        settle();
+        let (measured, body_yaw) = lite.get_head_pose_and_body_yaw().unwrap();
+        assert!(close(measured, pose), "{:?}", measured);
+        assert_eq!(body_yaw, 0.0);
+    }
+
+    #[test]
+    fn test_set_geometry() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.set_singularity_policy(SingularityPolicy::Refuse, 500.0);
+        let mut geometry = robot.config().geometry.clone();
+        geometry.motor_arm_length = 0.042;
+        robot.set_geometry(geometry.clone()).unwrap();
+        assert_eq!(robot.config().geometry.motor_arm_length, 0.042);
+        assert_eq!(
+            robot.kinematics.singularity_policy(),
+            (SingularityPolicy::Refuse, 500.0)
+        );
+
+        geometry.branches.truncate(5);
+        assert!(matches!(
+            robot.set_geometry(geometry),
+            Err(Error::Config(_))
+        ));
+        assert_eq!(robot.config().geometry.branches.len(), 6);
+    }
+
+    #[test]
+    fn test_singularity_refused() {
+        let mut robot = Robot::simulated(RobotConfig::lite());
+        robot.set_torque(true).unwrap();
+        // Near the top of the workspace a rod lines up with its arm
+        let (edge, _) = robot
+            .project_head_pose([0.0, 0.0, 100.0, 0.0, 0.0, 0.0])
+            .unwrap();
+        assert!(robot.set_head_pose(edge).unwrap().unwrap() > 1000.0);
+        assert_eq!(robot.set_head_pose([0.0; 6]).unwrap(), None);
+
+        robot.set_singularity_policy(SingularityPolicy::Refuse, 1000.0);
+        match robot.set_head_pose(edge) {
+            Err(Error::Kinematics(KinematicsError::NearSingular { .. })) => {}
+            other => panic!("expected a singularity error, got {:?}", other),
+        }
+        robot.set_head_pose([0.0; 6]).unwrap();
+
+        // A recording heading there is refused before anything moves
+        robot.set_singularity_policy(SingularityPolicy::Ignore, 1000.0);
+        let t = xyzrpy_to_transform(edge, 172.0);
+        let edge_rad = robot.kinematics.inverse_kinematics(t, None).unwrap();
+        robot.set_singularity_policy(SingularityPolicy::Refuse, 1000.0);
+        let recording = Recording {
+            period_ms: 10.0,
+            joints: robot.joints().head().into_iter().map(|j| j.name).collect(),
+            frames: vec![
+                robot.get_head_joints().unwrap(),
+                edge_rad.iter().map(|r| r.to_degrees()).collect(),
+            ],
+        };
+        assert!(matches!(
+            robot.replay(&recording),
+            Err(Error::Kinematics(KinematicsError::NearSingular { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_singularity_refused_with_body_yaw() {
+        let mut robot = Robot::simulated(RobotConfig::wireless());
+        robot.set_torque(true).unwrap();
+        let (edge, _) = robot
+            .project_head_pose([0.0, 0.0, 100.0, 0.0, 0.0, 0.0])
+            .unwrap();
+        // The same platform pose relative to a body turned by 30 degrees
+        let t = in_body_frame(&xyzrpy_to_transform(edge, 172.0), -30f32.to_radians());
+        let turned = transform_to_xyzrpy(&t, 172.0);
+        assert!(
+            robot
+                .set_head_pose_and_body_yaw(turned, 30.0)
+                .unwrap()
+                .unwrap()
+                > 1000.0
+        );
+        robot.set_head_pose_and_body_yaw([0.0; 6], 30.0).unwrap();
+        settle();
+
+        robot.set_singularity_policy(SingularityPolicy::Refuse, 1000.0);
+        let before = robot.get_all_joints().unwrap();
+        match robot.set_head_pose_and_body_yaw(turned, 30.0) {
+            Err(Error::Kinematics(KinematicsError::NearSingular { .. })) => {}
+            other => panic!("expected a singularity error, got {:?}", other),
+        }
+        settle();
+        let after = robot.get_all_joints().unwrap();
+        assert!(
+            after.iter().zip(&before).all(|(a, b)| (a - b).abs() < 0.5),
+            "{:?}",
+            after
+        );
+        let (_, body_yaw) = robot.get_head_pose_and_body_yaw().unwrap();
+        assert!((body_yaw - 30.0).abs() < 1.0, "{}", body_yaw);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_head_pose_over_pty() {
+        use crate::serial::SimulatedPty;
+
+        let config = RobotConfig::lite();
+        let pty = SimulatedPty::spawn(SimulatedBus::new(&config.motor_ids())).unwrap();
+        let mut robot = Robot::open(pty.path(), config).unwrap();
+        robot.set_timeout(Duration::from_millis(200));
+
+        robot.set_torque(true).unwrap();
+        robot
+            .set_head_pose([0.0, 0.0, 10.0, 0.0, 0.0, 15.0])
+            .unwrap();
+        settle();
+
+        let pose = robot.get_head_pose().unwrap();
+        assert!((pose[2] - 10.0).abs() < 1.0, "z = {}", pose[2]);
+        assert!((pose[5] - 15.0).abs() < 1.0, "yaw = {}", pose[5]);
+        assert!(robot
+            .get_diagnostics()
+            .unwrap()
+            .iter()
+            .all(|m| m.responding));
+    }
+}
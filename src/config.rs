@@ -0,0 +1,785 @@
+//! # Robot Configuration
+//!
+//! Everything that differs between Reachy Mini variants: the joint table (bus
+//! IDs, directions, offsets, limits), the Stewart platform geometry and the
+//! default transport. Built-in profiles cover the Lite and the wireless
+//! version; any other robot is described in JSON:
+//!
+//! ```json
+//! {
+//!   "name": "my-mini",
+//!   "joints": [
+//!     { "name": "stewart_1", "id": 11, "kind": "head" },
+//!     ...
+//!     { "name": "r_antenna", "id": 18, "kind": "right_antenna", "direction": -1 }
+//!   ],
+//!   "geometry": {
+//!     "motor_arm_length": 0.038,
+//!     "rod_length": 0.09,
+//!     "head_z_offset_mm": 172.0,
+//!     "branches": [ { "branch_position": [...], "T_motor_world": [...], "solution": 0 }, ... ],
+//!     "interference": { "min_arm_height": 0.045, "max_ball_joint_angle": 1.047,
+//!                       "head_radius": 0.05, "body_height": 0.12 }
+//!   },
+//!   "body_yaw_limits": { "max_relative_yaw_deg": 65, "max_body_yaw_deg": 160 },
+//!   "transport": { "type": "websocket", "url": "ws://reachy-mini.local:8000/api/move/ws/raw/write" }
+//! }
+//! ```
+//!
+//! `connect()` accepts a profile name (`"lite"`, `"wireless"`) or such a JSON
+//! document.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::OnceLock;
+
+use nalgebra::{Matrix4, Vector3};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+use crate::joints::{Joint, JointKind, JointTable};
+use crate::kinematics::{
+    xyzrpy_to_transform, Calibration, CalibrationError, CalibrationSample, InterferenceModel,
+    Kinematics,
+};
+use crate::workspace::{Workspace, WorkspaceGrid};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Stewart platform branch geometry of the Reachy Mini (loaded at compile time)
+const MOTOR_JSON: &str = include_str!("motors.json");
+
+/// Motor ID of the body yaw joint on the wireless version
+const BODY_YAW_ID: u8 = 10;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Description of one robot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RobotConfig {
+    /// Profile name
+    pub name: String,
+    /// Joints in bus order
+    pub joints: Vec<Joint>,
+    pub geometry: Geometry,
+    /// How far the body turns, on robots with a body yaw joint
+    #[serde(default)]
+    pub body_yaw_limits: BodyYawLimits,
+    /// Transport `connect()` uses
+    #[serde(default)]
+    pub transport: Transport,
+}
+
+/// Stewart platform geometry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    /// Motor horn length in meters
+    pub motor_arm_length: f32,
+    /// Rod length in meters
+    pub rod_length: f32,
+    /// Head height (mm) reported as `z = 0`
+    pub head_z_offset_mm: f32,
+    /// One branch per head joint, in head joint order
+    pub branches: Vec<BranchGeometry>,
+    /// Mechanical envelope IK solutions are checked against; `null` to
+    /// disable the check
+    #[serde(default = "default_interference")]
+    pub interference: Option<InterferenceModel>,
+}
+
+/// Mounting of one head motor and its rod.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchGeometry {
+    /// Rod anchor on the platform, in the platform frame (m)
+    pub branch_position: [f32; 3],
+    /// Pose of the world frame in the motor frame
+    #[serde(rename = "T_motor_world")]
+    pub t_motor_world: [[f32; 4]; 4],
+    /// Which of the two IK solutions the branch uses (0 or 1)
+    pub solution: f32,
+    /// Motor horn length of this branch (m), e.g. after calibration; the
+    /// geometry's if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motor_arm_length: Option<f32>,
+    /// Rod length of this branch (m); the geometry's if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rod_length: Option<f32>,
+}
+
+/// Body yaw limits applied to head poses on robots with a body yaw joint,
+/// see [`Kinematics::inverse_kinematics_safe`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BodyYawLimits {
+    /// Largest head yaw relative to the body (degrees); the body follows a
+    /// head turned further
+    pub max_relative_yaw_deg: f32,
+    /// Largest body yaw (degrees)
+    pub max_body_yaw_deg: f32,
+}
+
+impl Default for BodyYawLimits {
+    fn default() -> Self {
+        Self {
+            max_relative_yaw_deg: 65.0,
+            max_body_yaw_deg: 160.0,
+        }
+    }
+}
+
+impl BodyYawLimits {
+    /// Whether both limits are positive.
+    pub fn is_valid(&self) -> bool {
+        self.max_relative_yaw_deg > 0.0 && self.max_body_yaw_deg > 0.0
+    }
+}
+
+/// Head joint readings at a known head pose, for
+/// [`RobotConfig::calibrate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoseSample {
+    /// `[x, y, z, roll, pitch, yaw]` (mm, degrees), e.g. set by a jig or
+    /// measured by a tracker
+    pub pose: [f32; 6],
+    /// Head joint angles read at that pose (degrees, head joint order)
+    pub joints_deg: Vec<f32>,
+}
+
+/// How `connect()` reaches the motor bus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Transport {
+    /// WebSocket on localhost, falling back to WebSerial
+    #[default]
+    Auto,
+    WebSocket {
+        url: String,
+    },
+    WebSerial,
+    /// In-process simulated bus
+    Simulated,
+}
+
+impl RobotConfig {
+    /// Reachy Mini Lite: 6 head motors and 2 antennas over USB.
+    pub fn lite() -> Self {
+        Self {
+            name: "lite".to_string(),
+            joints: JointTable::reachy_mini().all().to_vec(),
+            geometry: Geometry::reachy_mini(),
+            body_yaw_limits: BodyYawLimits::default(),
+            transport: Transport::Auto,
+        }
+    }
+
+    /// Wireless Reachy Mini: the Lite joints plus a body yaw motor.
+    pub fn wireless() -> Self {
+        let mut joints = vec![Joint::new("body_yaw", BODY_YAW_ID, JointKind::BodyYaw)];
+        joints.extend(JointTable::reachy_mini().all().iter().cloned());
+        Self {
+            name: "wireless".to_string(),
+            joints,
+            geometry: Geometry::reachy_mini(),
+            body_yaw_limits: BodyYawLimits::default(),
+            transport: Transport::Auto,
+        }
+    }
+
+    /// Built-in profile called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "lite" => Some(Self::lite()),
+            "wireless" => Some(Self::wireless()),
+            _ => None,
+        }
+    }
+
+    /// Resolve a built-in profile name or a JSON configuration.
+    pub fn resolve(profile_or_json: &str) -> Result<Self, ConfigError> {
+        let spec = profile_or_json.trim();
+        if spec.starts_with('{') {
+            Self::from_json(spec)
+        } else {
+            Self::builtin(spec).ok_or_else(|| ConfigError::UnknownProfile(spec.to_string()))
+        }
+    }
+
+    /// Parse and validate a JSON configuration.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let config: Self =
+            serde_json::from_str(json).map_err(|e| ConfigError::Json(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("RobotConfig always serializes")
+    }
+
+    /// Check the configuration describes a drivable robot.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut names = HashSet::new();
+        let mut ids = HashSet::new();
+        for joint in &self.joints {
+            if !names.insert(joint.name.as_str()) {
+                return Err(ConfigError::DuplicateJoint(joint.name.clone()));
+            }
+            if !ids.insert(joint.id) {
+                return Err(ConfigError::DuplicateId(joint.id));
+            }
+            let [min_deg, max_deg] = joint.limits_deg;
+            if min_deg.is_nan() || max_deg.is_nan() || min_deg > max_deg || joint.direction == 0.0 {
+                return Err(ConfigError::InvalidJoint(joint.name.clone()));
+            }
+        }
+
+        // Robots without a body yaw joint never use the limits
+        let has_body_yaw = self.joints.iter().any(|j| j.kind == JointKind::BodyYaw);
+        if has_body_yaw && !self.body_yaw_limits.is_valid() {
+            return Err(ConfigError::InvalidBodyYawLimits(self.body_yaw_limits));
+        }
+
+        let head = self
+            .joints
+            .iter()
+            .filter(|j| j.kind == JointKind::Head)
+            .count();
+        let branches = self.geometry.branches.len();
+        if head == 0 || head != branches {
+            return Err(ConfigError::BranchMismatch { head, branches });
+        }
+
+        self.geometry.validate()
+    }
+
+    /// Joint table of this robot.
+    pub fn joint_table(&self) -> JointTable {
+        JointTable::new(self.joints.clone())
+    }
+
+    /// Motor IDs of every joint, in bus order.
+    pub fn motor_ids(&self) -> Vec<u8> {
+        self.joints.iter().map(|j| j.id).collect()
+    }
+
+    /// Kinematics solver for this geometry, with branches named after the
+    /// head joints; see [`Geometry::kinematics`].
+    ///
+    /// Assumes the configuration is valid.
+    pub fn kinematics(&self) -> Kinematics {
+        let mut kinematics = self.geometry.kinematics();
+        let head: Vec<String> = self
+            .joint_table()
+            .head()
+            .into_iter()
+            .map(|j| j.name)
+            .collect();
+        kinematics.set_branch_names(&head);
+        kinematics
+    }
+
+    /// Replace the geometry, e.g. for a hardware revision with other arm
+    /// lengths or anchors, keeping the joints.
+    ///
+    /// # Errors
+    /// Returns [`ConfigError`] if the geometry is invalid or does not have
+    /// one branch per head joint; the configuration is left unchanged.
+    pub fn set_geometry(&mut self, geometry: Geometry) -> Result<(), ConfigError> {
+        let previous = std::mem::replace(&mut self.geometry, geometry);
+        if let Err(e) = self.validate() {
+            self.geometry = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Fit the geometry and the head motor zeros to joint readings at known
+    /// head poses, see [`Kinematics::calibrate`].
+    ///
+    /// # Returns
+    /// This configuration with per-branch arm and rod lengths, corrected
+    /// anchors and head joint offsets, and the fit details.
+    ///
+    /// # Errors
+    /// Returns [`ConfigError::Calibration`] if the samples are unusable, or
+    /// [`ConfigError::InvalidGeometry`] if the fitted geometry is.
+    pub fn calibrate(&self, samples: &[PoseSample]) -> Result<(Self, Calibration), ConfigError> {
+        let offset = self.geometry.head_z_offset_mm;
+        let samples: Vec<CalibrationSample> = samples
+            .iter()
+            .map(|sample| CalibrationSample {
+                t_world_platform: xyzrpy_to_transform(sample.pose, offset),
+                joint_angles: sample.joints_deg.iter().map(|d| d.to_radians()).collect(),
+            })
+            .collect();
+        let calibration = self.kinematics().calibrate(&samples)?;
+
+        let mut calibrated = self.clone();
+        for (branch, fit) in calibrated
+            .geometry
+            .branches
+            .iter_mut()
+            .zip(&calibration.branches)
+        {
+            for (position, offset) in branch.branch_position.iter_mut().zip(&fit.anchor_offset) {
+                *position += offset;
+            }
+            branch.motor_arm_length = Some(fit.motor_arm_length);
+            branch.rod_length = Some(fit.rod_length);
+        }
+        // The arm is at `reading + zero_offset`: shift where the joint zero
+        // sits on the motor
+        let head = calibrated
+            .joints
+            .iter_mut()
+            .filter(|j| j.kind == JointKind::Head);
+        for (joint, fit) in head.zip(&calibration.branches) {
+            joint.offset_deg -= joint.direction * fit.zero_offset.to_degrees();
+        }
+        calibrated.geometry.validate()?;
+        Ok((calibrated, calibration))
+    }
+
+    /// Reachable head poses of `grid`, within the head joint limits.
+    ///
+    /// Computed offline; see [`crate::workspace`].
+    pub fn workspace(&self, grid: &WorkspaceGrid) -> Workspace {
+        let limits: Vec<[f32; 2]> = self
+            .joint_table()
+            .head()
+            .iter()
+            .map(|j| j.limits_deg.map(f32::to_radians))
+            .collect();
+        self.kinematics()
+            .compute_workspace(grid, self.geometry.head_z_offset_mm, &limits)
+    }
+}
+
+impl Default for RobotConfig {
+    fn default() -> Self {
+        Self::lite()
+    }
+}
+
+impl Geometry {
+    /// Geometry shared by every Reachy Mini variant.
+    pub fn reachy_mini() -> Self {
+        // Parsed once: every built-in profile starts from it
+        static BRANCHES: OnceLock<Vec<BranchGeometry>> = OnceLock::new();
+        let branches = BRANCHES.get_or_init(|| {
+            serde_json::from_str(MOTOR_JSON).expect("Failed to parse motor configuration JSON")
+        });
+        Self {
+            motor_arm_length: 0.038,
+            rod_length: 0.09,
+            head_z_offset_mm: 172.0,
+            branches: branches.clone(),
+            interference: default_interference(),
+        }
+    }
+
+    /// Parse and validate a JSON geometry (the `geometry` object of a
+    /// configuration).
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let geometry: Self =
+            serde_json::from_str(json).map_err(|e| ConfigError::Json(e.to_string()))?;
+        geometry.validate()?;
+        Ok(geometry)
+    }
+
+    /// Serialize as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Geometry always serializes")
+    }
+
+    /// Check the geometry describes a working Stewart platform: 6 branches,
+    /// positive lengths, invertible motor poses and a reachable neutral pose.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| Err(ConfigError::InvalidGeometry(reason.to_string()));
+        if self.branches.len() != 6 {
+            return invalid("the Stewart platform needs 6 branches");
+        }
+        let positive = |length: f32| length > 0.0;
+        let mut overrides = self
+            .branches
+            .iter()
+            .flat_map(|b| b.motor_arm_length.into_iter().chain(b.rod_length));
+        if !positive(self.motor_arm_length)
+            || !positive(self.rod_length)
+            || !overrides.all(positive)
+        {
+            return invalid("motor_arm_length and rod_length must be positive");
+        }
+        if !self.head_z_offset_mm.is_finite() {
+            return invalid("head_z_offset_mm must be finite");
+        }
+        if self.branches.iter().any(|b| {
+            b.t_motor_world().try_inverse().is_none()
+                || b.branch_position.iter().any(|v| !v.is_finite())
+        }) {
+            return invalid("branch positions must be finite and T_motor_world invertible");
+        }
+        let neutral = xyzrpy_to_transform([0.0; 6], self.head_z_offset_mm);
+        if self.kinematics().inverse_kinematics(neutral, None).is_err() {
+            return invalid("the neutral head pose must be reachable without interference");
+        }
+        Ok(())
+    }
+
+    /// Kinematics solver for this geometry, with forward kinematics starting
+    /// from the neutral head pose and the interference model applied.
+    ///
+    /// Assumes the geometry is valid.
+    pub fn kinematics(&self) -> Kinematics {
+        let mut kinematics = Kinematics::new(self.motor_arm_length, self.rod_length);
+        for (k, branch) in self.branches.iter().enumerate() {
+            let solution = if branch.solution != 0.0 { 1.0 } else { -1.0 };
+            kinematics.add_branch(
+                Vector3::from(branch.branch_position),
+                branch.t_motor_world().try_inverse().unwrap(),
+                solution,
+            );
+            kinematics.set_branch_lengths(
+                k,
+                branch.motor_arm_length.unwrap_or(self.motor_arm_length),
+                branch.rod_length.unwrap_or(self.rod_length),
+            );
+        }
+        let neutral = xyzrpy_to_transform([0.0; 6], self.head_z_offset_mm);
+        kinematics.reset_forward_kinematics(neutral);
+        // Only fails for an unreachable neutral pose, which validation rejects
+        let _ = kinematics.set_interference_model(self.interference, neutral);
+        kinematics
+    }
+}
+
+fn default_interference() -> Option<InterferenceModel> {
+    Some(InterferenceModel::reachy_mini())
+}
+
+impl BranchGeometry {
+    /// `T_motor_world` as a matrix.
+    pub fn t_motor_world(&self) -> Matrix4<f32> {
+        Matrix4::from_fn(|row, col| self.t_motor_world[row][col])
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Invalid robot configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// Not a built-in profile name
+    UnknownProfile(String),
+    /// Malformed JSON or missing fields
+    Json(String),
+    DuplicateJoint(String),
+    DuplicateId(u8),
+    /// Reversed limits or a zero direction
+    InvalidJoint(String),
+    /// Body yaw limits that are not both positive
+    InvalidBodyYawLimits(BodyYawLimits),
+    /// The head joints and the geometry branches don't match one to one
+    BranchMismatch {
+        head: usize,
+        branches: usize,
+    },
+    InvalidGeometry(String),
+    Calibration(CalibrationError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            ConfigError::UnknownProfile(name) => format!(
+                "Unknown robot profile '{}' (expected 'lite', 'wireless' or a JSON configuration)",
+                name
+            ),
+            ConfigError::Json(msg) => format!("Invalid robot configuration: {}", msg),
+            ConfigError::DuplicateJoint(name) => format!("Joint '{}' is defined twice", name),
+            ConfigError::DuplicateId(id) => format!("Motor ID {} is used twice", id),
+            ConfigError::InvalidJoint(name) => {
+                format!("Joint '{}' has reversed limits or a zero direction", name)
+            }
+            ConfigError::InvalidBodyYawLimits(limits) => format!(
+                "Body yaw limits must be positive (max relative yaw {}°, max body yaw {}°)",
+                limits.max_relative_yaw_deg, limits.max_body_yaw_deg
+            ),
+            ConfigError::BranchMismatch { head, branches } => {
+                format!("{} head joints but {} geometry branches", head, branches)
+            }
+            ConfigError::InvalidGeometry(msg) => format!("Invalid geometry: {}", msg),
+            ConfigError::Calibration(e) => format!("Calibration failed: {}", e),
+        })
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<CalibrationError> for ConfigError {
+    fn from(e: CalibrationError) -> Self {
+        ConfigError::Calibration(e)
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<ConfigError> for JsValue {
+    fn from(e: ConfigError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joints::ids;
+
+    #[test]
+    fn test_builtin_profiles() {
+        let lite = RobotConfig::resolve("lite").unwrap();
+        assert_eq!(lite, RobotConfig::default());
+        assert_eq!(lite.validate(), Ok(()));
+        assert_eq!(lite.motor_ids(), vec![11, 12, 13, 14, 15, 16, 17, 18]);
+
+        let wireless = RobotConfig::resolve("wireless").unwrap();
+        assert_eq!(wireless.validate(), Ok(()));
+        let table = wireless.joint_table();
+        assert_eq!(ids(&table.of_kind(JointKind::BodyYaw)), vec![BODY_YAW_ID]);
+        assert_eq!(ids(&table.head_and_antennas()), lite.motor_ids());
+
+        assert_eq!(
+            RobotConfig::resolve("pro"),
+            Err(ConfigError::UnknownProfile("pro".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip_with_defaults() {
+        let wireless = RobotConfig::wireless();
+        assert_eq!(RobotConfig::resolve(&wireless.to_json()), Ok(wireless));
+
+        // Omitted direction, offset, limits and transport take their defaults
+        let mut value: serde_json::Value =
+            serde_json::from_str(&RobotConfig::lite().to_json()).unwrap();
+        value["joints"][0] = serde_json::json!({ "name": "stewart_1", "id": 11, "kind": "head" });
+        value["transport"] = serde_json::json!({ "type": "websocket", "url": "ws://mini:8000" });
+        let config = RobotConfig::from_json(&value.to_string()).unwrap();
+        assert_eq!(
+            config.joints[0],
+            Joint::new("stewart_1", 11, JointKind::Head)
+        );
+        assert_eq!(
+            config.transport,
+            Transport::WebSocket {
+                url: "ws://mini:8000".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut config = RobotConfig::lite();
+        config.joints[7].id = 17;
+        assert_eq!(config.validate(), Err(ConfigError::DuplicateId(17)));
+
+        let mut config = RobotConfig::lite();
+        config.joints.remove(0);
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::BranchMismatch {
+                head: 5,
+                branches: 6
+            })
+        );
+
+        let mut config = RobotConfig::lite();
+        config.joints[6].limits_deg = [10.0, -10.0];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidJoint(name)) if name == "l_antenna"
+        ));
+
+        let mut config = RobotConfig::wireless();
+        config.body_yaw_limits.max_relative_yaw_deg = -65.0;
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidBodyYawLimits(config.body_yaw_limits))
+        );
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .starts_with("Body yaw limits must be positive"));
+        // The Lite has no body yaw joint to limit
+        let mut config = RobotConfig::lite();
+        config.body_yaw_limits.max_body_yaw_deg = 0.0;
+        assert_eq!(config.validate(), Ok(()));
+
+        // The head must rest above the body
+        let mut config = RobotConfig::lite();
+        config.geometry.interference.as_mut().unwrap().body_height = 0.2;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidGeometry(_))
+        ));
+
+        assert!(matches!(
+            RobotConfig::from_json("{\"name\": \"x\"}"),
+            Err(ConfigError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_kinematics_from_geometry() {
+        let config = RobotConfig::lite();
+        let mut kinematics = config.kinematics();
+        let t = Matrix4::new_translation(&Vector3::new(
+            0.0,
+            0.0,
+            config.geometry.head_z_offset_mm / 1000.0,
+        ));
+        let joints = kinematics.inverse_kinematics(t, None).unwrap();
+        assert_eq!(joints.len(), 6);
+        assert!(joints.iter().all(|j| j.is_finite()));
+    }
+
+    #[test]
+    fn test_calibrate_geometry() {
+        // The actual robot: longer rods, a moved anchor and shifted zeros
+        let mut actual = RobotConfig::lite();
+        for (k, branch) in actual.geometry.branches.iter_mut().enumerate() {
+            branch.rod_length = Some(0.0905);
+            branch.branch_position[2] += 0.0003;
+            actual.joints[k].offset_deg += k as f32 - 2.5;
+        }
+        actual.validate().unwrap();
+
+        // Readings through the nominal joint table, at scattered poses
+        let nominal = RobotConfig::lite();
+        let mut kinematics = actual.kinematics();
+        let samples: Vec<PoseSample> = (0..30)
+            .map(|i| {
+                let i = i as f32;
+                let pose = [
+                    8.0 * (1.3 * i).sin(),
+                    8.0 * (2.1 * i).cos(),
+                    6.0 * (0.7 * i).sin(),
+                    10.0 * (1.7 * i).cos(),
+                    10.0 * (0.9 * i).sin(),
+                    15.0 * (1.1 * i).cos(),
+                ];
+                let t = xyzrpy_to_transform(pose, 172.0);
+                let angles = kinematics.inverse_kinematics(t, None).unwrap();
+                let joints_deg = (0..6)
+                    .map(|k| {
+                        let motor = actual.joints[k].motor_radians(angles[k].to_degrees());
+                        nominal.joints[k].joint_degrees(motor)
+                    })
+                    .collect();
+                PoseSample { pose, joints_deg }
+            })
+            .collect();
+
+        let (calibrated, calibration) = nominal.calibrate(&samples).unwrap();
+        assert!(calibration.rms_after < 1e-6);
+        for k in 0..6 {
+            let branch = &calibrated.geometry.branches[k];
+            assert!((branch.rod_length.unwrap() - 0.0905).abs() < 1e-5);
+            assert!((branch.motor_arm_length.unwrap() - 0.038).abs() < 1e-5);
+            let expected = actual.geometry.branches[k].branch_position[2];
+            assert!((branch.branch_position[2] - expected).abs() < 1e-5);
+            let offset = calibrated.joints[k].offset_deg - actual.joints[k].offset_deg;
+            assert!(offset.abs() < 0.01, "{}", offset);
+        }
+
+        // The file round-trips, per-branch lengths included
+        let reloaded = RobotConfig::from_json(&calibrated.to_json()).unwrap();
+        assert_eq!(reloaded, calibrated);
+
+        assert!(matches!(
+            nominal.calibrate(&samples[..3]),
+            Err(ConfigError::Calibration(_))
+        ));
+    }
+
+    #[test]
+    fn test_geometry_from_json() {
+        // A prototype with longer motor arms
+        let mut value: serde_json::Value =
+            serde_json::from_str(&Geometry::reachy_mini().to_json()).unwrap();
+        value["motor_arm_length"] = serde_json::json!(0.042);
+        let geometry = Geometry::from_json(&value.to_string()).unwrap();
+        assert_eq!(geometry.motor_arm_length, 0.042);
+
+        let mut config = RobotConfig::lite();
+        config.set_geometry(geometry.clone()).unwrap();
+        let neutral = xyzrpy_to_transform([0.0; 6], 172.0);
+        let longer = config
+            .kinematics()
+            .inverse_kinematics(neutral, None)
+            .unwrap();
+        let stock = RobotConfig::lite()
+            .kinematics()
+            .inverse_kinematics(neutral, None)
+            .unwrap();
+        assert!(longer.iter().zip(&stock).any(|(a, b)| (a - b).abs() > 1e-3));
+
+        // Invalid documents are rejected with the reason
+        value["rod_length"] = serde_json::json!(-0.09);
+        assert!(matches!(
+            Geometry::from_json(&value.to_string()),
+            Err(ConfigError::InvalidGeometry(_))
+        ));
+        value["rod_length"] = serde_json::json!(0.02);
+        assert!(matches!(
+            Geometry::from_json(&value.to_string()),
+            Err(ConfigError::InvalidGeometry(reason)) if reason.contains("neutral")
+        ));
+        value["branches"].as_array_mut().unwrap().pop();
+        assert!(Geometry::from_json(&value.to_string()).is_err());
+        assert!(matches!(
+            Geometry::from_json("[]"),
+            Err(ConfigError::Json(_))
+        ));
+
+        // A failed replacement keeps the previous geometry
+        let mut short = geometry;
+        short.rod_length = 0.02;
+        assert!(config.set_geometry(short).is_err());
+        assert_eq!(config.geometry.motor_arm_length, 0.042);
+    }
+
+    #[test]
+    fn test_interference_model_optional() {
+        let low = xyzrpy_to_transform([0.0, 0.0, -40.0, 0.0, 0.0, 0.0], 172.0);
+        assert!(RobotConfig::lite()
+            .kinematics()
+            .inverse_kinematics(low, None)
+            .is_err());
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(&RobotConfig::lite().to_json()).unwrap();
+        value["geometry"]["interference"] = serde_json::Value::Null;
+        let config = RobotConfig::from_json(&value.to_string()).unwrap();
+        assert!(config.kinematics().inverse_kinematics(low, None).is_ok());
+
+        // Configurations saved before the model existed get the default
+        value["geometry"]
+            .as_object_mut()
+            .unwrap()
+            .remove("interference");
+        let config = RobotConfig::from_json(&value.to_string()).unwrap();
+        assert_eq!(config, RobotConfig::lite());
+    }
+}
@@ -0,0 +1,505 @@
+//! # Daemon Backend
+//!
+//! Client for the high-level JSON API of the Reachy Mini daemon, used when the
+//! daemon does not expose raw bus access (`/api/move/ws/raw/write`).
+//!
+//! ## Endpoint Mapping
+//!
+//! | API call                          | Method | Endpoint                      |
+//! |-----------------------------------|--------|-------------------------------|
+//! | `get_head_pose`, `get_antennas`   | GET    | `/api/state/full`             |
+//! | `set_head_pose`, `set_antennas`   | POST   | `/api/move/set_target`        |
+//! | `enable_torque`, `disable_torque` | POST   | `/api/motors/set_mode/{mode}` |
+//!
+//! ## Units
+//!
+//! The daemon speaks meters and radians, with antennas ordered `[right, left]`.
+//! This module converts to and from the crate's millimeters, degrees and
+//! `[left, right]` order. Head poses use the same Z = 0 reference; body yaw
+//! is sent with head targets and reported as 0 by daemons without one.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default daemon address
+pub const DEFAULT_DAEMON_URL: &str = "http://localhost:8000";
+
+/// Full robot state endpoint
+const STATE_PATH: &str = "/api/state/full";
+
+/// Streaming target endpoint
+const SET_TARGET_PATH: &str = "/api/move/set_target";
+
+/// Motor mode endpoint prefix
+const SET_MODE_PATH: &str = "/api/motors/set_mode";
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+/// HTTP method of a daemon request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// A daemon request, independent of the HTTP client used to send it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaemonRequest {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+/// Head pose as exchanged with the daemon (meters, radians).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct DaemonPose {
+    x: f32,
+    y: f32,
+    z: f32,
+    roll: f32,
+    pitch: f32,
+    yaw: f32,
+}
+
+impl DaemonPose {
+    /// Convert from `[x, y, z, roll, pitch, yaw]` in mm and degrees.
+    fn from_xyzrpy(pose: [f32; 6]) -> Self {
+        Self {
+            x: pose[0] / 1000.0,
+            y: pose[1] / 1000.0,
+            z: pose[2] / 1000.0,
+            roll: pose[3].to_radians(),
+            pitch: pose[4].to_radians(),
+            yaw: pose[5].to_radians(),
+        }
+    }
+
+    /// Convert to `[x, y, z, roll, pitch, yaw]` in mm and degrees.
+    fn to_xyzrpy(self) -> [f32; 6] {
+        [
+            self.x * 1000.0,
+            self.y * 1000.0,
+            self.z * 1000.0,
+            self.roll.to_degrees(),
+            self.pitch.to_degrees(),
+            self.yaw.to_degrees(),
+        ]
+    }
+}
+
+/// Subset of `/api/state/full` used by this backend.
+#[derive(Debug, Deserialize)]
+struct DaemonState {
+    head_pose: DaemonPose,
+    /// `[right, left]` in radians
+    antennas_position: [f32; 2],
+    /// Radians; missing or `null` without a body yaw motor
+    #[serde(default)]
+    body_yaw: Option<f32>,
+}
+
+/// Request reading the full robot state.
+fn state_request() -> DaemonRequest {
+    DaemonRequest {
+        method: Method::Get,
+        path: STATE_PATH.to_string(),
+        body: None,
+    }
+}
+
+/// Request moving the head to `pose` (`[x, y, z, roll, pitch, yaw]`, mm/deg)
+/// and the body to `body_yaw_deg` if given.
+fn head_target_request(pose: [f32; 6], body_yaw_deg: Option<f32>) -> DaemonRequest {
+    let mut target = json!({ "target_head_pose": DaemonPose::from_xyzrpy(pose) });
+    if let Some(body_yaw) = body_yaw_deg {
+        target["target_body_yaw"] = json!(body_yaw.to_radians());
+    }
+    DaemonRequest {
+        method: Method::Post,
+        path: SET_TARGET_PATH.to_string(),
+        body: Some(target),
+    }
+}
+
+/// Request moving both antennas (degrees).
+fn antennas_target_request(left_deg: f32, right_deg: f32) -> DaemonRequest {
+    DaemonRequest {
+        method: Method::Post,
+        path: SET_TARGET_PATH.to_string(),
+        body: Some(json!({
+            "target_antennas": [right_deg.to_radians(), left_deg.to_radians()]
+        })),
+    }
+}
+
+/// Request enabling or disabling motor torque.
+fn torque_request(enable: bool) -> DaemonRequest {
+    let mode = if enable { "enabled" } else { "disabled" };
+    DaemonRequest {
+        method: Method::Post,
+        path: format!("{}/{}", SET_MODE_PATH, mode),
+        body: None,
+    }
+}
+
+fn parse_state(value: Value) -> Result<DaemonState, DaemonError> {
+    serde_json::from_value(value).map_err(|e| DaemonError::InvalidResponse(e.to_string()))
+}
+
+// ============================================================================
+// Client
+// ============================================================================
+
+/// Client for the daemon's high-level HTTP API.
+pub struct DaemonClient {
+    base_url: String,
+}
+
+impl DaemonClient {
+    /// Create a client for the daemon at `base_url` (e.g. `http://localhost:8000`).
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Current head pose as `[x, y, z, roll, pitch, yaw]` (mm, degrees) and
+    /// body yaw (degrees).
+    pub async fn get_head_pose(&self) -> Result<([f32; 6], f32), DaemonError> {
+        let state = parse_state(self.execute(&state_request()).await?)?;
+        let body_yaw = state.body_yaw.unwrap_or(0.0).to_degrees();
+        Ok((state.head_pose.to_xyzrpy(), body_yaw))
+    }
+
+    /// Move the head to `[x, y, z, roll, pitch, yaw]` (mm, degrees), and the
+    /// body to `body_yaw_deg` if given.
+    pub async fn set_head_pose(
+        &self,
+        pose: [f32; 6],
+        body_yaw_deg: Option<f32>,
+    ) -> Result<(), DaemonError> {
+        self.execute(&head_target_request(pose, body_yaw_deg))
+            .await?;
+        Ok(())
+    }
+
+    /// Current antenna positions as `[left, right]` in degrees.
+    pub async fn get_antennas(&self) -> Result<[f32; 2], DaemonError> {
+        let state = parse_state(self.execute(&state_request()).await?)?;
+        let [right, left] = state.antennas_position;
+        Ok([left.to_degrees(), right.to_degrees()])
+    }
+
+    /// Move both antennas (degrees).
+    pub async fn set_antennas(&self, left_deg: f32, right_deg: f32) -> Result<(), DaemonError> {
+        self.execute(&antennas_target_request(left_deg, right_deg))
+            .await?;
+        Ok(())
+    }
+
+    /// Enable or disable torque on all motors.
+    pub async fn set_torque(&self, enable: bool) -> Result<(), DaemonError> {
+        self.execute(&torque_request(enable)).await?;
+        Ok(())
+    }
+
+    /// Send a request and decode its JSON response (`null` if the body is empty).
+    async fn execute(&self, request: &DaemonRequest) -> Result<Value, DaemonError> {
+        let url = format!("{}{}", self.base_url, request.path);
+        let (status, body) = http::send(&url, request).await?;
+        if !(200..300).contains(&status) {
+            return Err(DaemonError::Status(status, body));
+        }
+        if body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&body).map_err(|e| DaemonError::InvalidResponse(e.to_string()))
+    }
+}
+
+// ============================================================================
+// HTTP Transport
+// ============================================================================
+
+/// Browser HTTP client (fetch).
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod http {
+    use super::{DaemonError, DaemonRequest, Method};
+    use gloo::net::http::Request;
+
+    pub async fn send(url: &str, request: &DaemonRequest) -> Result<(u16, String), DaemonError> {
+        let builder = match request.method {
+            Method::Get => Request::get(url),
+            Method::Post => Request::post(url),
+        };
+        let pending = match &request.body {
+            Some(body) => builder.json(body),
+            None => builder.build(),
+        }
+        .map_err(|e| DaemonError::Http(e.to_string()))?;
+
+        let response = pending
+            .send()
+            .await
+            .map_err(|e| DaemonError::Http(e.to_string()))?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| DaemonError::Http(e.to_string()))?;
+        Ok((response.status(), body))
+    }
+}
+
+/// Minimal blocking HTTP/1.1 client for native builds and tests.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+mod http {
+    use super::{DaemonError, DaemonRequest, Method};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    pub async fn send(url: &str, request: &DaemonRequest) -> Result<(u16, String), DaemonError> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| DaemonError::Http(format!("unsupported URL '{}'", url)))?;
+        let (host, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+
+        let method = match request.method {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        };
+        let body = request
+            .body
+            .as_ref()
+            .map(|b| b.to_string())
+            .unwrap_or_default();
+
+        let io_err = |e: std::io::Error| DaemonError::Http(e.to_string());
+        let mut stream = TcpStream::connect(host).map_err(io_err)?;
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            host,
+            body.len(),
+            body
+        )
+        .map_err(io_err)?;
+
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).map_err(io_err)?;
+
+        let (head, body) = raw
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| DaemonError::Http("malformed HTTP response".to_string()))?;
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| DaemonError::Http("malformed HTTP status line".to_string()))?;
+        Ok((status, body.to_string()))
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Daemon communication error
+#[derive(Debug, Clone, PartialEq)]
+pub enum DaemonError {
+    /// The request could not be sent or the response could not be read
+    Http(String),
+    /// The daemon answered with a non-2xx status
+    Status(u16, String),
+    /// The response body did not have the expected shape
+    InvalidResponse(String),
+}
+
+impl fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            DaemonError::Http(reason) => format!("Daemon request failed: {}", reason),
+            DaemonError::Status(code, body) => format!("Daemon returned {}: {}", code, body),
+            DaemonError::InvalidResponse(reason) => {
+                format!("Invalid daemon response: {}", reason)
+            }
+        })
+    }
+}
+
+impl std::error::Error for DaemonError {}
+
+#[cfg(feature = "wasm")]
+impl From<DaemonError> for JsValue {
+    fn from(e: DaemonError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Local stub of the daemon's endpoints, serving `requests` connections.
+    ///
+    /// Keeps a head pose and antenna state that `set_target` updates and
+    /// `state/full` reports, like the real daemon. Returns the base URL and a
+    /// handle yielding the `(request line, body)` pairs it received.
+    fn spawn_stub_daemon(requests: usize) -> (String, thread::JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut head_pose =
+                json!({"x": 0.0, "y": 0.0, "z": 0.0, "roll": 0.0, "pitch": 0.0, "yaw": 0.0});
+            let mut antennas = json!([0.0, 0.0]);
+            let mut body_yaw = Value::Null;
+            let mut log = Vec::new();
+
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some(len) = header.strip_prefix("Content-Length: ") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+
+                let request_line = request_line.trim().to_string();
+                let response = if request_line.starts_with("GET /api/state/full") {
+                    json!({
+                        "control_mode": "enabled",
+                        "head_pose": head_pose,
+                        "antennas_position": antennas,
+                        "body_yaw": body_yaw,
+                    })
+                    .to_string()
+                } else if request_line.starts_with("POST /api/move/set_target") {
+                    let target: Value = serde_json::from_str(&body).unwrap();
+                    if let Some(pose) = target.get("target_head_pose") {
+                        head_pose = pose.clone();
+                    }
+                    if let Some(a) = target.get("target_antennas") {
+                        antennas = a.clone();
+                    }
+                    if let Some(yaw) = target.get("target_body_yaw") {
+                        body_yaw = yaw.clone();
+                    }
+                    String::new()
+                } else if request_line.starts_with("POST /api/motors/set_mode/") {
+                    String::new()
+                } else {
+                    "not found".to_string()
+                };
+
+                let status = if response == "not found" {
+                    "404 Not Found"
+                } else {
+                    "200 OK"
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\n\r\n{}",
+                    status,
+                    response.len(),
+                    response
+                )
+                .unwrap();
+                log.push((request_line, body));
+            }
+            log
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_head_pose_roundtrip_through_stub() {
+        let (url, stub) = spawn_stub_daemon(2);
+        let client = DaemonClient::new(&url);
+
+        block_on(client.set_head_pose([10.0, -5.0, 20.0, 0.0, 15.0, -30.0], Some(25.0))).unwrap();
+        let (pose, body_yaw) = block_on(client.get_head_pose()).unwrap();
+        assert!((body_yaw - 25.0).abs() < 1e-3);
+
+        let expected = [10.0, -5.0, 20.0, 0.0, 15.0, -30.0];
+        assert!(pose
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+
+        let log = stub.join().unwrap();
+        let sent: Value = serde_json::from_str(&log[0].1).unwrap();
+        assert!((sent["target_head_pose"]["z"].as_f64().unwrap() - 0.02).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_antennas_are_reordered() {
+        let (url, stub) = spawn_stub_daemon(2);
+        let client = DaemonClient::new(&url);
+
+        block_on(client.set_antennas(45.0, -30.0)).unwrap();
+        let [left, right] = block_on(client.get_antennas()).unwrap();
+        assert!((left - 45.0).abs() < 1e-3);
+        assert!((right + 30.0).abs() < 1e-3);
+
+        // The daemon receives [right, left] in radians
+        let log = stub.join().unwrap();
+        let sent: Value = serde_json::from_str(&log[0].1).unwrap();
+        let right_rad = sent["target_antennas"][0].as_f64().unwrap() as f32;
+        assert!((right_rad - (-30.0f32).to_radians()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_torque_and_error_status() {
+        let (url, stub) = spawn_stub_daemon(1);
+        let client = DaemonClient::new(&format!("{}/", url));
+        block_on(client.set_torque(false)).unwrap();
+        let log = stub.join().unwrap();
+        assert_eq!(log[0].0, "POST /api/motors/set_mode/disabled HTTP/1.1");
+
+        let (url, _stub) = spawn_stub_daemon(1);
+        let request = DaemonRequest {
+            method: Method::Get,
+            path: "/api/unknown".to_string(),
+            body: None,
+        };
+        let result = block_on(DaemonClient::new(&url).execute(&request));
+        assert!(matches!(result, Err(DaemonError::Status(404, _))));
+    }
+}
@@ -4,14 +4,23 @@
 //!
 //! ## XL330 Control Table (commonly used addresses)
 //!
-//! | Address | Name                | Size | Access |
-//! |---------|---------------------|------|--------|
-//! | 64      | Torque Enable       | 1    | RW     |
-//! | 116     | Goal Position       | 4    | RW     |
-//! | 126     | Present Load        | 2    | R      |
-//! | 132     | Present Position    | 4    | R      |
-//! | 146     | Present Temperature | 1    | R      |
-
+//! | Address | Name                  | Size | Access |
+//! |---------|-----------------------|------|--------|
+//! | 0       | Model Number          | 2    | R      |
+//! | 7       | ID                    | 1    | RW     |
+//! | 64      | Torque Enable         | 1    | RW     |
+//! | 70      | Hardware Error Status | 1    | R      |
+//! | 112     | Profile Velocity      | 4    | RW     |
+//! | 116     | Goal Position         | 4    | RW     |
+//! | 126     | Present Load          | 2    | R      |
+//! | 128     | Present Velocity      | 4    | R      |
+//! | 132     | Present Position      | 4    | R      |
+//! | 144     | Present Input Voltage | 2    | R      |
+//! | 146     | Present Temperature   | 1    | R      |
+
+use std::fmt;
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::JsValue;
 
 // ============================================================================
@@ -23,22 +32,67 @@ pub const BROADCAST_ID: u8 = 0xFE;
 
 /// XL330 control table addresses
 pub mod address {
+    pub const MODEL_NUMBER: u16 = 0;
+    pub const FIRMWARE_VERSION: u16 = 6;
+    pub const ID: u16 = 7;
+    pub const RETURN_DELAY_TIME: u16 = 9;
     pub const TORQUE_ENABLE: u16 = 64;
+    pub const HARDWARE_ERROR_STATUS: u16 = 70;
+    pub const PROFILE_VELOCITY: u16 = 112;
     pub const GOAL_POSITION: u16 = 116;
+    pub const MOVING: u16 = 122;
     pub const PRESENT_LOAD: u16 = 126;
+    pub const PRESENT_VELOCITY: u16 = 128;
     pub const PRESENT_POSITION: u16 = 132;
+    pub const PRESENT_INPUT_VOLTAGE: u16 = 144;
     pub const PRESENT_TEMPERATURE: u16 = 146;
 }
 
 /// Dynamixel Protocol 2.0 instruction codes
-mod instruction {
+pub mod instruction {
+    pub const PING: u8 = 0x01;
     pub const READ: u8 = 0x02;
+    pub const WRITE: u8 = 0x03;
     pub const REBOOT: u8 = 0x08;
     pub const SYNC_READ: u8 = 0x82;
     pub const SYNC_WRITE: u8 = 0x83;
     pub const STATUS: u8 = 0x55;
 }
 
+/// Error codes carried in the error byte of a status packet
+pub mod status_error {
+    pub const INSTRUCTION: u8 = 0x02;
+    pub const CRC: u8 = 0x03;
+    pub const DATA_LENGTH: u8 = 0x05;
+    pub const ACCESS: u8 = 0x07;
+    /// Set when the motor has a hardware error (see Hardware Error Status)
+    pub const ALERT: u8 = 0x80;
+}
+
+/// Bits of the Hardware Error Status register (address 70)
+pub mod hardware_error {
+    pub const INPUT_VOLTAGE: u8 = 0x01;
+    pub const OVERHEATING: u8 = 0x04;
+    pub const MOTOR_ENCODER: u8 = 0x08;
+    pub const ELECTRICAL_SHOCK: u8 = 0x10;
+    pub const OVERLOAD: u8 = 0x20;
+
+    /// Human-readable names of the bits set in `status`.
+    pub fn names(status: u8) -> Vec<&'static str> {
+        [
+            (INPUT_VOLTAGE, "input voltage"),
+            (OVERHEATING, "overheating"),
+            (MOTOR_ENCODER, "motor encoder"),
+            (ELECTRICAL_SHOCK, "electrical shock"),
+            (OVERLOAD, "overload"),
+        ]
+        .iter()
+        .filter(|(bit, _)| status & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
 // ============================================================================
 // CRC Calculation
 // ============================================================================
@@ -169,6 +223,34 @@ pub fn build_read_packet(motor_id: u8, addr: u16, length: u16) -> Vec<u8> {
         .build()
 }
 
+/// Build PING packet. Use [`BROADCAST_ID`] to ping every motor on the bus.
+#[inline]
+#[allow(dead_code)]
+pub fn build_ping_packet(motor_id: u8) -> Vec<u8> {
+    PacketBuilder::new(motor_id, 10)
+        .instruction(instruction::PING, 0)
+        .build()
+}
+
+/// Build WRITE packet writing `data` at `addr` of a single motor.
+#[allow(dead_code)]
+pub fn build_write_packet(motor_id: u8, addr: u16, data: &[u8]) -> Vec<u8> {
+    PacketBuilder::new(motor_id, 12 + data.len())
+        .instruction(instruction::WRITE, 2 + data.len() as u16)
+        .u16_le(addr)
+        .bytes(data)
+        .build()
+}
+
+/// Build a STATUS packet, as sent by a motor in response to an instruction.
+pub fn build_status_packet(motor_id: u8, error: u8, params: &[u8]) -> Vec<u8> {
+    PacketBuilder::new(motor_id, 11 + params.len())
+        .instruction(instruction::STATUS, 1 + params.len() as u16)
+        .u8(error)
+        .bytes(params)
+        .build()
+}
+
 /// Build REBOOT packet for a single motor.
 #[inline]
 pub fn build_reboot_packet(motor_id: u8) -> Vec<u8> {
@@ -231,6 +313,18 @@ pub fn build_sync_write_position_radians(motor_ids: &[u8], radians: &[f32]) -> V
     build_sync_write_position(motor_ids, &positions)
 }
 
+/// Build SYNC_READ of `length` bytes at `addr` from multiple motors.
+pub fn build_sync_read(motor_ids: &[u8], addr: u16, length: u16) -> Vec<u8> {
+    let param_len = 4 + motor_ids.len() as u16;
+
+    PacketBuilder::new(BROADCAST_ID, 14 + motor_ids.len())
+        .instruction(instruction::SYNC_READ, param_len)
+        .u16_le(addr)
+        .u16_le(length)
+        .bytes(motor_ids)
+        .build()
+}
+
 /// Build SYNC_READ for temperature from multiple motors.
 pub fn build_sync_read_temperature(motor_ids: &[u8]) -> Vec<u8> {
     let param_len = 4 + motor_ids.len() as u16;
@@ -266,23 +360,32 @@ pub enum ParseError {
     InvalidHeader,
     InvalidInstruction,
     InvalidLength,
+    InvalidCrc,
     MotorError(u8),
 }
 
-impl From<ParseError> for JsValue {
-    fn from(e: ParseError) -> Self {
-        JsValue::from_str(match e {
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
             ParseError::TooShort => "Packet too short",
             ParseError::InvalidHeader => "Invalid header",
             ParseError::InvalidInstruction => "Invalid instruction",
             ParseError::InvalidLength => "Invalid length",
-            ParseError::MotorError(code) => {
-                return JsValue::from_str(&format!("Motor error: 0x{:02X}", code))
-            }
+            ParseError::InvalidCrc => "Invalid CRC",
+            ParseError::MotorError(code) => return write!(f, "Motor error: 0x{:02X}", code),
         })
     }
 }
 
+impl std::error::Error for ParseError {}
+
+#[cfg(feature = "wasm")]
+impl From<ParseError> for JsValue {
+    fn from(e: ParseError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
 /// Validate packet header and return (id, length, error_byte, data_start)
 #[inline]
 fn validate_header(data: &[u8], min_len: usize) -> Result<(u8, u16, u8, usize), ParseError> {
@@ -307,17 +410,78 @@ fn validate_header(data: &[u8], min_len: usize) -> Result<(u8, u16, u8, usize),
     Ok((id, length, error, 9))
 }
 
+/// A decoded instruction packet (host to motor).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionPacket {
+    pub id: u8,
+    pub instruction: u8,
+    pub params: Vec<u8>,
+}
+
+/// Parse one instruction packet at the start of `data`, verifying its CRC.
+///
+/// Returns the packet and the number of bytes it occupies, so callers can
+/// walk a buffer holding several concatenated packets.
+pub fn parse_instruction_packet(data: &[u8]) -> Result<(InstructionPacket, usize), ParseError> {
+    // Smallest packet: header(4) + id(1) + len(2) + instr(1) + crc(2) = 10
+    if data.len() < 10 {
+        return Err(ParseError::TooShort);
+    }
+    if data[0..4] != [0xFF, 0xFF, 0xFD, 0x00] {
+        return Err(ParseError::InvalidHeader);
+    }
+
+    let length = u16::from_le_bytes([data[5], data[6]]) as usize;
+    if length < 3 {
+        return Err(ParseError::InvalidLength);
+    }
+    let total = 7 + length;
+    if data.len() < total {
+        return Err(ParseError::TooShort);
+    }
+
+    let crc = u16::from_le_bytes([data[total - 2], data[total - 1]]);
+    if crc != crc16(&data[..total - 2]) {
+        return Err(ParseError::InvalidCrc);
+    }
+
+    Ok((
+        InstructionPacket {
+            id: data[4],
+            instruction: data[7],
+            params: data[8..total - 2].to_vec(),
+        },
+        total,
+    ))
+}
+
+/// Motor IDs expected to answer `request` with a status packet.
+pub fn expected_responders(request: &[u8]) -> Vec<u8> {
+    let packet = match parse_instruction_packet(request) {
+        Ok((packet, _)) => packet,
+        Err(_) => return Vec::new(),
+    };
+
+    match (packet.id, packet.instruction) {
+        // addr(2) + data_len(2) + ids
+        (BROADCAST_ID, instruction::SYNC_READ) => packet.params.get(4..).unwrap_or(&[]).to_vec(),
+        (BROADCAST_ID, _) | (_, instruction::SYNC_WRITE) => Vec::new(),
+        (id, _) => vec![id],
+    }
+}
+
 /// Parse status packet for position read (4 bytes).
 ///
 /// Returns `(motor_id, raw_position)`.
-pub fn parse_status_packet(data: &[u8], offset: usize) -> Result<(u8, i32), JsValue> {
+#[allow(dead_code)]
+pub fn parse_status_packet(data: &[u8], offset: usize) -> Result<(u8, i32), ParseError> {
     let slice = &data[offset..];
 
     // Position response: header(4) + id(1) + len(2) + instr(1) + err(1) + data(4) + crc(2) = 15
     let (id, length, _error, data_start) = validate_header(slice, 15)?;
 
     if length != 8 {
-        return Err(ParseError::InvalidLength.into());
+        return Err(ParseError::InvalidLength);
     }
 
     let pos = i32::from_le_bytes([
@@ -331,24 +495,24 @@ pub fn parse_status_packet(data: &[u8], offset: usize) -> Result<(u8, i32), JsVa
 }
 
 /// Parse status packet for 1-byte read (e.g., temperature).
-pub fn parse_status_packet_1byte(data: &[u8]) -> Result<u8, JsValue> {
+pub fn parse_status_packet_1byte(data: &[u8]) -> Result<u8, ParseError> {
     // 1-byte response: header(4) + id(1) + len(2) + instr(1) + err(1) + data(1) + crc(2) = 12
     let (_id, _length, error, data_start) = validate_header(data, 12)?;
 
     if error != 0 {
-        return Err(ParseError::MotorError(error).into());
+        return Err(ParseError::MotorError(error));
     }
 
     Ok(data[data_start])
 }
 
 /// Parse status packet for 2-byte signed read (e.g., load).
-pub fn parse_status_packet_2byte_signed(data: &[u8]) -> Result<i16, JsValue> {
+pub fn parse_status_packet_2byte_signed(data: &[u8]) -> Result<i16, ParseError> {
     // 2-byte response: header(4) + id(1) + len(2) + instr(1) + err(1) + data(2) + crc(2) = 13
     let (_id, _length, error, data_start) = validate_header(data, 13)?;
 
     if error != 0 {
-        return Err(ParseError::MotorError(error).into());
+        return Err(ParseError::MotorError(error));
     }
 
     Ok(i16::from_le_bytes([data[data_start], data[data_start + 1]]))
@@ -430,6 +594,29 @@ pub fn parse_position_packets(data: &[u8]) -> Vec<(u8, i32)> {
     results
 }
 
+/// Parse every well-formed status packet in a response buffer.
+///
+/// # Returns
+/// Vector of (motor_id, error, data) for each packet with a valid CRC.
+pub fn parse_status_packets(data: &[u8]) -> Vec<(u8, u8, Vec<u8>)> {
+    let mut results = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        match parse_instruction_packet(&data[offset..]) {
+            Ok((packet, used)) => {
+                if packet.instruction == instruction::STATUS && !packet.params.is_empty() {
+                    results.push((packet.id, packet.params[0], packet.params[1..].to_vec()));
+                }
+                offset += used;
+            }
+            Err(_) => offset += 1,
+        }
+    }
+
+    results
+}
+
 /// Parse all 1-byte status packets (e.g., temperature) from a response buffer.
 ///
 /// # Returns
@@ -455,8 +642,9 @@ pub fn parse_1byte_packets(data: &[u8]) -> Vec<(u8, u8)> {
             continue;
         }
 
+        // The alert bit flags a hardware error; the data is still valid
         let error = slice[8];
-        if error != 0 {
+        if error & !status_error::ALERT != 0 {
             continue;
         }
 
@@ -494,8 +682,9 @@ pub fn parse_2byte_signed_packets(data: &[u8]) -> Vec<(u8, i16)> {
             continue;
         }
 
+        // The alert bit flags a hardware error; the data is still valid
         let error = slice[8];
-        if error != 0 {
+        if error & !status_error::ALERT != 0 {
             continue;
         }
 
@@ -547,6 +736,54 @@ mod tests {
         assert_eq!(packet[9], 0); // Address high
     }
 
+    #[test]
+    fn test_status_packet_roundtrip() {
+        let packet = build_status_packet(11, 0, &[0x2A]);
+        assert_eq!(packet.len(), 12);
+        assert_eq!(parse_1byte_packets(&packet), vec![(11, 0x2A)]);
+        assert_eq!(parse_status_packet_1byte(&packet).unwrap(), 0x2A);
+    }
+
+    #[test]
+    fn test_parse_instruction_packet() {
+        let mut data = build_write_packet(12, address::GOAL_POSITION, &[0, 8, 0, 0]);
+        data.extend(build_ping_packet(BROADCAST_ID));
+
+        let (write, used) = parse_instruction_packet(&data).unwrap();
+        assert_eq!(write.id, 12);
+        assert_eq!(write.instruction, instruction::WRITE);
+        assert_eq!(write.params, vec![116, 0, 0, 8, 0, 0]);
+
+        let (ping, _) = parse_instruction_packet(&data[used..]).unwrap();
+        assert_eq!(ping.id, BROADCAST_ID);
+        assert_eq!(ping.instruction, instruction::PING);
+
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        assert!(matches!(
+            parse_instruction_packet(&data[used..]),
+            Err(ParseError::InvalidCrc)
+        ));
+    }
+
+    #[test]
+    fn test_alert_keeps_data() {
+        let mut data = build_status_packet(11, status_error::ALERT, &[70]);
+        data.extend(build_status_packet(12, status_error::ACCESS, &[40]));
+        assert_eq!(parse_1byte_packets(&data), vec![(11, 70)]);
+        assert_eq!(
+            parse_status_packets(&data),
+            vec![
+                (11, status_error::ALERT, vec![70]),
+                (12, status_error::ACCESS, vec![40])
+            ]
+        );
+        assert_eq!(
+            hardware_error::names(hardware_error::OVERHEATING | hardware_error::OVERLOAD),
+            vec!["overheating", "overload"]
+        );
+    }
+
     #[test]
     fn test_reboot_packet_structure() {
         let packet = build_reboot_packet(17);
@@ -0,0 +1,104 @@
+//! # Native Errors
+//!
+//! [`Error`] gathers every failure of the native [`Robot`](crate::client::Robot)
+//! API: transport I/O, motors that did not answer, malformed packets, joint
+//! addressing and configuration.
+
+use std::fmt;
+use std::io;
+
+use crate::capture::CaptureError;
+use crate::config::ConfigError;
+use crate::dynamixel::ParseError;
+use crate::joints::JointError;
+use crate::kinematics::KinematicsError;
+
+// ============================================================================
+// Error
+// ============================================================================
+
+/// Native API error
+#[derive(Debug)]
+pub enum Error {
+    /// The transport failed (device missing, permission denied, unplugged)
+    Io(io::Error),
+    /// These motors did not answer before the timeout
+    Timeout(Vec<u8>),
+    /// A status packet could not be decoded
+    Parse(ParseError),
+    Joint(JointError),
+    /// The head pose cannot be reached, or is refused near a singularity
+    Kinematics(KinematicsError),
+    Config(ConfigError),
+    Capture(CaptureError),
+    /// A call received values it cannot use (wrong count, bad format)
+    InvalidArgument(String),
+}
+
+/// Result of the native API.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            Error::Io(e) => format!("I/O error: {}", e),
+            Error::Timeout(ids) => format!("No response from motors {:?}", ids),
+            Error::Parse(e) => e.to_string(),
+            Error::Joint(e) => e.to_string(),
+            Error::Kinematics(e) => e.to_string(),
+            Error::Config(e) => e.to_string(),
+            Error::Capture(e) => e.to_string(),
+            Error::InvalidArgument(reason) => reason.clone(),
+        })
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Joint(e) => Some(e),
+            Error::Kinematics(e) => Some(e),
+            Error::Config(e) => Some(e),
+            Error::Capture(e) => Some(e),
+            Error::Timeout(_) | Error::InvalidArgument(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<JointError> for Error {
+    fn from(e: JointError) -> Self {
+        Error::Joint(e)
+    }
+}
+
+impl From<KinematicsError> for Error {
+    fn from(e: KinematicsError) -> Self {
+        Error::Kinematics(e)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
+
+impl From<CaptureError> for Error {
+    fn from(e: CaptureError) -> Self {
+        Error::Capture(e)
+    }
+}
@@ -0,0 +1,371 @@
+//! # Joint Table
+//!
+//! Single source of truth mapping joint names to Dynamixel IDs, rotation
+//! directions and angle limits. Everything that addresses a motor by name, by
+//! group (head, antennas) or by position in a joint vector goes through it.
+//!
+//! ## Reachy Mini Joints
+//!
+//! | Name        | Motor ID | Group         |
+//! |-------------|----------|---------------|
+//! | `stewart_1` | 11       | Head          |
+//! | `stewart_2` | 12       | Head          |
+//! | `stewart_3` | 13       | Head          |
+//! | `stewart_4` | 14       | Head          |
+//! | `stewart_5` | 15       | Head          |
+//! | `stewart_6` | 16       | Head          |
+//! | `l_antenna` | 17       | Left antenna  |
+//! | `r_antenna` | 18       | Right antenna |
+//! | `body_yaw`  | 10       | Body yaw      |
+//!
+//! `body_yaw` only exists on the wireless version.
+//!
+//! Earlier documentation called motors 11, 12 and 13 `neck_roll`,
+//! `neck_pitch` and `neck_yaw`. Name lookups still accept these as
+//! deprecated aliases of `stewart_1`, `stewart_2` and `stewart_3`; joints are
+//! always reported under their current names.
+//!
+//! The six head motors drive the Stewart platform together; a head joint angle
+//! is a motor angle, not a rotation of the head about one axis.
+//!
+//! ## Angles
+//!
+//! Joint angles are in degrees. `direction` and `offset_deg` map them to motor
+//! angles (`motor = direction × joint + offset`), so a motor mounted the other
+//! way round or off-center can still move "up" for positive angles.
+//!
+//! The table is part of the [`RobotConfig`](crate::config::RobotConfig) and is
+//! serialized with it:
+//!
+//! ```json
+//! { "name": "l_antenna", "id": 17, "kind": "left_antenna", "limits_deg": [-90, 90] }
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Deprecated joint names from earlier documentation, with the joint each
+/// one refers to.
+pub const JOINT_ALIASES: [(&str, &str); 3] = [
+    ("neck_roll", "stewart_1"),
+    ("neck_pitch", "stewart_2"),
+    ("neck_yaw", "stewart_3"),
+];
+
+// ============================================================================
+// Joint Table
+// ============================================================================
+
+/// Role of a joint on the robot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JointKind {
+    /// One of the six Stewart platform motors
+    Head,
+    LeftAntenna,
+    RightAntenna,
+    /// Rotation of the whole body about the vertical axis (wireless version)
+    BodyYaw,
+}
+
+/// One motor of the robot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Joint {
+    pub name: String,
+    pub id: u8,
+    pub kind: JointKind,
+    /// +1 or -1, see the module docs
+    #[serde(default = "default_direction")]
+    pub direction: f32,
+    /// Motor angle (degrees) at joint angle zero
+    #[serde(default)]
+    pub offset_deg: f32,
+    /// Allowed commanded range `[min, max]` in degrees
+    #[serde(default = "default_limits")]
+    pub limits_deg: [f32; 2],
+}
+
+fn default_direction() -> f32 {
+    1.0
+}
+
+fn default_limits() -> [f32; 2] {
+    [-180.0, 180.0]
+}
+
+impl Joint {
+    /// Create a joint with direction 1, no offset and ±180° limits.
+    pub fn new(name: &str, id: u8, kind: JointKind) -> Self {
+        Self {
+            name: name.to_string(),
+            id,
+            kind,
+            direction: default_direction(),
+            offset_deg: 0.0,
+            limits_deg: default_limits(),
+        }
+    }
+
+    /// Motor angle in radians for a joint angle in degrees.
+    pub fn motor_radians(&self, angle_deg: f32) -> f32 {
+        (self.direction * angle_deg + self.offset_deg).to_radians()
+    }
+
+    /// Joint angle in degrees for a motor angle in radians.
+    pub fn joint_degrees(&self, motor_rad: f32) -> f32 {
+        self.direction * (motor_rad.to_degrees() - self.offset_deg)
+    }
+
+    /// Check `angle_deg` lies within the joint limits.
+    pub fn check_limits(&self, angle_deg: f32) -> Result<(), JointError> {
+        let [min_deg, max_deg] = self.limits_deg;
+        if (min_deg..=max_deg).contains(&angle_deg) {
+            Ok(())
+        } else {
+            Err(JointError::OutOfLimits {
+                joint: self.name.clone(),
+                value_deg: angle_deg,
+                min_deg,
+                max_deg,
+            })
+        }
+    }
+}
+
+/// All joints of a robot, in bus order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointTable {
+    joints: Vec<Joint>,
+}
+
+impl JointTable {
+    /// Build a table from joints in bus order.
+    pub fn new(joints: Vec<Joint>) -> Self {
+        Self { joints }
+    }
+
+    /// Joint table of the Reachy Mini Lite.
+    pub fn reachy_mini() -> Self {
+        let mut joints: Vec<Joint> = (1..=6)
+            .map(|i| Joint::new(&format!("stewart_{}", i), 10 + i, JointKind::Head))
+            .collect();
+        joints.push(Joint::new("l_antenna", 17, JointKind::LeftAntenna));
+        joints.push(Joint::new("r_antenna", 18, JointKind::RightAntenna));
+        Self { joints }
+    }
+
+    /// Every joint, in bus order.
+    pub fn all(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Every joint, for modification.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Joint> {
+        self.joints.iter_mut()
+    }
+
+    /// Look up a joint by name, or by one of the [`JOINT_ALIASES`].
+    pub fn get(&self, name: &str) -> Result<&Joint, JointError> {
+        let target = canonical_name(name);
+        self.joints
+            .iter()
+            .find(|j| j.name == target)
+            .ok_or_else(|| JointError::UnknownJoint(name.to_string()))
+    }
+
+    /// Look up a joint by name (or alias) for modification.
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Joint, JointError> {
+        let target = canonical_name(name);
+        self.joints
+            .iter_mut()
+            .find(|j| j.name == target)
+            .ok_or_else(|| JointError::UnknownJoint(name.to_string()))
+    }
+
+    /// Position of motor `id` in [`JointTable::all`].
+    pub fn index_of(&self, id: u8) -> Option<usize> {
+        self.joints.iter().position(|j| j.id == id)
+    }
+
+    /// Joints of the given kind, in bus order.
+    pub fn of_kind(&self, kind: JointKind) -> Vec<Joint> {
+        self.joints
+            .iter()
+            .filter(|j| j.kind == kind)
+            .cloned()
+            .collect()
+    }
+
+    /// The six head joints.
+    pub fn head(&self) -> Vec<Joint> {
+        self.of_kind(JointKind::Head)
+    }
+
+    /// The body yaw joint, on robots that have one.
+    pub fn body_yaw(&self) -> Option<Joint> {
+        self.of_kind(JointKind::BodyYaw).into_iter().next()
+    }
+
+    /// The antenna joints as `[left, right]`.
+    pub fn antennas(&self) -> Vec<Joint> {
+        let mut antennas = self.of_kind(JointKind::LeftAntenna);
+        antennas.extend(self.of_kind(JointKind::RightAntenna));
+        antennas
+    }
+
+    /// The head and antenna joints, in bus order (the `get_all_joints()` layout).
+    pub fn head_and_antennas(&self) -> Vec<Joint> {
+        let mut joints = self.head();
+        joints.extend(self.antennas());
+        joints
+    }
+
+    /// Resolve `(name, angle)` targets to their joints, checking limits.
+    pub fn resolve(&self, targets: &[(String, f32)]) -> Result<Vec<(Joint, f32)>, JointError> {
+        targets
+            .iter()
+            .map(|(name, angle_deg)| {
+                let joint = self.get(name)?;
+                joint.check_limits(*angle_deg)?;
+                Ok((joint.clone(), *angle_deg))
+            })
+            .collect()
+    }
+}
+
+/// Motor IDs of `joints`.
+pub fn ids(joints: &[Joint]) -> Vec<u8> {
+    joints.iter().map(|j| j.id).collect()
+}
+
+/// The current name for `name`, which may be a deprecated alias.
+fn canonical_name(name: &str) -> &str {
+    JOINT_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, joint)| joint)
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Joint addressing error
+#[derive(Debug, Clone, PartialEq)]
+pub enum JointError {
+    /// No joint has this name
+    UnknownJoint(String),
+    /// A command exceeded the joint limits
+    OutOfLimits {
+        joint: String,
+        value_deg: f32,
+        min_deg: f32,
+        max_deg: f32,
+    },
+}
+
+impl fmt::Display for JointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            JointError::UnknownJoint(name) => format!("Unknown joint '{}'", name),
+            JointError::OutOfLimits {
+                joint,
+                value_deg,
+                min_deg,
+                max_deg,
+            } => format!(
+                "Joint {} angle {:.1}° outside limits [{:.1}°, {:.1}°]",
+                joint, value_deg, min_deg, max_deg
+            ),
+        })
+    }
+}
+
+impl std::error::Error for JointError {}
+
+#[cfg(feature = "wasm")]
+impl From<JointError> for JsValue {
+    fn from(e: JointError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reachy_mini_table() {
+        let table = JointTable::reachy_mini();
+        assert_eq!(ids(&table.head()), vec![11, 12, 13, 14, 15, 16]);
+        assert_eq!(ids(&table.antennas()), vec![17, 18]);
+        assert_eq!(table.get("r_antenna").unwrap().id, 18);
+        assert_eq!(table.index_of(17), Some(6));
+        assert_eq!(
+            table.get("neck").unwrap_err(),
+            JointError::UnknownJoint("neck".to_string())
+        );
+    }
+
+    #[test]
+    fn test_legacy_aliases() {
+        let mut table = JointTable::reachy_mini();
+        assert_eq!(table.get("neck_roll").unwrap().name, "stewart_1");
+        assert_eq!(table.get("neck_pitch").unwrap().id, 12);
+        assert_eq!(table.get("neck_yaw").unwrap().id, 13);
+
+        table.get_mut("neck_yaw").unwrap().limits_deg = [-10.0, 10.0];
+        assert_eq!(table.get("stewart_3").unwrap().limits_deg, [-10.0, 10.0]);
+        let resolved = table.resolve(&[("neck_pitch".to_string(), 5.0)]).unwrap();
+        assert_eq!(resolved[0].0.name, "stewart_2");
+    }
+
+    #[test]
+    fn test_resolve_checks_limits() {
+        let mut table = JointTable::reachy_mini();
+        table.get_mut("l_antenna").unwrap().limits_deg = [-30.0, 30.0];
+
+        let resolved = table
+            .resolve(&[
+                ("stewart_2".to_string(), 90.0),
+                ("l_antenna".to_string(), 20.0),
+            ])
+            .unwrap();
+        assert_eq!(resolved[0].0.id, 12);
+        assert_eq!(resolved[1].1, 20.0);
+
+        assert!(matches!(
+            table.resolve(&[("l_antenna".to_string(), 45.0)]),
+            Err(JointError::OutOfLimits { value_deg, .. }) if value_deg == 45.0
+        ));
+        assert!(table
+            .get("stewart_1")
+            .unwrap()
+            .check_limits(f32::NAN)
+            .is_err());
+    }
+
+    #[test]
+    fn test_direction() {
+        let mut joint = Joint::new("r_antenna", 18, JointKind::RightAntenna);
+        joint.direction = -1.0;
+        let motor = joint.motor_radians(90.0);
+        assert!((motor + std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!((joint.joint_degrees(motor) - 90.0).abs() < 1e-4);
+
+        joint.offset_deg = 10.0;
+        assert!((joint.motor_radians(90.0).to_degrees() + 80.0).abs() < 1e-4);
+        assert!((joint.joint_degrees(joint.motor_radians(-25.0)) + 25.0).abs() < 1e-4);
+    }
+}
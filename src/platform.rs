@@ -0,0 +1,149 @@
+//! # Platform
+//!
+//! Global scope detection and timers, so the control stack runs the same on
+//! the page and inside a Web Worker (where there is no `window` or DOM).
+//!
+//! ```javascript
+//! // worker.js
+//! import init, { connect_simulated, start_fk_stream } from "reachy-mini";
+//! await init();
+//! await connect_simulated();
+//! start_fk_stream();  // poses are posted to the page as { type: "pose", pose }
+//! ```
+
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{DedicatedWorkerGlobalScope, Window, WorkerGlobalScope};
+
+// ============================================================================
+// Global Scope
+// ============================================================================
+
+/// The JavaScript global object the module is running in.
+pub enum GlobalScope {
+    /// Browser page
+    Window(Window),
+    /// Dedicated, shared or service worker
+    Worker(WorkerGlobalScope),
+    /// Anything else providing `setTimeout` (e.g. Node.js)
+    Other(Object),
+}
+
+impl GlobalScope {
+    /// Detect the current global scope.
+    pub fn current() -> Self {
+        let global = js_sys::global();
+        if let Some(window) = global.dyn_ref::<Window>() {
+            GlobalScope::Window(window.clone())
+        } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+            GlobalScope::Worker(worker.clone())
+        } else {
+            GlobalScope::Other(global)
+        }
+    }
+
+    /// Whether the module runs inside a worker.
+    pub fn is_worker(&self) -> bool {
+        matches!(self, GlobalScope::Worker(_))
+    }
+
+    /// Call `callback` after `ms` milliseconds.
+    pub fn set_timeout(&self, callback: &Function, ms: i32) -> Result<(), JsValue> {
+        match self {
+            GlobalScope::Window(window) => {
+                window.set_timeout_with_callback_and_timeout_and_arguments_0(callback, ms)?;
+            }
+            GlobalScope::Worker(worker) => {
+                worker.set_timeout_with_callback_and_timeout_and_arguments_0(callback, ms)?;
+            }
+            GlobalScope::Other(global) => {
+                let set_timeout: Function = Reflect::get(global, &"setTimeout".into())?
+                    .dyn_into()
+                    .map_err(|_| JsValue::from_str("setTimeout is not available"))?;
+                set_timeout.call2(global, callback, &ms.into())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send `message` to the page that created this worker.
+    ///
+    /// Does nothing outside a dedicated worker.
+    pub fn post_to_page(&self, message: &JsValue) -> Result<(), JsValue> {
+        if let GlobalScope::Worker(worker) = self {
+            if let Some(dedicated) = worker.dyn_ref::<DedicatedWorkerGlobalScope>() {
+                dedicated.post_message(message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Look up a function installed on the global object (e.g. a JS helper).
+///
+/// Works for helpers set on `window`, `self` or `globalThis` alike.
+pub fn global_function(name: &str) -> Option<Function> {
+    Reflect::get(&js_sys::global(), &name.into())
+        .ok()
+        .and_then(|value| value.dyn_into().ok())
+}
+
+// ============================================================================
+// Loop Pacing
+// ============================================================================
+
+/// Schedules the iterations of a periodic loop on fixed deadlines.
+///
+/// Sleeping a fixed delay after each iteration lets the period grow with the
+/// time spent in the iteration. Pacing against deadlines keeps the rate steady,
+/// and an iteration that overruns by more than a period restarts the schedule
+/// instead of bursting to catch up.
+pub struct LoopPacer {
+    period_ms: f64,
+    next_ms: Option<f64>,
+}
+
+impl LoopPacer {
+    /// Pace a loop at one iteration every `period_ms`.
+    pub fn new(period_ms: f64) -> Self {
+        Self {
+            period_ms,
+            next_ms: None,
+        }
+    }
+
+    /// Milliseconds to wait at time `now_ms` before starting the next iteration.
+    pub fn delay_ms(&mut self, now_ms: f64) -> u32 {
+        let next = self.next_ms.unwrap_or(now_ms) + self.period_ms;
+        let next = if next < now_ms { now_ms } else { next };
+        self.next_ms = Some(next);
+        (next - now_ms).round() as u32
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer_absorbs_iteration_time() {
+        let mut pacer = LoopPacer::new(20.0);
+        assert_eq!(pacer.delay_ms(0.0), 20);
+        // Iterations taking 12 ms only wait for the rest of the period
+        assert_eq!(pacer.delay_ms(32.0), 8);
+        assert_eq!(pacer.delay_ms(55.0), 5);
+    }
+
+    #[test]
+    fn test_pacer_restarts_after_overrun() {
+        let mut pacer = LoopPacer::new(20.0);
+        pacer.delay_ms(0.0);
+        // A 100 ms stall does not cause a burst of catch-up iterations
+        assert_eq!(pacer.delay_ms(120.0), 0);
+        assert_eq!(pacer.delay_ms(125.0), 15);
+    }
+}
@@ -0,0 +1,340 @@
+//! # Native Ports
+//!
+//! Blocking byte transports used by the native [`Robot`](crate::client::Robot).
+//! A [`Port`] only needs to write bytes and read them back with a timeout;
+//! [`Port::transact`] builds request/response exchanges on top, returning as
+//! soon as every motor addressed by the request has answered.
+//!
+//! [`SimulatedPort`] answers from an in-process [`SimulatedBus`], the native
+//! counterpart of `connect_simulated()`.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::dynamixel::{expected_responders, instruction, parse_instruction_packet, BROADCAST_ID};
+use crate::error::Result;
+use crate::simulator::SimulatedBus;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default time to wait for status packets (ms)
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Size of the chunks read from the transport
+const READ_CHUNK: usize = 256;
+
+/// Bus speed of the Reachy Mini motors (bits per second)
+pub const BUS_BAUD_RATE: u32 = 1_000_000;
+
+/// Bits on the wire per byte (8N1: start, 8 data, stop)
+const BITS_PER_BYTE: u32 = 10;
+
+// ============================================================================
+// Port
+// ============================================================================
+
+/// A blocking, byte-oriented connection to a Dynamixel bus.
+pub trait Port {
+    /// Send `data` to the bus.
+    fn write(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Read available bytes into `buf`, waiting at most `timeout` for the
+    /// first one.
+    ///
+    /// # Returns
+    /// The number of bytes read, 0 if nothing arrived in time.
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize>;
+
+    /// Discard received bytes nobody read, e.g. late answers to a previous
+    /// request.
+    fn clear(&mut self) -> Result<()> {
+        let mut buf = [0u8; READ_CHUNK];
+        while self.read(&mut buf, Duration::ZERO)? > 0 {}
+        Ok(())
+    }
+
+    /// Send `request` and collect the status packets it triggers.
+    ///
+    /// Returns as soon as every motor addressed by `request` has answered, or
+    /// after `timeout` with whatever arrived. Requests without answers (sync
+    /// writes, broadcasts) return an empty response right away, except
+    /// broadcast pings, which collect answers for the whole `timeout`.
+    fn transact(&mut self, request: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        self.clear()?;
+        self.write(request)?;
+
+        let expected = expected_packets(request);
+        let mut response = Vec::new();
+        if expected == Some(0) {
+            return Ok(response);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut buf = [0u8; READ_CHUNK];
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            let n = self.read(&mut buf, deadline - now)?;
+            response.extend_from_slice(&buf[..n]);
+            if let Some(expected) = expected {
+                if complete_packets(&response).0 >= expected {
+                    break;
+                }
+            }
+        }
+        Ok(response)
+    }
+}
+
+impl<P: Port + ?Sized> Port for Box<P> {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        (**self).write(data)
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        (**self).read(buf, timeout)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        (**self).clear()
+    }
+}
+
+// ============================================================================
+// Simulated Port
+// ============================================================================
+
+/// A port answered by a [`SimulatedBus`], with motors moving in real time.
+///
+/// Answers are available immediately unless [`with_timing`] is used.
+///
+/// [`with_timing`]: SimulatedPort::with_timing
+pub struct SimulatedPort {
+    bus: SimulatedBus,
+    start: Instant,
+    /// Time to transmit one byte, `None` for instant answers
+    byte_time: Option<Duration>,
+    /// Status bytes not read yet, with the time they reach the host
+    pending: VecDeque<(Instant, u8)>,
+}
+
+impl SimulatedPort {
+    /// Connect to `bus`.
+    pub fn new(bus: SimulatedBus) -> Self {
+        Self {
+            bus,
+            start: Instant::now(),
+            byte_time: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Deliver status packets when a real bus at `baud_rate` would: once the
+    /// request is transmitted, each motor waits its Return Delay Time, then
+    /// sends its packet byte by byte.
+    pub fn with_timing(mut self, baud_rate: u32) -> Self {
+        self.byte_time = Some(Duration::from_secs_f64(
+            BITS_PER_BYTE as f64 / baud_rate as f64,
+        ));
+        self
+    }
+
+    /// The simulated motors, e.g. to inject faults or inspect goals.
+    pub fn bus(&self) -> &SimulatedBus {
+        &self.bus
+    }
+
+    /// The simulated motors, for modification.
+    pub fn bus_mut(&mut self) -> &mut SimulatedBus {
+        &mut self.bus
+    }
+
+    fn now_ms(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+impl Port for SimulatedPort {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let now = Instant::now();
+        let now_ms = self.now_ms();
+        let response = self.bus.process(data, now_ms);
+
+        let byte_time = match self.byte_time {
+            Some(byte_time) => byte_time,
+            None => {
+                self.pending
+                    .extend(response.into_iter().map(|byte| (now, byte)));
+                return Ok(());
+            }
+        };
+        // Motors answer one after the other once the request is on the wire
+        let mut at = now + byte_time * data.len() as u32;
+        let mut offset = 0;
+        while offset + 7 <= response.len() {
+            let length = u16::from_le_bytes([response[offset + 5], response[offset + 6]]) as usize;
+            let delay_us = self
+                .bus
+                .motor(response[offset + 4])
+                .map_or(0, |motor| motor.return_delay_us());
+            at += Duration::from_micros(delay_us as u64);
+            for &byte in &response[offset..offset + 7 + length] {
+                at += byte_time;
+                self.pending.push_back((at, byte));
+            }
+            offset += 7 + length;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let now = Instant::now();
+        match self.pending.front() {
+            // Nothing will arrive before the next write
+            None => {
+                std::thread::sleep(timeout);
+                return Ok(0);
+            }
+            Some(&(at, _)) if at > now => std::thread::sleep((at - now).min(timeout)),
+            Some(_) => {}
+        }
+
+        let now = Instant::now();
+        let mut n = 0;
+        while n < buf.len() {
+            match self.pending.front() {
+                Some(&(at, byte)) if at <= now => {
+                    buf[n] = byte;
+                    self.pending.pop_front();
+                    n += 1;
+                }
+                _ => break,
+            }
+        }
+        Ok(n)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Number of status packets `request` triggers, `None` if unknown in advance
+/// (broadcast ping).
+pub(crate) fn expected_packets(request: &[u8]) -> Option<usize> {
+    match parse_instruction_packet(request) {
+        Ok((packet, _)) if packet.id == BROADCAST_ID && packet.instruction == instruction::PING => {
+            None
+        }
+        _ => Some(expected_responders(request).len()),
+    }
+}
+
+/// Number of complete packets at the start of `data`, skipping noise between
+/// them, and the number of bytes they span. CRCs are not checked; parsing
+/// does that later.
+pub(crate) fn complete_packets(data: &[u8]) -> (usize, usize) {
+    let mut count = 0;
+    let mut offset = 0;
+    let mut used = 0;
+    while offset + 7 <= data.len() {
+        if data[offset..offset + 4] != [0xFF, 0xFF, 0xFD, 0x00] {
+            offset += 1;
+            continue;
+        }
+        let length = u16::from_le_bytes([data[offset + 5], data[offset + 6]]) as usize;
+        if offset + 7 + length > data.len() {
+            break;
+        }
+        count += 1;
+        offset += 7 + length;
+        used = offset;
+    }
+    (count, used)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{
+        build_ping_packet, build_sync_current_position, build_sync_write_torque,
+        parse_position_packets,
+    };
+
+    #[test]
+    fn test_complete_packets() {
+        let mut bus = SimulatedBus::new(&[1, 2]);
+        let response = bus.process(&build_sync_current_position(&[1, 2]), 0.0);
+        let first = response.len() / 2;
+        assert_eq!(complete_packets(&response), (2, response.len()));
+        assert_eq!(
+            complete_packets(&response[..response.len() - 1]),
+            (1, first)
+        );
+
+        let mut noisy = vec![0x00, 0xFF];
+        noisy.extend(&response);
+        assert_eq!(complete_packets(&noisy), (2, noisy.len()));
+    }
+
+    #[test]
+    fn test_expected_packets() {
+        assert_eq!(
+            expected_packets(&build_sync_current_position(&[11, 12, 13])),
+            Some(3)
+        );
+        assert_eq!(
+            expected_packets(&build_sync_write_torque(&[11], true)),
+            Some(0)
+        );
+        assert_eq!(expected_packets(&build_ping_packet(11)), Some(1));
+        assert_eq!(expected_packets(&build_ping_packet(BROADCAST_ID)), None);
+    }
+
+    #[test]
+    fn test_simulated_transact() {
+        let mut port = SimulatedPort::new(SimulatedBus::new(&[11, 12]));
+        let response = port
+            .transact(&build_sync_current_position(&[11, 12]), DEFAULT_TIMEOUT)
+            .unwrap();
+        assert_eq!(
+            parse_position_packets(&response),
+            vec![(11, 2048), (12, 2048)]
+        );
+
+        let response = port
+            .transact(&build_sync_write_torque(&[11, 12], true), DEFAULT_TIMEOUT)
+            .unwrap();
+        assert!(response.is_empty());
+        assert!(port.bus().motor(11).unwrap().torque_enabled());
+    }
+
+    #[test]
+    fn test_simulated_timing() {
+        let ids = [11, 12, 13, 14, 15, 16, 17, 18];
+        let mut port = SimulatedPort::new(SimulatedBus::new(&ids)).with_timing(BUS_BAUD_RATE);
+        let start = Instant::now();
+        let response = port
+            .transact(&build_sync_current_position(&ids), DEFAULT_TIMEOUT)
+            .unwrap();
+        let elapsed = start.elapsed();
+
+        // 8 × (500 µs return delay + 15 bytes × 10 µs) after the request
+        assert_eq!(parse_position_packets(&response).len(), 8);
+        assert!(elapsed >= Duration::from_micros(5400), "{:?}", elapsed);
+        assert!(elapsed < DEFAULT_TIMEOUT, "{:?}", elapsed);
+    }
+}
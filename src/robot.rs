@@ -0,0 +1,1475 @@
+//! # Robot Instance
+//!
+//! [`ReachyMini`] owns everything needed to drive one robot: its connection,
+//! robot configuration, kinematics solver, recorded frames, joint table and
+//! cancellation flag.
+//! Several instances can live on the same page, each with its own connection,
+//! and `stop()` on one leaves the others running.
+//!
+//! ```javascript
+//! const left = new ReachyMini();
+//! const right = new ReachyMini();
+//! await left.connect();
+//! await right.connect_simulated();
+//! await left.set_head_pose(0, 0, 10, 0, 0, 0);
+//! ```
+//!
+//! The free functions of this crate forward to a default instance.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use nalgebra::Matrix4;
+use wasm_bindgen::prelude::*;
+use web_sys::console;
+
+use crate::bindings::{
+    close_serial_port, read_motor_positions, sleep, update_pose, GenericPort, DEFAULT_WAIT_MS,
+    FK_STREAM_PERIOD_MS, REPLAY_PERIOD_MS,
+};
+use crate::capture::Capture;
+use crate::config::{BodyYawLimits, ConfigError, Geometry, RobotConfig};
+use crate::daemon::{DaemonClient, DEFAULT_DAEMON_URL};
+use crate::dynamixel::{
+    address, build_read_packet, build_reboot_packet, build_sync_current_position,
+    build_sync_read_load, build_sync_read_temperature, build_sync_write_position_radians,
+    build_sync_write_torque, parse_1byte_packets, parse_2byte_signed_packets,
+    parse_position_packets, parse_status_packet_1byte, parse_status_packet_2byte_signed,
+    raw_to_radians,
+};
+use crate::joints::{ids, Joint, JointKind, JointTable};
+use crate::kinematics::{
+    in_body_frame, pose_to_transform, transform_to_pose, transform_to_xyzrpy, xyzrpy_to_transform,
+    ForwardSolution, Kinematics, KinematicsError, PoseFormat, SingularityPolicy,
+    MAX_CONDITION_NUMBER,
+};
+use crate::platform::LoopPacer;
+use crate::scheduler::Priority;
+use crate::types::{
+    AntennaState, HeadJacobian, HeadPose, HeadPoseProjection, HeadPoseSolution, JointState,
+    MotorDiagnostics,
+};
+use crate::workspace::WorkspaceGrid;
+
+// ============================================================================
+// Robot
+// ============================================================================
+
+/// A Reachy Mini robot and its connection.
+#[wasm_bindgen]
+pub struct ReachyMini {
+    /// Raw bus connection
+    port: RefCell<Option<Rc<GenericPort>>>,
+    /// High-level daemon connection (exclusive with `port`)
+    daemon: RefCell<Option<Rc<DaemonClient>>>,
+    /// Robot profile: joints, geometry and default transport
+    config: RefCell<RobotConfig>,
+    /// Kinematics solver, kept between calls so FK is warm-started
+    kinematics: RefCell<Kinematics>,
+    /// Frames recorded by `start_fk_stream(duration)`, in radians
+    recording: RefCell<Vec<Vec<f32>>>,
+    /// Joint names, motor IDs, directions and limits
+    joints: RefCell<JointTable>,
+    /// Set by `stop()` to end continuous operations
+    stop_requested: Cell<bool>,
+}
+
+impl Default for ReachyMini {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl ReachyMini {
+    /// Create a disconnected robot instance with the Lite profile.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ReachyMini {
+        let config = RobotConfig::default();
+        Self {
+            port: RefCell::new(None),
+            daemon: RefCell::new(None),
+            kinematics: RefCell::new(config.kinematics()),
+            recording: RefCell::new(Vec::new()),
+            joints: RefCell::new(config.joint_table()),
+            config: RefCell::new(config),
+            stop_requested: Cell::new(false),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Configuration
+    // ------------------------------------------------------------------------
+
+    /// Switch to a robot profile: `"lite"`, `"wireless"` or a JSON `RobotConfig`.
+    ///
+    /// Resets joint limits to the ones of the profile.
+    pub fn load_config(&self, config: String) -> Result<(), JsValue> {
+        self.apply_config(RobotConfig::resolve(&config)?);
+        Ok(())
+    }
+
+    /// The active robot configuration as JSON.
+    pub fn get_config(&self) -> String {
+        self.config.borrow().to_json()
+    }
+
+    /// Replace the kinematic geometry with a JSON `Geometry` (arm and rod
+    /// lengths, head offset, branch anchors), keeping joints and limits.
+    ///
+    /// The geometry is validated and the solver rebuilt once; on error the
+    /// current model stays active.
+    pub fn set_kinematic_model(&self, json: String) -> Result<(), JsValue> {
+        let geometry = Geometry::from_json(&json)?;
+        let mut config = self.config.borrow_mut();
+        config.set_geometry(geometry)?;
+        self.rebuild_kinematics(&config);
+        Ok(())
+    }
+
+    /// The active kinematic geometry as JSON.
+    pub fn get_kinematic_model(&self) -> String {
+        self.config.borrow().geometry.to_json()
+    }
+
+    // ------------------------------------------------------------------------
+    // Connection Management
+    // ------------------------------------------------------------------------
+
+    /// Connect with the transport of `config` (default profile: Lite, over
+    /// WebSocket with a WebSerial fallback).
+    ///
+    /// # Arguments
+    /// * `config` - `"lite"`, `"wireless"` or a JSON `RobotConfig`; keeps the
+    ///   current profile if omitted
+    pub async fn connect(&self, config: Option<String>) -> Result<bool, JsValue> {
+        if let Some(config) = config {
+            self.load_config(config)?;
+        }
+        let (transport, motor_ids) = {
+            let config = self.config.borrow();
+            (config.transport.clone(), config.motor_ids())
+        };
+        let port = GenericPort::open(&transport, &motor_ids).await?;
+        self.set_port(port);
+        console::log_1(&JsValue::from_str("Connected to Reachy Mini"));
+        Ok(true)
+    }
+
+    /// Connect to an in-process simulated robot with the motors of `config`.
+    pub async fn connect_simulated(&self, config: Option<String>) -> Result<bool, JsValue> {
+        if let Some(config) = config {
+            self.load_config(config)?;
+        }
+        let motor_ids = self.config.borrow().motor_ids();
+        self.set_port(GenericPort::simulated(&motor_ids));
+        console::log_1(&JsValue::from_str("Connected to simulated Reachy Mini"));
+        Ok(true)
+    }
+
+    /// Connect through the daemon's high-level JSON API.
+    ///
+    /// # Arguments
+    /// * `url` - Daemon base URL (default `http://localhost:8000`)
+    pub async fn connect_daemon(&self, url: Option<String>) -> Result<bool, JsValue> {
+        let client = DaemonClient::new(url.as_deref().unwrap_or(DEFAULT_DAEMON_URL));
+        // Fail early if the daemon is unreachable
+        client.get_head_pose().await?;
+
+        *self.port.borrow_mut() = None;
+        *self.daemon.borrow_mut() = Some(Rc::new(client));
+        console::log_1(&JsValue::from_str("Connected to Reachy Mini daemon"));
+        Ok(true)
+    }
+
+    /// Connect to a replay of a previously captured session.
+    ///
+    /// # Arguments
+    /// * `capture_json` - Capture returned by `stop_capture()`
+    pub async fn connect_replay(&self, capture_json: String) -> Result<bool, JsValue> {
+        let capture = Capture::from_json(&capture_json)?;
+        self.set_port(GenericPort::replay(capture));
+        console::log_1(&JsValue::from_str("Connected to capture replay"));
+        Ok(true)
+    }
+
+    /// Release the connection.
+    pub async fn disconnect(&self) -> Result<(), JsValue> {
+        if let Some(port) = self.port.take() {
+            let _ = port.release_lock();
+        }
+        self.daemon.take();
+        close_serial_port().await;
+        console::log_1(&JsValue::from_str("Disconnected from Reachy Mini"));
+        Ok(())
+    }
+
+    /// Check if this instance is connected.
+    pub fn is_connected(&self) -> bool {
+        self.port.borrow().is_some() || self.daemon.borrow().is_some()
+    }
+
+    // ------------------------------------------------------------------------
+    // Head Pose
+    // ------------------------------------------------------------------------
+
+    /// Current head pose as `[x, y, z, roll, pitch, yaw, body_yaw]` (mm,
+    /// degrees), in the world frame; `body_yaw` is 0 without a body yaw
+    /// motor.
+    pub async fn get_head_pose(&self) -> Result<Vec<f32>, JsValue> {
+        if let Some(daemon) = self.daemon() {
+            let (pose, body_yaw) = daemon.get_head_pose().await?;
+            return Ok(HeadPose::from_xyzrpy(pose, body_yaw).to_array());
+        }
+        Ok(self.get_head_pose_solution().await?.pose.to_array())
+    }
+
+    /// Current head pose with the residual and iteration count of the
+    /// forward kinematics solve, warm-started from the previous pose.
+    pub async fn get_head_pose_solution(&self) -> Result<HeadPoseSolution, JsValue> {
+        if self.daemon().is_some() {
+            return Err(JsValue::from_str(
+                "Forward kinematics runs on the daemon for this connection",
+            ));
+        }
+
+        let (solution, body_yaw_deg) = self.solve_head_pose().await?;
+        let pose = transform_to_xyzrpy(&solution.t_world_platform, self.head_z_offset_mm());
+        Ok(HeadPoseSolution::new(pose, body_yaw_deg, &solution))
+    }
+
+    /// Current head pose in the world frame, written in `format`:
+    /// `"xyzrpy"`, `"quaternion"` (`[x, y, z, qx, qy, qz, qw]`), `"matrix"`
+    /// (4x4 homogeneous transform, row by row) or `"axis_angle"`
+    /// (`[x, y, z, ax, ay, az, angle]`), in mm and degrees.
+    ///
+    /// The values are canonical, so passing them to `set_head_pose_as`
+    /// gives the same pose. The body yaw is not included, see
+    /// `get_head_pose`.
+    pub async fn get_head_pose_as(&self, format: String) -> Result<Vec<f32>, JsValue> {
+        let format: PoseFormat = format.parse()?;
+        let offset = self.head_z_offset_mm();
+        let t = match self.daemon() {
+            Some(daemon) => xyzrpy_to_transform(daemon.get_head_pose().await?.0, offset),
+            None => self.solve_head_pose().await?.0.t_world_platform,
+        };
+        Ok(transform_to_pose(format, &t, offset))
+    }
+
+    /// Move the head to a pose written in `format` (see `get_head_pose_as`)
+    /// with the body turned to `body_yaw` (degrees, default 0).
+    pub async fn set_head_pose_as(
+        &self,
+        format: String,
+        values: Vec<f32>,
+        body_yaw: Option<f32>,
+    ) -> Result<(), JsValue> {
+        let format: PoseFormat = format.parse()?;
+        let t = pose_to_transform(format, &values, self.head_z_offset_mm())?;
+        self.set_head_transform(t, body_yaw).await
+    }
+
+    /// Rewrite a head pose from one format to another without moving the
+    /// robot, e.g. `convert_head_pose(q, "quaternion", "xyzrpy")`.
+    pub fn convert_head_pose(
+        &self,
+        values: Vec<f32>,
+        from: String,
+        to: String,
+    ) -> Result<Vec<f32>, JsValue> {
+        let (from, to): (PoseFormat, PoseFormat) = (from.parse()?, to.parse()?);
+        let offset = self.head_z_offset_mm();
+        Ok(transform_to_pose(
+            to,
+            &pose_to_transform(from, &values, offset)?,
+            offset,
+        ))
+    }
+
+    /// Read the head (and body yaw) joints and solve forward kinematics:
+    /// the solve and the body yaw (degrees, 0 without a body yaw motor).
+    async fn solve_head_pose(&self) -> Result<(ForwardSolution, f32), JsValue> {
+        let body = self.joints.borrow().body_yaw();
+        let joints: Vec<Joint> = self.head_joints().into_iter().chain(body.clone()).collect();
+        let mut joint_deg = self.read_joints(&joints).await?;
+        let body_yaw_deg = match body {
+            Some(_) => joint_deg.pop(),
+            None => None,
+        };
+
+        let joint_rad: Vec<f32> = joint_deg.iter().map(|d| d.to_radians()).collect();
+        let solution = self
+            .kinematics
+            .borrow_mut()
+            .solve_forward_kinematics(&joint_rad, body_yaw_deg.map(f32::to_radians));
+        Ok((solution, body_yaw_deg.unwrap_or(0.0)))
+    }
+
+    /// Move the head to a pose (mm, degrees, world frame) through inverse
+    /// kinematics, with the body turned to `body_yaw` (degrees, default 0).
+    ///
+    /// The body follows a head yawed further from it than the body yaw
+    /// limits allow, see `set_body_yaw_limits`. Robots without a body yaw
+    /// motor only accept a body yaw of 0.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn set_head_pose(
+        &self,
+        x: f32,
+        y: f32,
+        z: f32,
+        roll: f32,
+        pitch: f32,
+        yaw: f32,
+        body_yaw: Option<f32>,
+    ) -> Result<(), JsValue> {
+        let pose = [x, y, z, roll, pitch, yaw];
+        self.set_head_transform(xyzrpy_to_transform(pose, self.head_z_offset_mm()), body_yaw)
+            .await
+    }
+
+    /// Move the head to the world-frame transform `t` with the body turned
+    /// to `body_yaw` (degrees, default 0).
+    ///
+    /// With a body yaw motor, the pose is solved by `inverse_kinematics_safe`,
+    /// so the body written may differ from `body_yaw` where the limits make it
+    /// follow the head. The singularity policy applies to the head joints at
+    /// the platform pose relative to that body: turning the body is a rigid
+    /// rotation of the whole platform and leaves its Jacobian unchanged.
+    async fn set_head_transform(
+        &self,
+        t: Matrix4<f32>,
+        body_yaw: Option<f32>,
+    ) -> Result<(), JsValue> {
+        let pose = transform_to_xyzrpy(&t, self.head_z_offset_mm());
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.set_head_pose(pose, body_yaw).await?);
+        }
+
+        let body_yaw_deg = body_yaw.unwrap_or(0.0);
+        let body = self.joints.borrow().body_yaw();
+        let joint_angles = match &body {
+            Some(_) => {
+                let limits = self.config.borrow().body_yaw_limits;
+                self.kinematics.borrow_mut().inverse_kinematics_safe(
+                    t,
+                    Some(body_yaw_deg.to_radians()),
+                    Some(limits.max_relative_yaw_deg.to_radians()),
+                    Some(limits.max_body_yaw_deg.to_radians()),
+                )?
+            }
+            None if body_yaw_deg == 0.0 => {
+                self.kinematics.borrow_mut().inverse_kinematics(t, None)?
+            }
+            None => return Err(JsValue::from_str("This robot has no body yaw motor")),
+        };
+
+        let body: Vec<Joint> = body.into_iter().collect();
+        let t_body = in_body_frame(
+            &t,
+            if body.is_empty() {
+                0.0
+            } else {
+                joint_angles[0]
+            },
+        );
+        let warning = self
+            .kinematics
+            .borrow()
+            .check_singularity(&joint_angles[body.len()..], &t_body)?;
+        if let Some(condition_number) = warning {
+            console::warn_1(
+                &format!(
+                    "Head pose {:?} is near a singularity (condition number {:.0})",
+                    pose, condition_number
+                )
+                .into(),
+            );
+        }
+        let joints: Vec<Joint> = body.into_iter().chain(self.head_joints()).collect();
+        let joint_deg: Vec<f32> = joint_angles.iter().map(|r| r.to_degrees()).collect();
+        self.write_joints(&joints, &joint_deg).await
+    }
+
+    /// Set how far the body turns with head poses: the largest head yaw
+    /// relative to the body (default 65°) and the largest body yaw (default
+    /// 160°).
+    pub fn set_body_yaw_limits(
+        &self,
+        max_relative_yaw_deg: f32,
+        max_body_yaw_deg: f32,
+    ) -> Result<(), JsValue> {
+        let limits = BodyYawLimits {
+            max_relative_yaw_deg,
+            max_body_yaw_deg,
+        };
+        if !limits.is_valid() {
+            return Err(ConfigError::InvalidBodyYawLimits(limits).into());
+        }
+        self.config.borrow_mut().body_yaw_limits = limits;
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Joint Positions
+    // ------------------------------------------------------------------------
+
+    /// Angles of the 6 head motors in degrees.
+    pub async fn get_head_joints(&self) -> Result<Vec<f32>, JsValue> {
+        self.read_joints(&self.head_joints()).await
+    }
+
+    /// Command the 6 head motors (degrees).
+    pub async fn set_head_joints(&self, angles_deg: Vec<f32>) -> Result<(), JsValue> {
+        let head = self.head_joints();
+        if angles_deg.len() != head.len() {
+            return Err(JsValue::from_str("Expected 6 joint angles for head motors"));
+        }
+        self.write_joints(&head, &angles_deg).await
+    }
+
+    /// Angles of all 8 motors (6 head + left + right antenna) in degrees.
+    pub async fn get_all_joints(&self) -> Result<Vec<f32>, JsValue> {
+        let joints = self.joints.borrow().head_and_antennas();
+        self.read_joints(&joints).await
+    }
+
+    /// Command all 8 motors (degrees).
+    pub async fn set_all_joints(&self, angles_deg: Vec<f32>) -> Result<(), JsValue> {
+        let joints = self.joints.borrow().head_and_antennas();
+        if angles_deg.len() != joints.len() {
+            return Err(JsValue::from_str(
+                "Expected 8 joint angles (6 head + 2 antennas)",
+            ));
+        }
+        self.write_joints(&joints, &angles_deg).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Named Joints
+    // ------------------------------------------------------------------------
+
+    /// Angle of the joint called `name`, in degrees.
+    ///
+    /// # Arguments
+    /// * `name` - Joint name, e.g. `"stewart_1"` or `"l_antenna"`
+    ///
+    /// # Errors
+    /// Returns an error if no joint has this name.
+    pub async fn get_joint(&self, name: String) -> Result<f32, JsValue> {
+        let joint = self.joints.borrow().get(&name)?.clone();
+        Ok(self.read_joints(&[joint]).await?[0])
+    }
+
+    /// Angles of every joint as an object keyed by joint name (degrees).
+    pub async fn get_joints(&self) -> Result<JsValue, JsValue> {
+        let joints = self.all_joints();
+        let angles = self.read_joints(&joints).await?;
+        let by_name: serde_json::Map<String, serde_json::Value> = joints
+            .iter()
+            .zip(angles)
+            .map(|(joint, angle)| (joint.name.clone(), angle.into()))
+            .collect();
+        js_sys::JSON::parse(&serde_json::Value::Object(by_name).to_string())
+    }
+
+    /// Command joints by name in a single bus write.
+    ///
+    /// # Arguments
+    /// * `targets` - Object mapping joint names to angles in degrees
+    ///
+    /// # Errors
+    /// Returns an error, without moving anything, if a name is unknown, an
+    /// angle is not a number or an angle exceeds its joint limits.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await robot.set_joints({ l_antenna: 30, r_antenna: -30 });
+    /// ```
+    pub async fn set_joints(&self, targets: JsValue) -> Result<(), JsValue> {
+        let targets: js_sys::Object = targets
+            .dyn_into()
+            .map_err(|_| JsValue::from_str("Expected an object of {name: angle}"))?;
+        let mut named = Vec::new();
+        for entry in js_sys::Object::entries(&targets).iter() {
+            let entry = js_sys::Array::from(&entry);
+            let name = entry.get(0).as_string().unwrap_or_default();
+            let angle_deg = entry.get(1).as_f64().ok_or_else(|| {
+                JsValue::from_str(&format!("Angle of '{}' is not a number", name))
+            })?;
+            named.push((name, angle_deg as f32));
+        }
+
+        let (joints, angles_deg): (Vec<Joint>, Vec<f32>) =
+            self.joints.borrow().resolve(&named)?.into_iter().unzip();
+        self.write_joints(&joints, &angles_deg).await
+    }
+
+    /// Restrict the commanded angle of one joint to `[min_deg, max_deg]`.
+    pub fn set_joint_limits(
+        &self,
+        name: String,
+        min_deg: f32,
+        max_deg: f32,
+    ) -> Result<(), JsValue> {
+        let range = checked_range(min_deg, max_deg)?;
+        self.joints.borrow_mut().get_mut(&name)?.limits_deg = range;
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Antennas
+    // ------------------------------------------------------------------------
+
+    /// Left antenna angle in degrees.
+    pub async fn get_left_antenna(&self) -> Result<f32, JsValue> {
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.get_antennas().await?[0]);
+        }
+        self.read_antenna(JointKind::LeftAntenna).await
+    }
+
+    /// Move the left antenna (degrees).
+    pub async fn set_left_antenna(&self, angle_deg: f32) -> Result<(), JsValue> {
+        if let Some(daemon) = self.daemon() {
+            let [_, right] = daemon.get_antennas().await?;
+            return Ok(daemon.set_antennas(angle_deg, right).await?);
+        }
+        let joint = self.antenna(JointKind::LeftAntenna)?;
+        self.write_joints(&[joint], &[angle_deg]).await
+    }
+
+    /// Right antenna angle in degrees.
+    pub async fn get_right_antenna(&self) -> Result<f32, JsValue> {
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.get_antennas().await?[1]);
+        }
+        self.read_antenna(JointKind::RightAntenna).await
+    }
+
+    /// Move the right antenna (degrees).
+    pub async fn set_right_antenna(&self, angle_deg: f32) -> Result<(), JsValue> {
+        if let Some(daemon) = self.daemon() {
+            let [left, _] = daemon.get_antennas().await?;
+            return Ok(daemon.set_antennas(left, angle_deg).await?);
+        }
+        let joint = self.antenna(JointKind::RightAntenna)?;
+        self.write_joints(&[joint], &[angle_deg]).await
+    }
+
+    /// Both antenna angles as `[left, right]` in degrees.
+    pub async fn get_antennas(&self) -> Result<Vec<f32>, JsValue> {
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.get_antennas().await?.to_vec());
+        }
+
+        self.read_joints(&self.antenna_joints()).await
+    }
+
+    /// Move both antennas (degrees).
+    pub async fn set_antennas(&self, left_deg: f32, right_deg: f32) -> Result<(), JsValue> {
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.set_antennas(left_deg, right_deg).await?);
+        }
+        self.write_joints(&self.antenna_joints(), &[left_deg, right_deg])
+            .await
+    }
+
+    // ------------------------------------------------------------------------
+    // Torque
+    // ------------------------------------------------------------------------
+
+    /// Enable torque on all motors.
+    pub async fn enable_torque(&self) -> Result<(), JsValue> {
+        self.set_torque(true).await
+    }
+
+    /// Disable torque on all motors.
+    pub async fn disable_torque(&self) -> Result<(), JsValue> {
+        self.set_torque(false).await
+    }
+
+    /// Enable torque on head motors only (11-16).
+    pub async fn enable_head_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&ids(&self.head_joints()), true).await
+    }
+
+    /// Disable torque on head motors only (11-16).
+    pub async fn disable_head_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&ids(&self.head_joints()), false).await
+    }
+
+    /// Enable torque on antenna motors only (17-18).
+    pub async fn enable_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&ids(&self.antenna_joints()), true).await
+    }
+
+    /// Disable torque on antenna motors only (17-18).
+    pub async fn disable_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&ids(&self.antenna_joints()), false).await
+    }
+
+    /// Enable torque on the left antenna motor only (17).
+    pub async fn enable_left_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&[self.antenna(JointKind::LeftAntenna)?.id], true)
+            .await
+    }
+
+    /// Disable torque on the left antenna motor only (17).
+    pub async fn disable_left_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&[self.antenna(JointKind::LeftAntenna)?.id], false)
+            .await
+    }
+
+    /// Enable torque on the right antenna motor only (18).
+    pub async fn enable_right_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&[self.antenna(JointKind::RightAntenna)?.id], true)
+            .await
+    }
+
+    /// Disable torque on the right antenna motor only (18).
+    pub async fn disable_right_antenna_torque(&self) -> Result<(), JsValue> {
+        self.write_torque(&[self.antenna(JointKind::RightAntenna)?.id], false)
+            .await
+    }
+
+    // ------------------------------------------------------------------------
+    // Motor Diagnostics
+    // ------------------------------------------------------------------------
+
+    /// Temperature of one motor in °C.
+    pub async fn get_motor_temperature(&self, motor_id: u8) -> Result<u8, JsValue> {
+        let port = self.port()?;
+        let packet = build_read_packet(motor_id, address::PRESENT_TEMPERATURE, 1);
+        let response = port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?;
+        Ok(parse_status_packet_1byte(&response)?)
+    }
+
+    /// Load of one motor, from -1000 to 1000 (percentage of max torque × 10).
+    pub async fn get_motor_load(&self, motor_id: u8) -> Result<i16, JsValue> {
+        let port = self.port()?;
+        let packet = build_read_packet(motor_id, address::PRESENT_LOAD, 2);
+        let response = port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?;
+        Ok(parse_status_packet_2byte_signed(&response)?)
+    }
+
+    /// Temperatures of motors 11-18 in °C (0 if a motor didn't respond).
+    pub async fn get_all_motor_temperatures(&self) -> Result<Vec<u8>, JsValue> {
+        self.read_temperatures(&ids(&self.all_joints())).await
+    }
+
+    /// Loads of motors 11-18 (0 if a motor didn't respond).
+    pub async fn get_all_motor_loads(&self) -> Result<Vec<i16>, JsValue> {
+        self.read_loads(&ids(&self.all_joints())).await
+    }
+
+    /// Temperatures of head motors 11-16 in °C (0 if a motor didn't respond).
+    pub async fn get_head_motor_temperatures(&self) -> Result<Vec<u8>, JsValue> {
+        self.read_temperatures(&ids(&self.head_joints())).await
+    }
+
+    /// Loads of head motors 11-16 (0 if a motor didn't respond).
+    pub async fn get_head_motor_loads(&self) -> Result<Vec<i16>, JsValue> {
+        self.read_loads(&ids(&self.head_joints())).await
+    }
+
+    /// Temperature of the left antenna motor (17) in °C.
+    pub async fn get_left_antenna_temperature(&self) -> Result<u8, JsValue> {
+        self.get_motor_temperature(self.antenna(JointKind::LeftAntenna)?.id)
+            .await
+    }
+
+    /// Temperature of the right antenna motor (18) in °C.
+    pub async fn get_right_antenna_temperature(&self) -> Result<u8, JsValue> {
+        self.get_motor_temperature(self.antenna(JointKind::RightAntenna)?.id)
+            .await
+    }
+
+    /// Antenna temperatures as `[left, right]` in °C.
+    pub async fn get_antenna_temperatures(&self) -> Result<Vec<u8>, JsValue> {
+        self.read_temperatures(&ids(&self.antenna_joints())).await
+    }
+
+    /// Load of the left antenna motor (17).
+    pub async fn get_left_antenna_load(&self) -> Result<i16, JsValue> {
+        self.get_motor_load(self.antenna(JointKind::LeftAntenna)?.id)
+            .await
+    }
+
+    /// Load of the right antenna motor (18).
+    pub async fn get_right_antenna_load(&self) -> Result<i16, JsValue> {
+        self.get_motor_load(self.antenna(JointKind::RightAntenna)?.id)
+            .await
+    }
+
+    /// Antenna loads as `[left, right]`.
+    pub async fn get_antenna_loads(&self) -> Result<Vec<i16>, JsValue> {
+        self.read_loads(&ids(&self.antenna_joints())).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Motor Reboot
+    // ------------------------------------------------------------------------
+
+    /// Reboot one motor, clearing its hardware error status (~500 ms).
+    pub async fn reboot_motor(&self, motor_id: u8) -> Result<(), JsValue> {
+        let port = self.port()?;
+        console::log_1(&format!("Rebooting motor {}...", motor_id).into());
+
+        let packet = build_reboot_packet(motor_id);
+        port.write(&packet).await?;
+
+        // Wait for motor to reboot
+        sleep(500).await?;
+
+        console::log_1(&format!("Motor {} rebooted", motor_id).into());
+        Ok(())
+    }
+
+    /// Reboot the left antenna motor (17).
+    pub async fn reboot_left_antenna(&self) -> Result<(), JsValue> {
+        self.reboot_motor(self.antenna(JointKind::LeftAntenna)?.id)
+            .await
+    }
+
+    /// Reboot the right antenna motor (18).
+    pub async fn reboot_right_antenna(&self) -> Result<(), JsValue> {
+        self.reboot_motor(self.antenna(JointKind::RightAntenna)?.id)
+            .await
+    }
+
+    /// Reboot both antenna motors.
+    pub async fn reboot_antennas(&self) -> Result<(), JsValue> {
+        self.reboot_motor(self.antenna(JointKind::LeftAntenna)?.id)
+            .await?;
+        self.reboot_motor(self.antenna(JointKind::RightAntenna)?.id)
+            .await
+    }
+
+    /// Reboot all head motors (11-16), about 3 seconds.
+    pub async fn reboot_head_motors(&self) -> Result<(), JsValue> {
+        console::log_1(&JsValue::from_str("Rebooting head motors..."));
+        for motor_id in ids(&self.head_joints()) {
+            self.reboot_motor(motor_id).await?;
+        }
+        console::log_1(&JsValue::from_str("Head motors rebooted"));
+        Ok(())
+    }
+
+    /// Reboot all motors, about 4 seconds.
+    pub async fn reboot_all_motors(&self) -> Result<(), JsValue> {
+        console::log_1(&JsValue::from_str("Rebooting all motors..."));
+        for motor_id in ids(&self.all_joints()) {
+            self.reboot_motor(motor_id).await?;
+        }
+        console::log_1(&JsValue::from_str("All motors rebooted successfully"));
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Typed State
+    // ------------------------------------------------------------------------
+
+    /// Current head pose.
+    pub async fn get_pose(&self) -> Result<HeadPose, JsValue> {
+        let pose = self.get_head_pose().await?;
+        HeadPose::from_array(pose)
+    }
+
+    /// Move the head to `pose`.
+    pub async fn set_pose(&self, pose: &HeadPose) -> Result<(), JsValue> {
+        let HeadPose {
+            x,
+            y,
+            z,
+            roll,
+            pitch,
+            yaw,
+            body_yaw,
+        } = *pose;
+        self.set_head_pose(x, y, z, roll, pitch, yaw, Some(body_yaw))
+            .await
+    }
+
+    /// Angles of every joint.
+    pub async fn get_joint_state(&self) -> Result<JointState, JsValue> {
+        JointState::from_array(self.get_all_joints().await?)
+    }
+
+    /// Command every joint.
+    pub async fn set_joint_state(&self, joints: &JointState) -> Result<(), JsValue> {
+        self.set_all_joints(joints.to_array()).await
+    }
+
+    /// Antenna angles.
+    pub async fn get_antenna_state(&self) -> Result<AntennaState, JsValue> {
+        let angles = self.get_antennas().await?;
+        Ok(AntennaState::new(angles[0], angles[1]))
+    }
+
+    /// Move both antennas.
+    pub async fn set_antenna_state(&self, antennas: &AntennaState) -> Result<(), JsValue> {
+        self.set_antennas(antennas.left, antennas.right).await
+    }
+
+    /// Temperature and load of every motor, in motor ID order.
+    pub async fn get_motor_diagnostics(&self) -> Result<Vec<MotorDiagnostics>, JsValue> {
+        let port = self.port()?;
+        let joints = self.all_joints();
+        let motor_ids = ids(&joints);
+        let packet = build_sync_read_temperature(&motor_ids);
+        let temperatures =
+            parse_1byte_packets(&port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?);
+        let packet = build_sync_read_load(&motor_ids);
+        let loads =
+            parse_2byte_signed_packets(&port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?);
+        Ok(MotorDiagnostics::collect(&joints, &temperatures, &loads))
+    }
+
+    /// Temperature and load of every motor as an object keyed by joint name.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const diagnostics = await robot.get_joint_diagnostics();
+    /// console.log(diagnostics.l_antenna.temperature);
+    /// ```
+    pub async fn get_joint_diagnostics(&self) -> Result<JsValue, JsValue> {
+        let by_name = js_sys::Object::new();
+        for diagnostics in self.get_motor_diagnostics().await? {
+            let name = JsValue::from_str(&diagnostics.name);
+            js_sys::Reflect::set(&by_name, &name, &diagnostics.into())?;
+        }
+        Ok(by_name.into())
+    }
+
+    // ------------------------------------------------------------------------
+    // Kinematics (no hardware access)
+    // ------------------------------------------------------------------------
+
+    /// Head pose `[x, y, z, roll, pitch, yaw]` (mm, degrees) for 6 joint angles (degrees).
+    pub fn forward_kinematics(&self, angles_deg: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+        if angles_deg.len() < 6 {
+            return Err(JsValue::from_str("Expected at least 6 joint angles"));
+        }
+
+        let angles_rad: Vec<f32> = angles_deg[0..6].iter().map(|d| d.to_radians()).collect();
+        let head_z_offset_mm = self.head_z_offset_mm();
+
+        // Solve from the neutral pose: the angles may be far from the robot's
+        let mut kinematics = self.kinematics.borrow_mut();
+        kinematics.reset_forward_kinematics(xyzrpy_to_transform([0.0; 6], head_z_offset_mm));
+        let solution = kinematics.solve_forward_kinematics(&angles_rad, None);
+        Ok(transform_to_xyzrpy(&solution.t_world_platform, head_z_offset_mm).to_vec())
+    }
+
+    /// Head joint angles (degrees) for a head pose `[x, y, z, roll, pitch,
+    /// yaw]` (mm, degrees, world frame), optionally followed by the body yaw
+    /// as in `get_head_pose`; the head is then solved with the body turned
+    /// that far.
+    pub fn inverse_kinematics(&self, xyzrpy: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+        let pose = HeadPose::from_array(xyzrpy)?;
+        let joints = self.solve_inverse_kinematics(pose.xyzrpy(), pose.body_yaw)?;
+        Ok(joints.iter().map(|r| r.to_degrees()).collect())
+    }
+
+    /// Velocity kinematics of the head at `[x, y, z, roll, pitch, yaw]` (mm,
+    /// degrees).
+    pub fn head_jacobian(&self, xyzrpy: Vec<f32>) -> Result<HeadJacobian, JsValue> {
+        let pose = HeadPose::from_array(xyzrpy)?.xyzrpy();
+        let t = xyzrpy_to_transform(pose, self.head_z_offset_mm());
+        let mut kinematics = self.kinematics.borrow_mut();
+        let angles = kinematics.inverse_kinematics(t, None)?;
+        Ok(kinematics.platform_jacobian(&angles, &t).into())
+    }
+
+    /// The reachable pose closest to a request (mm, degrees), on the path
+    /// from the neutral pose, and how far it was moved.
+    pub fn project_head_pose(
+        &self,
+        x: f32,
+        y: f32,
+        z: f32,
+        roll: f32,
+        pitch: f32,
+        yaw: f32,
+    ) -> Result<HeadPoseProjection, JsValue> {
+        let pose = [x, y, z, roll, pitch, yaw];
+        let offset = self.head_z_offset_mm();
+        let projection = self.kinematics.borrow_mut().project_inverse_kinematics(
+            xyzrpy_to_transform(pose, offset),
+            xyzrpy_to_transform([0.0; 6], offset),
+            None,
+        )?;
+        let reachable = if projection.moved() {
+            transform_to_xyzrpy(&projection.t_world_platform, offset)
+        } else {
+            pose
+        };
+        Ok(HeadPoseProjection::new(reachable, &projection))
+    }
+
+    /// Reachable head poses of a grid (default: translations every 5 mm),
+    /// within the current head joint limits, as the JSON of
+    /// [`Workspace::to_json`](crate::workspace::Workspace::to_json).
+    pub fn compute_workspace(&self, grid: Option<String>) -> Result<String, JsValue> {
+        let grid = match grid {
+            Some(json) => serde_json::from_str::<WorkspaceGrid>(&json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid workspace grid: {}", e)))?,
+            None => WorkspaceGrid::translation(5.0),
+        };
+        let limits: Vec<[f32; 2]> = self
+            .joints
+            .borrow()
+            .head()
+            .iter()
+            .map(|j| j.limits_deg.map(f32::to_radians))
+            .collect();
+        let workspace =
+            self.kinematics
+                .borrow_mut()
+                .compute_workspace(&grid, self.head_z_offset_mm(), &limits);
+        Ok(workspace.to_json())
+    }
+
+    // ------------------------------------------------------------------------
+    // Recording & Playback
+    // ------------------------------------------------------------------------
+
+    /// Stream FK to `updatePose()` until `stop()`, recording for `duration` ms if given.
+    ///
+    /// Iterations are paced on fixed deadlines, so a stream running in a Web
+    /// Worker keeps its rate while the page is busy.
+    pub async fn start_fk_stream(&self, duration: Option<f64>) -> Result<(), JsValue> {
+        let port = self.port()?;
+
+        let table = self.joints.borrow().clone();
+        let motor_ids = ids(table.all());
+        let head_indices: Vec<usize> = ids(&table.head())
+            .into_iter()
+            .filter_map(|id| table.index_of(id))
+            .collect();
+        let head_z_offset_mm = self.head_z_offset_mm();
+        let mut results = vec![0.0f32; motor_ids.len()];
+        let start_time = js_sys::Date::now();
+
+        self.stop_requested.set(false);
+        self.recording.borrow_mut().clear();
+        let mut pacer = LoopPacer::new(FK_STREAM_PERIOD_MS);
+
+        loop {
+            port.record_loop();
+            let ping_current = build_sync_current_position(&motor_ids);
+            let result = port
+                .write_read_with_priority(&ping_current, Some(DEFAULT_WAIT_MS), Priority::Low)
+                .await;
+
+            match result {
+                Err(err) => {
+                    console::log_1(&format!("Error reading: {:?}", err).into());
+                }
+                Ok(res) => {
+                    // Use resilient parsing that handles missing motor responses
+                    for (id, pos) in parse_position_packets(&res) {
+                        if let Some(idx) = table.index_of(id) {
+                            results[idx] = raw_to_radians(pos);
+                        }
+                    }
+
+                    if let Some(dur) = duration {
+                        let progress = (js_sys::Date::now() - start_time) / dur;
+                        if progress >= 1.0 {
+                            break;
+                        }
+                        self.recording.borrow_mut().push(results.clone());
+                    }
+
+                    let head: Vec<f32> = head_indices
+                        .iter()
+                        .map(|&i| table.all()[i].joint_degrees(results[i]).to_radians())
+                        .collect();
+                    let solution = self
+                        .kinematics
+                        .borrow_mut()
+                        .solve_forward_kinematics(&head, None);
+                    update_pose(transform_to_xyzrpy(
+                        &solution.t_world_platform,
+                        head_z_offset_mm,
+                    ));
+                }
+            }
+
+            sleep(pacer.delay_ms(js_sys::Date::now())).await?;
+
+            if self.stop_requested.get() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Play back the frames recorded by `start_fk_stream(duration)`.
+    ///
+    /// The head motion is checked for interference and against the
+    /// singularity policy before anything moves: near a singularity it logs
+    /// a console warning, or returns an error if such poses are refused.
+    pub async fn replay_recording(&self) -> Result<(), JsValue> {
+        let frames = self.recording.borrow().clone();
+        if frames.is_empty() {
+            return Err(JsValue::from_str("No recorded frames to replay"));
+        }
+        if let Some(condition_number) = self.check_recording(&frames)? {
+            console::warn_1(
+                &format!(
+                    "The recording passes near a singularity (condition number {:.0})",
+                    condition_number
+                )
+                .into(),
+            );
+        }
+
+        self.enable_torque().await?;
+
+        self.stop_requested.set(false);
+        let port = self.port()?;
+        let motor_ids = ids(&self.all_joints());
+        let mut pacer = LoopPacer::new(REPLAY_PERIOD_MS);
+
+        for frame in frames.iter() {
+            port.record_loop();
+            let packet = build_sync_write_position_radians(&motor_ids, frame);
+            port.write_with_priority(&packet, Priority::High).await?;
+            sleep(pacer.delay_ms(js_sys::Date::now())).await?;
+
+            if self.stop_requested.get() {
+                break;
+            }
+        }
+
+        self.disable_torque().await?;
+        Ok(())
+    }
+
+    /// Stop this instance's FK stream or replay.
+    pub fn stop(&self) {
+        self.stop_requested.set(true);
+    }
+
+    /// Clear recorded frames.
+    pub fn clear_recording(&self) {
+        self.recording.borrow_mut().clear();
+    }
+
+    /// Number of recorded frames.
+    pub fn get_recording_length(&self) -> usize {
+        self.recording.borrow().len()
+    }
+
+    // ------------------------------------------------------------------------
+    // Limits
+    // ------------------------------------------------------------------------
+
+    /// Restrict commanded head joint angles to `[min_deg, max_deg]`.
+    ///
+    /// Commands outside the range, including IK solutions of `set_head_pose`,
+    /// are rejected before anything is sent.
+    pub fn set_head_joint_limits(&self, min_deg: f32, max_deg: f32) -> Result<(), JsValue> {
+        self.set_group_limits(&[JointKind::Head], min_deg, max_deg)
+    }
+
+    /// Handle head poses near a singularity: `"ignore"`, `"warn"` (default,
+    /// on the console) or `"refuse"` (IK returns an error), when the platform
+    /// Jacobian condition number exceeds `max_condition_number` (default
+    /// 1000).
+    pub fn set_singularity_policy(
+        &self,
+        policy: String,
+        max_condition_number: Option<f32>,
+    ) -> Result<(), JsValue> {
+        let policy = match policy.as_str() {
+            "ignore" => SingularityPolicy::Ignore,
+            "warn" => SingularityPolicy::Warn,
+            "refuse" => SingularityPolicy::Refuse,
+            other => {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown singularity policy '{}' (ignore, warn or refuse)",
+                    other
+                )))
+            }
+        };
+        self.kinematics
+            .borrow_mut()
+            .set_singularity_policy(policy, max_condition_number.unwrap_or(MAX_CONDITION_NUMBER));
+        Ok(())
+    }
+
+    /// Restrict commanded antenna angles to `[min_deg, max_deg]`.
+    pub fn set_antenna_limits(&self, min_deg: f32, max_deg: f32) -> Result<(), JsValue> {
+        self.set_group_limits(
+            &[JointKind::LeftAntenna, JointKind::RightAntenna],
+            min_deg,
+            max_deg,
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    // Capture & Statistics
+    // ------------------------------------------------------------------------
+
+    /// Start recording all bus traffic.
+    pub fn start_capture(&self) -> Result<(), JsValue> {
+        self.port()?.start_capture();
+        Ok(())
+    }
+
+    /// Stop recording bus traffic and return the capture as JSON.
+    pub fn stop_capture(&self) -> Result<String, JsValue> {
+        self.port()?
+            .stop_capture()
+            .map(|capture| capture.to_json())
+            .ok_or_else(|| JsValue::from_str("No capture running. Call start_capture() first."))
+    }
+
+    /// Number of recorded events a replay has not consumed yet.
+    pub fn get_replay_remaining(&self) -> Result<usize, JsValue> {
+        self.port()?
+            .replay_remaining()
+            .ok_or_else(|| JsValue::from_str("Not connected to a replay"))
+    }
+
+    /// Bus health statistics since connecting or the last reset.
+    pub fn get_connection_stats(&self) -> Result<JsValue, JsValue> {
+        js_sys::JSON::parse(&self.port()?.stats_json())
+    }
+
+    /// Reset all connection statistics to zero.
+    pub fn reset_connection_stats(&self) -> Result<(), JsValue> {
+        self.port()?.reset_stats();
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Internal Helpers
+// ============================================================================
+
+impl ReachyMini {
+    /// Get the raw bus connection or return an error.
+    fn port(&self) -> Result<Rc<GenericPort>, JsValue> {
+        if self.daemon.borrow().is_some() {
+            return Err(JsValue::from_str(
+                "Not supported by the daemon backend (requires raw bus access)",
+            ));
+        }
+        self.port
+            .borrow()
+            .clone()
+            .ok_or_else(|| JsValue::from_str("Not connected to Reachy Mini. Call connect() first."))
+    }
+
+    /// Make `port` the connection, replacing any daemon connection.
+    fn set_port(&self, port: GenericPort) {
+        *self.daemon.borrow_mut() = None;
+        *self.port.borrow_mut() = Some(Rc::new(port));
+    }
+
+    /// Get the daemon client if connected through the daemon's high-level API.
+    fn daemon(&self) -> Option<Rc<DaemonClient>> {
+        self.daemon.borrow().clone()
+    }
+
+    /// The six head joints.
+    fn head_joints(&self) -> Vec<Joint> {
+        self.joints.borrow().head()
+    }
+
+    /// The antenna joints as `[left, right]`.
+    fn antenna_joints(&self) -> Vec<Joint> {
+        self.joints.borrow().antennas()
+    }
+
+    /// Every joint, in bus order.
+    fn all_joints(&self) -> Vec<Joint> {
+        self.joints.borrow().all().to_vec()
+    }
+
+    /// The antenna joint of `kind`.
+    fn antenna(&self, kind: JointKind) -> Result<Joint, JsValue> {
+        self.joints
+            .borrow()
+            .of_kind(kind)
+            .into_iter()
+            .next()
+            .ok_or_else(|| JsValue::from_str(&format!("No {:?} joint on this robot", kind)))
+    }
+
+    /// Apply `[min_deg, max_deg]` to every joint of the given kinds.
+    fn set_group_limits(
+        &self,
+        kinds: &[JointKind],
+        min_deg: f32,
+        max_deg: f32,
+    ) -> Result<(), JsValue> {
+        let range = checked_range(min_deg, max_deg)?;
+        for joint in self.joints.borrow_mut().iter_mut() {
+            if kinds.contains(&joint.kind) {
+                joint.limits_deg = range;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set torque on all motors.
+    async fn set_torque(&self, enable: bool) -> Result<(), JsValue> {
+        if let Some(daemon) = self.daemon() {
+            return Ok(daemon.set_torque(enable).await?);
+        }
+        self.write_torque(&ids(&self.all_joints()), enable).await
+    }
+
+    async fn write_torque(&self, motor_ids: &[u8], enable: bool) -> Result<(), JsValue> {
+        let port = self.port()?;
+        port.write(&build_sync_write_torque(motor_ids, enable))
+            .await
+    }
+
+    /// Read a single antenna joint (degrees).
+    async fn read_antenna(&self, kind: JointKind) -> Result<f32, JsValue> {
+        let joint = self.antenna(kind)?;
+        Ok(self.read_joints(&[joint]).await?[0])
+    }
+
+    /// Read joint angles (degrees), in `joints` order.
+    async fn read_joints(&self, joints: &[Joint]) -> Result<Vec<f32>, JsValue> {
+        let port = self.port()?;
+        let angles_rad = read_motor_positions(&port, &ids(joints)).await?;
+        Ok(joints
+            .iter()
+            .zip(angles_rad)
+            .map(|(joint, angle_rad)| joint.joint_degrees(angle_rad))
+            .collect())
+    }
+
+    /// Command joint angles (degrees) after checking every joint's limits.
+    async fn write_joints(&self, joints: &[Joint], angles_deg: &[f32]) -> Result<(), JsValue> {
+        for (joint, &angle_deg) in joints.iter().zip(angles_deg) {
+            joint.check_limits(angle_deg)?;
+        }
+
+        let port = self.port()?;
+        let angles_rad: Vec<f32> = joints
+            .iter()
+            .zip(angles_deg)
+            .map(|(joint, &angle_deg)| joint.motor_radians(angle_deg))
+            .collect();
+        let packet = build_sync_write_position_radians(&ids(joints), &angles_rad);
+        port.write_with_priority(&packet, Priority::High).await?;
+        Ok(())
+    }
+
+    /// Sync-read temperatures, in `motor_ids` order (0 for missing motors).
+    async fn read_temperatures(&self, motor_ids: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let port = self.port()?;
+        let packet = build_sync_read_temperature(motor_ids);
+        let response = port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?;
+        Ok(by_motor_id(motor_ids, parse_1byte_packets(&response)))
+    }
+
+    /// Sync-read loads, in `motor_ids` order (0 for missing motors).
+    async fn read_loads(&self, motor_ids: &[u8]) -> Result<Vec<i16>, JsValue> {
+        let port = self.port()?;
+        let packet = build_sync_read_load(motor_ids);
+        let response = port.write_read(&packet, Some(DEFAULT_WAIT_MS)).await?;
+        Ok(by_motor_id(
+            motor_ids,
+            parse_2byte_signed_packets(&response),
+        ))
+    }
+
+    /// Apply the interference model and the singularity policy to the head
+    /// motion of recorded `frames` (motor radians, in joint table order).
+    fn check_recording(&self, frames: &[Vec<f32>]) -> Result<Option<f32>, KinematicsError> {
+        let table = self.joints.borrow().clone();
+        let head: Vec<(usize, Joint)> = table
+            .head()
+            .into_iter()
+            .filter_map(|joint| Some((table.index_of(joint.id)?, joint)))
+            .collect();
+        let samples: Vec<Vec<f32>> = frames
+            .iter()
+            .map(|frame| {
+                head.iter()
+                    .map(|(i, joint)| joint.joint_degrees(frame[*i]).to_radians())
+                    .collect()
+            })
+            .collect();
+        self.kinematics.borrow_mut().check_trajectory(&samples)
+    }
+
+    /// Head joint angles (radians) for `[x, y, z, roll, pitch, yaw]` (mm, degrees).
+    fn solve_inverse_kinematics(
+        &self,
+        pose: [f32; 6],
+        body_yaw_deg: f32,
+    ) -> Result<Vec<f32>, KinematicsError> {
+        let body_yaw = (body_yaw_deg != 0.0).then(|| body_yaw_deg.to_radians());
+        self.kinematics
+            .borrow_mut()
+            .inverse_kinematics(xyzrpy_to_transform(pose, self.head_z_offset_mm()), body_yaw)
+    }
+
+    /// Head height (mm) reported as `z = 0`.
+    fn head_z_offset_mm(&self) -> f32 {
+        self.config.borrow().geometry.head_z_offset_mm
+    }
+
+    /// Make `config` the active profile, rebuilding the joint table and solver.
+    fn apply_config(&self, config: RobotConfig) {
+        *self.joints.borrow_mut() = config.joint_table();
+        self.rebuild_kinematics(&config);
+        *self.config.borrow_mut() = config;
+    }
+
+    /// Rebuild the solver for `config`, keeping the singularity policy and
+    /// forward kinematics limits.
+    fn rebuild_kinematics(&self, config: &RobotConfig) {
+        let mut kinematics = config.kinematics();
+        kinematics.copy_settings_from(&self.kinematics.borrow());
+        *self.kinematics.borrow_mut() = kinematics;
+    }
+}
+
+/// Order parsed `(id, value)` pairs like `motor_ids`, defaulting missing motors.
+fn by_motor_id<T: Copy + Default>(motor_ids: &[u8], parsed: Vec<(u8, T)>) -> Vec<T> {
+    let mut values = vec![T::default(); motor_ids.len()];
+    for (id, value) in parsed {
+        if let Some(idx) = motor_ids.iter().position(|&m| m == id) {
+            values[idx] = value;
+        }
+    }
+    values
+}
+
+/// Validate a `[min, max]` limit range.
+fn checked_range(min_deg: f32, max_deg: f32) -> Result<[f32; 2], JsValue> {
+    if min_deg <= max_deg {
+        Ok([min_deg, max_deg])
+    } else {
+        Err(JsValue::from_str("Limit minimum must not exceed maximum"))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_limits() {
+        let robot = ReachyMini::new();
+        robot.set_antenna_limits(-30.0, 30.0).unwrap();
+        robot
+            .set_joint_limits("stewart_2".to_string(), -10.0, 10.0)
+            .unwrap();
+
+        let joints = robot.joints.borrow();
+        assert_eq!(joints.get("l_antenna").unwrap().limits_deg, [-30.0, 30.0]);
+        assert_eq!(joints.get("r_antenna").unwrap().limits_deg, [-30.0, 30.0]);
+        assert_eq!(joints.get("stewart_1").unwrap().limits_deg, [-180.0, 180.0]);
+        assert_eq!(joints.get("stewart_2").unwrap().limits_deg, [-10.0, 10.0]);
+        assert!(joints.get("l_antenna").unwrap().check_limits(45.0).is_err());
+    }
+
+    #[test]
+    fn test_instances_are_independent() {
+        let first = ReachyMini::new();
+        let second = ReachyMini::new();
+        assert!(!first.is_connected());
+
+        first.stop();
+        first.set_antenna_limits(-10.0, 10.0).unwrap();
+        assert!(first.stop_requested.get());
+        assert!(!second.stop_requested.get());
+        assert_eq!(*second.joints.borrow(), JointTable::reachy_mini());
+
+        first.recording.borrow_mut().push(vec![0.0; 8]);
+        assert_eq!(first.get_recording_length(), 1);
+        assert_eq!(second.get_recording_length(), 0);
+    }
+
+    #[test]
+    fn test_replay_checks_interference() {
+        let robot = ReachyMini::new();
+        let mut config = RobotConfig::default();
+        config.geometry.interference = None;
+        let mut unchecked = config.kinematics();
+
+        // Recorded frames hold motor radians for every joint of the table
+        let table = robot.joints.borrow().clone();
+        let mut frame = |pose: [f32; 6]| {
+            let joint_rad = unchecked
+                .inverse_kinematics(xyzrpy_to_transform(pose, 172.0), None)
+                .unwrap();
+            let mut frame = vec![0.0; table.all().len()];
+            for (joint, angle) in table.head().iter().zip(&joint_rad) {
+                frame[table.index_of(joint.id).unwrap()] = joint.motor_radians(angle.to_degrees());
+            }
+            frame
+        };
+        let neutral = frame([0.0; 6]);
+        let low = frame([0.0, 0.0, -40.0, 0.0, 0.0, 0.0]);
+
+        let recording = [neutral, low];
+        assert_eq!(robot.check_recording(&recording[..1]), Ok(None));
+        assert!(matches!(
+            robot.check_recording(&recording),
+            Err(KinematicsError::Interference(_))
+        ));
+    }
+
+    #[test]
+    fn test_inverse_kinematics_takes_head_pose() {
+        let robot = ReachyMini::new();
+        let pose = vec![0.0, 0.0, 10.0, 0.0, 5.0, 30.0];
+        let without_body = robot.inverse_kinematics(pose.clone()).unwrap();
+        let mut with_zero_body = pose.clone();
+        with_zero_body.push(0.0);
+        assert_eq!(
+            robot.inverse_kinematics(with_zero_body).unwrap(),
+            without_body
+        );
+
+        // Head yawed 30° in the world with the body at 20° is 10° from the body
+        let turned = robot
+            .inverse_kinematics(vec![0.0, 0.0, 10.0, 0.0, 5.0, 30.0, 20.0])
+            .unwrap();
+        let relative = robot
+            .inverse_kinematics(vec![0.0, 0.0, 10.0, 0.0, 5.0, 10.0])
+            .unwrap();
+        assert!(turned
+            .iter()
+            .zip(&relative)
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_profile_switch_keeps_solver_settings() {
+        let robot = ReachyMini::new();
+        robot
+            .set_singularity_policy("refuse".to_string(), Some(500.0))
+            .unwrap();
+        robot.load_config("wireless".to_string()).unwrap();
+        assert_eq!(
+            robot.kinematics.borrow().singularity_policy(),
+            (SingularityPolicy::Refuse, 500.0)
+        );
+    }
+
+    #[test]
+    fn test_by_motor_id() {
+        let values = by_motor_id(&[17, 18, 11], vec![(11, 40u8), (17, 35), (99, 1)]);
+        assert_eq!(values, vec![35, 0, 40]);
+    }
+}
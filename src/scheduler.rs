@@ -0,0 +1,274 @@
+//! # Bus Scheduler
+//!
+//! The Dynamixel bus is half-duplex: a request and its status packet must not be
+//! interleaved with another caller's traffic. [`BusScheduler`] hands out exclusive
+//! access to the bus one transaction at a time.
+//!
+//! ## Ordering
+//!
+//! - Waiters with a higher [`Priority`] are served first
+//! - Waiters with the same priority are served in FIFO order
+//! - A waiter that is dropped before being served leaves the queue
+//!
+//! The scheduler is single-threaded (like the browser event loop) and does not
+//! depend on any JavaScript API, so any number of concurrent `async` callers can
+//! await it.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+// ============================================================================
+// Priority
+// ============================================================================
+
+/// Priority of a bus transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Background traffic (telemetry streams, polling loops)
+    Low = 0,
+    /// Regular requests (reads, diagnostics)
+    #[default]
+    Normal = 1,
+    /// Motion commands that should not wait behind telemetry
+    High = 2,
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// A caller waiting for the bus.
+struct Waiter {
+    ticket: u64,
+    priority: Priority,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Whether a transaction currently owns the bus
+    busy: bool,
+    /// Ticket handed the bus but not yet polled by its owner
+    granted: Option<u64>,
+    /// Monotonic counter used for FIFO ordering
+    next_ticket: u64,
+    /// Callers waiting for the bus
+    queue: Vec<Waiter>,
+}
+
+impl State {
+    /// Pass the bus to the next waiter, or mark it idle if nobody is waiting.
+    fn release(&mut self) {
+        let next = self
+            .queue
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                a.priority
+                    .cmp(&b.priority)
+                    .then_with(|| b.ticket.cmp(&a.ticket))
+            })
+            .map(|(idx, _)| idx);
+
+        match next {
+            Some(idx) => {
+                let waiter = self.queue.remove(idx);
+                self.granted = Some(waiter.ticket);
+                if let Some(waker) = waiter.waker {
+                    waker.wake();
+                }
+            }
+            None => {
+                self.busy = false;
+                self.granted = None;
+            }
+        }
+    }
+}
+
+/// FIFO + priority scheduler granting exclusive bus access.
+#[derive(Default)]
+pub struct BusScheduler {
+    state: RefCell<State>,
+}
+
+impl BusScheduler {
+    /// Create an idle scheduler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for exclusive access to the bus.
+    ///
+    /// The bus is released when the returned [`BusGuard`] is dropped.
+    pub fn acquire(&self, priority: Priority) -> Acquire<'_> {
+        Acquire {
+            scheduler: self,
+            priority,
+            ticket: None,
+            done: false,
+        }
+    }
+}
+
+/// Future returned by [`BusScheduler::acquire`].
+pub struct Acquire<'a> {
+    scheduler: &'a BusScheduler,
+    priority: Priority,
+    ticket: Option<u64>,
+    done: bool,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = BusGuard<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let scheduler = self.scheduler;
+        let mut state = scheduler.state.borrow_mut();
+
+        match self.ticket {
+            None => {
+                // Fast path: bus idle and nobody queued
+                if !state.busy && state.queue.is_empty() {
+                    state.busy = true;
+                    drop(state);
+                    self.done = true;
+                    return Poll::Ready(BusGuard { scheduler });
+                }
+
+                let ticket = state.next_ticket;
+                state.next_ticket += 1;
+                state.queue.push(Waiter {
+                    ticket,
+                    priority: self.priority,
+                    waker: Some(cx.waker().clone()),
+                });
+                drop(state);
+                self.ticket = Some(ticket);
+                Poll::Pending
+            }
+            Some(ticket) => {
+                if state.granted == Some(ticket) {
+                    state.granted = None;
+                    drop(state);
+                    self.done = true;
+                    return Poll::Ready(BusGuard { scheduler });
+                }
+
+                if let Some(waiter) = state.queue.iter_mut().find(|w| w.ticket == ticket) {
+                    waiter.waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        if let Some(ticket) = self.ticket {
+            let mut state = self.scheduler.state.borrow_mut();
+            if state.granted == Some(ticket) {
+                // We were handed the bus but gave up before using it
+                state.release();
+            } else {
+                state.queue.retain(|w| w.ticket != ticket);
+            }
+        }
+    }
+}
+
+/// Exclusive bus access. Dropping the guard hands the bus to the next waiter.
+pub struct BusGuard<'a> {
+    scheduler: &'a BusScheduler,
+}
+
+impl Drop for BusGuard<'_> {
+    fn drop(&mut self) {
+        self.scheduler.state.borrow_mut().release();
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll_once<F: Future + Unpin>(fut: &mut F) -> Poll<F::Output> {
+        let mut cx = Context::from_waker(Waker::noop());
+        Pin::new(fut).poll(&mut cx)
+    }
+
+    #[test]
+    fn test_idle_bus_is_granted_immediately() {
+        let scheduler = BusScheduler::new();
+        let mut a = scheduler.acquire(Priority::Normal);
+        let guard = poll_once(&mut a);
+        assert!(guard.is_ready());
+        assert!(scheduler.state.borrow().busy);
+        drop(guard);
+        assert!(!scheduler.state.borrow().busy);
+    }
+
+    #[test]
+    fn test_fifo_within_same_priority() {
+        let scheduler = BusScheduler::new();
+        let mut first = scheduler.acquire(Priority::Normal);
+        let guard = poll_once(&mut first);
+
+        let mut second = scheduler.acquire(Priority::Normal);
+        let mut third = scheduler.acquire(Priority::Normal);
+        assert!(poll_once(&mut second).is_pending());
+        assert!(poll_once(&mut third).is_pending());
+        assert_eq!(scheduler.state.borrow().queue.len(), 2);
+
+        drop(guard);
+        assert!(poll_once(&mut third).is_pending());
+        let guard = poll_once(&mut second);
+        assert!(guard.is_ready());
+
+        drop(guard);
+        assert!(poll_once(&mut third).is_ready());
+    }
+
+    #[test]
+    fn test_higher_priority_goes_first() {
+        let scheduler = BusScheduler::new();
+        let mut owner = scheduler.acquire(Priority::Normal);
+        let guard = poll_once(&mut owner);
+
+        let mut telemetry = scheduler.acquire(Priority::Low);
+        let mut command = scheduler.acquire(Priority::High);
+        assert!(poll_once(&mut telemetry).is_pending());
+        assert!(poll_once(&mut command).is_pending());
+
+        drop(guard);
+        assert!(poll_once(&mut telemetry).is_pending());
+        assert!(poll_once(&mut command).is_ready());
+    }
+
+    #[test]
+    fn test_dropped_waiter_leaves_queue() {
+        let scheduler = BusScheduler::new();
+        let mut owner = scheduler.acquire(Priority::Normal);
+        let guard = poll_once(&mut owner);
+
+        let mut abandoned = scheduler.acquire(Priority::High);
+        let mut next = scheduler.acquire(Priority::Normal);
+        assert!(poll_once(&mut abandoned).is_pending());
+        assert!(poll_once(&mut next).is_pending());
+
+        // Granted to `abandoned`, which is dropped before it runs
+        drop(guard);
+        drop(abandoned);
+        assert!(poll_once(&mut next).is_ready());
+    }
+}
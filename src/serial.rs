@@ -0,0 +1,393 @@
+//! # Native Serial Port (Linux)
+//!
+//! Opens the robot's USB serial adapter (`/dev/ttyUSB*`, `/dev/ttyACM*`) in
+//! raw mode at 1 Mbps, with the FTDI low-latency flag set so status packets
+//! are delivered as they arrive instead of after the driver's 16 ms latency
+//! timer.
+//!
+//! [`SimulatedPty`] serves a [`SimulatedBus`] on a pseudo-terminal, so the
+//! serial code path can be exercised without hardware:
+//!
+//! ```no_run
+//! use reachy_mini::serial::{SerialPort, SimulatedPty};
+//! use reachy_mini::simulator::SimulatedBus;
+//!
+//! let pty = SimulatedPty::spawn(SimulatedBus::new(&[11, 12, 13, 14, 15, 16, 17, 18]))?;
+//! let port = SerialPort::open(pty.path())?;
+//! # Ok::<(), reachy_mini::error::Error>(())
+//! ```
+
+use std::ffi::CStr;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::port::{complete_packets, Port};
+use crate::simulator::SimulatedBus;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Bus speed of the Reachy Mini motors
+const BAUD_RATE: libc::speed_t = libc::B1000000;
+
+/// `ASYNC_LOW_LATENCY` from `<linux/serial.h>`
+const ASYNC_LOW_LATENCY: libc::c_int = 1 << 13;
+
+/// Index of `flags` in `struct serial_struct`, viewed as an array of ints
+const SERIAL_STRUCT_FLAGS: usize = 4;
+
+/// Device name prefixes of USB serial adapters
+const PORT_PREFIXES: [&str; 2] = ["ttyUSB", "ttyACM"];
+
+/// How often the PTY simulator checks whether it should stop (ms)
+const PTY_POLL_MS: libc::c_int = 10;
+
+// ============================================================================
+// Discovery
+// ============================================================================
+
+/// USB serial adapters present in `/dev`, sorted by path.
+pub fn available_ports() -> Vec<PathBuf> {
+    let mut ports: Vec<PathBuf> = std::fs::read_dir("/dev")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    PORT_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+                })
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    ports.sort();
+    ports
+}
+
+// ============================================================================
+// Serial Port
+// ============================================================================
+
+/// A serial device configured for the Dynamixel bus.
+pub struct SerialPort {
+    file: File,
+    path: PathBuf,
+}
+
+impl SerialPort {
+    /// Open `path` at 1 Mbps, 8N1, raw mode, with low-latency delivery.
+    ///
+    /// # Errors
+    /// Returns an I/O error if the device cannot be opened or is not a
+    /// terminal. Failing to set the low-latency flag is not an error: PTYs
+    /// and some drivers do not support it.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(path)?;
+        let fd = file.as_raw_fd();
+
+        configure(fd)?;
+        set_low_latency(fd);
+        // O_NONBLOCK only kept open() from waiting for carrier detect
+        set_blocking(fd)?;
+        check(unsafe { libc::tcflush(fd, libc::TCIOFLUSH) })?;
+
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Device path this port was opened from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Port for SerialPort {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.file.write_all(data)?;
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let revents = match poll_readable(self.file.as_raw_fd(), timeout)? {
+            Some(revents) => revents,
+            None => return Ok(0),
+        };
+        if revents & libc::POLLIN == 0 {
+            return Err(
+                io::Error::new(io::ErrorKind::BrokenPipe, "serial device disconnected").into(),
+            );
+        }
+        match self.file.read(buf) {
+            Ok(n) => Ok(n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        check(unsafe { libc::tcflush(self.file.as_raw_fd(), libc::TCIFLUSH) })?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Simulated PTY
+// ============================================================================
+
+/// A [`SimulatedBus`] answering on the slave side of a pseudo-terminal.
+///
+/// A background thread reads instruction packets from the master side and
+/// writes the simulated motors' status packets back. Open [`path`](Self::path)
+/// with [`SerialPort::open`] like a real adapter.
+pub struct SimulatedPty {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<SimulatedBus>>,
+}
+
+impl SimulatedPty {
+    /// Create the pseudo-terminal and start serving `bus` on it.
+    pub fn spawn(bus: SimulatedBus) -> Result<Self> {
+        let master = open_pty_master()?;
+        let path = pty_slave_path(master.as_raw_fd())?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || serve_pty(master, bus, &stop))
+        };
+        Ok(Self {
+            path,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Path of the slave device, e.g. `/dev/pts/3`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop serving and hand back the simulated motors for inspection.
+    pub fn stop(mut self) -> SimulatedBus {
+        self.stop.store(true, Ordering::Relaxed);
+        let thread = self.thread.take().expect("PTY thread already joined");
+        thread.join().expect("PTY simulator thread panicked")
+    }
+}
+
+impl Drop for SimulatedPty {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Feed complete instruction packets from `master` to `bus` until `stop`.
+fn serve_pty(mut master: File, mut bus: SimulatedBus, stop: &AtomicBool) -> SimulatedBus {
+    let start = std::time::Instant::now();
+    let mut pending = Vec::new();
+    let mut buf = [0u8; 256];
+
+    while !stop.load(Ordering::Relaxed) {
+        let timeout = Duration::from_millis(PTY_POLL_MS as u64);
+        match poll_readable(master.as_raw_fd(), timeout) {
+            Ok(Some(revents)) if revents & libc::POLLIN != 0 => {}
+            // No slave opened yet, or it was closed: wait for the next one
+            Ok(Some(_)) => {
+                thread::sleep(timeout);
+                continue;
+            }
+            Ok(None) => continue,
+            Err(_) => break,
+        }
+        let n = match master.read(&mut buf) {
+            Ok(n) => n,
+            Err(_) => continue,
+        };
+        pending.extend_from_slice(&buf[..n]);
+
+        let (_, used) = complete_packets(&pending);
+        if used == 0 {
+            continue;
+        }
+        let now_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let response = bus.process(&pending[..used], now_ms);
+        pending.drain(..used);
+        if !response.is_empty() && master.write_all(&response).is_err() {
+            break;
+        }
+    }
+    bus
+}
+
+// ============================================================================
+// System Calls
+// ============================================================================
+
+/// Turn a `-1` return value into the last OS error.
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Raw 8N1 at [`BAUD_RATE`], no flow control, reads return immediately.
+fn configure(fd: RawFd) -> io::Result<()> {
+    let mut tty: libc::termios = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::tcgetattr(fd, &mut tty) })?;
+
+    unsafe { libc::cfmakeraw(&mut tty) };
+    tty.c_cflag |= libc::CLOCAL | libc::CREAD;
+    tty.c_cflag &= !(libc::CSTOPB | libc::CRTSCTS);
+    tty.c_cc[libc::VMIN] = 0;
+    tty.c_cc[libc::VTIME] = 0;
+    check(unsafe { libc::cfsetispeed(&mut tty, BAUD_RATE) })?;
+    check(unsafe { libc::cfsetospeed(&mut tty, BAUD_RATE) })?;
+
+    check(unsafe { libc::tcsetattr(fd, libc::TCSANOW, &tty) })?;
+    Ok(())
+}
+
+/// Best-effort `ASYNC_LOW_LATENCY` through `TIOCGSERIAL` / `TIOCSSERIAL`.
+fn set_low_latency(fd: RawFd) {
+    // Larger than `struct serial_struct` on every architecture
+    let mut serial = [0 as libc::c_int; 32];
+    unsafe {
+        if libc::ioctl(fd, libc::TIOCGSERIAL, serial.as_mut_ptr()) == 0 {
+            serial[SERIAL_STRUCT_FLAGS] |= ASYNC_LOW_LATENCY;
+            libc::ioctl(fd, libc::TIOCSSERIAL, serial.as_ptr());
+        }
+    }
+}
+
+/// Clear `O_NONBLOCK` so writes wait for room in the driver's buffer.
+fn set_blocking(fd: RawFd) -> io::Result<()> {
+    let flags = check(unsafe { libc::fcntl(fd, libc::F_GETFL) })?;
+    check(unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) })?;
+    Ok(())
+}
+
+/// Wait up to `timeout` for `fd` to become readable.
+///
+/// Returns the poll events, or `None` on timeout or interruption.
+fn poll_readable(fd: RawFd, timeout: Duration) -> io::Result<Option<libc::c_short>> {
+    let mut pfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // Round up so sub-millisecond waits still wait
+    let timeout_ms = timeout
+        .as_micros()
+        .div_ceil(1000)
+        .min(libc::c_int::MAX as u128);
+    let ready = unsafe { libc::poll(&mut pfd, 1, timeout_ms as libc::c_int) };
+    if ready < 0 {
+        let err = io::Error::last_os_error();
+        return if err.kind() == io::ErrorKind::Interrupted {
+            Ok(None)
+        } else {
+            Err(err)
+        };
+    }
+    Ok(if ready == 0 { None } else { Some(pfd.revents) })
+}
+
+/// Open a pseudo-terminal master in raw mode.
+fn open_pty_master() -> io::Result<File> {
+    let fd = check(unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) })?;
+    let master = unsafe { File::from_raw_fd(fd) };
+    check(unsafe { libc::grantpt(fd) })?;
+    check(unsafe { libc::unlockpt(fd) })?;
+
+    let mut tty: libc::termios = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::tcgetattr(fd, &mut tty) })?;
+    unsafe { libc::cfmakeraw(&mut tty) };
+    check(unsafe { libc::tcsetattr(fd, libc::TCSANOW, &tty) })?;
+    Ok(master)
+}
+
+/// Path of the slave side of the pseudo-terminal `master`.
+fn pty_slave_path(master: RawFd) -> io::Result<PathBuf> {
+    let mut name = [0 as libc::c_char; 128];
+    let ret = unsafe { libc::ptsname_r(master, name.as_mut_ptr(), name.len()) };
+    if ret != 0 {
+        return Err(io::Error::from_raw_os_error(ret));
+    }
+    let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+    Ok(PathBuf::from(name.to_string_lossy().into_owned()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{
+        build_ping_packet, build_sync_current_position, build_sync_write_position,
+        build_sync_write_torque, parse_position_packets, BROADCAST_ID,
+    };
+    use crate::port::DEFAULT_TIMEOUT;
+
+    const IDS: [u8; 3] = [11, 12, 13];
+
+    #[test]
+    fn test_pty_transact() {
+        let pty = SimulatedPty::spawn(SimulatedBus::new(&IDS)).unwrap();
+        let mut port = SerialPort::open(pty.path()).unwrap();
+
+        let response = port
+            .transact(&build_sync_current_position(&IDS), DEFAULT_TIMEOUT * 10)
+            .unwrap();
+        assert_eq!(
+            parse_position_packets(&response),
+            vec![(11, 2048), (12, 2048), (13, 2048)]
+        );
+
+        let response = port
+            .transact(&build_ping_packet(BROADCAST_ID), DEFAULT_TIMEOUT * 5)
+            .unwrap();
+        // Three 14-byte ping answers (model number + firmware version)
+        assert_eq!(response.len(), 3 * 14);
+    }
+
+    #[test]
+    fn test_pty_writes_reach_bus() {
+        let pty = SimulatedPty::spawn(SimulatedBus::new(&IDS)).unwrap();
+        {
+            let mut port = SerialPort::open(pty.path()).unwrap();
+            port.write(&build_sync_write_torque(&IDS, true)).unwrap();
+            port.write(&build_sync_write_position(&IDS, &[1000, 2000, 3000]))
+                .unwrap();
+            // Round trip so both writes are processed before stopping
+            port.transact(&build_ping_packet(11), DEFAULT_TIMEOUT * 10)
+                .unwrap();
+        }
+        let bus = pty.stop();
+        assert!(bus.motor(12).unwrap().torque_enabled());
+        assert_eq!(bus.motor(13).unwrap().goal_position(), 3000);
+    }
+}
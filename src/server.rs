@@ -0,0 +1,321 @@
+//! # Raw Bus WebSocket Server
+//!
+//! Serves the daemon's raw bus endpoint (`/api/move/ws/raw/write`) from any
+//! native [`Port`], so the browser API can `connect()` to a robot plugged
+//! into another machine, or to a simulated one.
+//!
+//! ## Protocol
+//!
+//! Each binary message from the client is written to the bus as is. If the
+//! instruction expects status packets, every answer collected before the
+//! timeout is sent back as one binary message, empty when no motor answered.
+//! Writes without answers (sync writes, broadcasts) get no reply.
+//!
+//! ## Access
+//!
+//! - Only one client is served at a time; others are refused with `409`
+//! - With a token, clients must pass `?token=...` (browsers cannot set
+//!   headers on WebSockets) or `Authorization: Bearer ...`, else `401`
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+
+use crate::error::Result;
+use crate::port::{expected_packets, Port, DEFAULT_TIMEOUT};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Path of the raw bus endpoint, as served by the daemon
+pub const RAW_WRITE_PATH: &str = "/api/move/ws/raw/write";
+
+/// Default bind address, the daemon's port on the local machine
+pub const DEFAULT_BIND: &str = "127.0.0.1:8000";
+
+// ============================================================================
+// Options
+// ============================================================================
+
+/// How a [`Server`] accepts clients and talks to the bus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerOptions {
+    /// Shared secret clients must present; anyone may connect if `None`
+    pub token: Option<String>,
+    /// Time to wait for status packets
+    pub timeout: Duration,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            token: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+/// A listening raw bus endpoint.
+pub struct Server {
+    listener: TcpListener,
+    options: ServerOptions,
+    /// Set while a client is connected
+    busy: Arc<AtomicBool>,
+}
+
+impl Server {
+    /// Listen on `addr`, e.g. `"0.0.0.0:8000"` to accept LAN clients.
+    pub fn bind<A: ToSocketAddrs>(addr: A, options: ServerOptions) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            options,
+            busy: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Address actually bound (useful when binding port 0).
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept clients forever, forwarding their packets to `port`.
+    ///
+    /// Each connection runs on its own thread so refused clients get an
+    /// answer while another one is being served.
+    pub fn serve<P: Port + Send + 'static>(self, port: P) -> Result<()> {
+        let port = Arc::new(Mutex::new(port));
+        let options = Arc::new(self.options);
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let port = port.clone();
+            let options = options.clone();
+            let busy = self.busy.clone();
+            thread::spawn(move || handle_client(stream, &port, &options, &busy));
+        }
+        Ok(())
+    }
+}
+
+/// Run the handshake, then forward messages until the client leaves.
+// The handshake callback must return tungstenite's (large) `ErrorResponse`
+#[allow(clippy::result_large_err)]
+fn handle_client<P: Port>(
+    stream: TcpStream,
+    port: &Mutex<P>,
+    options: &ServerOptions,
+    busy: &AtomicBool,
+) {
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_| "unknown".to_string(), |addr| addr.to_string());
+    let _ = stream.set_nodelay(true);
+
+    let mut acquired = false;
+    let callback = |request: &Request, response: Response| {
+        if let Err((status, reason)) = check_request(request, options.token.as_deref()) {
+            return Err(reject(status, reason));
+        }
+        if busy
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(reject(StatusCode::CONFLICT, "Another client is connected"));
+        }
+        acquired = true;
+        Ok(response)
+    };
+
+    match tungstenite::accept_hdr(stream, callback) {
+        Ok(mut socket) => {
+            eprintln!("Client {} connected", peer);
+            if let Err(e) = forward(&mut socket, port, options.timeout) {
+                eprintln!("Client {}: {}", peer, e);
+            }
+            eprintln!("Client {} disconnected", peer);
+        }
+        Err(e) => eprintln!("Refused {}: {}", peer, e),
+    }
+    if acquired {
+        busy.store(false, Ordering::Release);
+    }
+}
+
+/// Check the endpoint path and token of a handshake request.
+///
+/// # Errors
+/// Returns the HTTP status and reason to refuse the request with.
+fn check_request(
+    request: &Request,
+    token: Option<&str>,
+) -> std::result::Result<(), (StatusCode, &'static str)> {
+    if request.uri().path() != RAW_WRITE_PATH {
+        return Err((StatusCode::NOT_FOUND, "Unknown endpoint"));
+    }
+    let token = match token {
+        Some(token) => token,
+        None => return Ok(()),
+    };
+
+    let from_query = request.uri().query().into_iter().flat_map(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.strip_prefix("token="))
+    });
+    let from_header = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if from_query.chain(from_header).any(|given| given == token) {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "Invalid or missing token"))
+    }
+}
+
+fn reject(status: StatusCode, reason: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(reason.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+/// Write each binary message to the bus and reply with the status packets.
+fn forward<P: Port>(
+    socket: &mut WebSocket<TcpStream>,
+    port: &Mutex<P>,
+    timeout: Duration,
+) -> Result<()> {
+    loop {
+        let request = match socket.read() {
+            Ok(Message::Binary(data)) => data,
+            Ok(Message::Close(_)) => return Ok(()),
+            // Pings are answered by tungstenite, text is not bus traffic
+            Ok(_) => continue,
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(io::Error::other(e).into()),
+        };
+
+        let mut port = port.lock().unwrap_or_else(|e| e.into_inner());
+        if expected_packets(&request) == Some(0) {
+            port.write(&request)?;
+            continue;
+        }
+        let response = port.transact(&request, timeout)?;
+        drop(port);
+        socket
+            .send(Message::Binary(response))
+            .map_err(io::Error::other)?;
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{
+        build_sync_current_position, build_sync_write_torque, parse_position_packets,
+    };
+    use crate::port::SimulatedPort;
+    use crate::simulator::SimulatedBus;
+
+    /// Serve a simulated bus on a free local port.
+    fn spawn(token: Option<&str>) -> SocketAddr {
+        let options = ServerOptions {
+            token: token.map(String::from),
+            ..ServerOptions::default()
+        };
+        let server = Server::bind("127.0.0.1:0", options).unwrap();
+        let addr = server.local_addr().unwrap();
+        let port = SimulatedPort::new(SimulatedBus::new(&[11, 12]));
+        thread::spawn(move || server.serve(port));
+        addr
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn connect(addr: SocketAddr, query: &str) -> tungstenite::Result<WebSocket<TcpStream>> {
+        let url = format!("ws://{}{}{}", addr, RAW_WRITE_PATH, query);
+        let stream = TcpStream::connect(addr).unwrap();
+        tungstenite::client(url, stream)
+            .map(|(socket, _)| socket)
+            .map_err(|e| match e {
+                tungstenite::HandshakeError::Failure(e) => e,
+                tungstenite::HandshakeError::Interrupted(_) => unreachable!(),
+            })
+    }
+
+    fn status_of(result: tungstenite::Result<WebSocket<TcpStream>>) -> u16 {
+        match result {
+            Err(tungstenite::Error::Http(response)) => response.status().as_u16(),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => 101,
+        }
+    }
+
+    #[test]
+    fn test_forwards_packets() {
+        let addr = spawn(None);
+        let mut socket = connect(addr, "").unwrap();
+
+        let torque = build_sync_write_torque(&[11, 12], true);
+        socket.send(Message::Binary(torque)).unwrap();
+        let read = build_sync_current_position(&[11, 12]);
+        socket.send(Message::Binary(read)).unwrap();
+
+        // The sync write gets no reply, so the first message answers the read
+        match socket.read().unwrap() {
+            Message::Binary(data) => {
+                assert_eq!(parse_position_packets(&data), vec![(11, 2048), (12, 2048)])
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_token_required() {
+        let addr = spawn(Some("s3cret"));
+        assert_eq!(status_of(connect(addr, "")), 401);
+        assert_eq!(status_of(connect(addr, "?token=wrong")), 401);
+        assert_eq!(status_of(connect(addr, "?token=s3cret")), 101);
+    }
+
+    #[test]
+    fn test_single_client() {
+        let addr = spawn(None);
+        let first = connect(addr, "").unwrap();
+        assert_eq!(status_of(connect(addr, "")), 409);
+
+        drop(first);
+        // The slot is released once the server notices the disconnect
+        let mut status = 0;
+        for _ in 0..50 {
+            status = status_of(connect(addr, ""));
+            if status == 101 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(status, 101);
+    }
+}
@@ -0,0 +1,612 @@
+//! # Simulated Motor Bus
+//!
+//! An in-process model of the Reachy Mini Dynamixel bus: eight XL330 motors
+//! answering Protocol 2.0 instruction packets with CRC-correct status packets.
+//!
+//! ## Model
+//!
+//! - Each motor owns a byte-addressed control table (see [`crate::dynamixel`])
+//! - PING, READ, WRITE, REBOOT, SYNC_READ and SYNC_WRITE are supported
+//! - With torque enabled, the present position tracks the goal as a first-order
+//!   system whose speed is capped by Profile Velocity (0 = maximum speed)
+//! - Present velocity, load and the moving flag follow the tracking error
+//!
+//! ## Fault Injection
+//!
+//! - [`SimulatedMotor::set_connected`] unplugs a motor from the bus
+//! - [`SimulatedMotor::raise_hardware_error`] latches overheating, overload...
+//!   until the motor is rebooted
+//! - [`SimulatedBus::set_crc_error_rate`] corrupts a fraction of status packets
+//!
+//! The simulator is driven by explicit timestamps so it behaves identically in
+//! the browser and in unit tests.
+
+use crate::dynamixel::{
+    address, build_status_packet, hardware_error, instruction, parse_instruction_packet,
+    status_error, ParseError, BROADCAST_ID,
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Motor IDs of a Reachy Mini Lite (6 head motors + 2 antennas)
+#[cfg(test)]
+const REACHY_MINI_IDS: [u8; 8] = [11, 12, 13, 14, 15, 16, 17, 18];
+
+/// Size of the simulated control table in bytes
+const CONTROL_TABLE_SIZE: usize = 148;
+
+/// First address of the RAM area (EEPROM below is locked while torque is on)
+const RAM_START: u16 = address::TORQUE_ENABLE;
+
+/// First read-only RAM address (Realtime Tick and everything after)
+const RAM_READ_ONLY_START: u16 = 120;
+
+/// XL330-M288 model number
+const MODEL_NUMBER: u16 = 1200;
+
+/// Reported firmware version
+const FIRMWARE_VERSION: u8 = 52;
+
+/// Tracking time constant in seconds
+const TRACKING_TIME_CONSTANT_S: f32 = 0.05;
+
+/// No-load speed at 5 V in ticks per second (~100 rpm)
+const MAX_SPEED_TICKS_PER_S: f32 = 7000.0;
+
+/// Profile Velocity unit in rpm
+const VELOCITY_UNIT_RPM: f32 = 0.229;
+
+/// Load reported per tick of tracking error (load unit is 0.1 %)
+const LOAD_PER_TICK: f32 = 5.0;
+
+/// Idle temperature in °C
+const AMBIENT_TEMPERATURE: u8 = 32;
+
+/// Supply voltage in 0.1 V
+const INPUT_VOLTAGE: u16 = 50;
+
+/// Factory Return Delay Time (2 µs units, i.e. 500 µs)
+const RETURN_DELAY_TIME: u8 = 250;
+
+/// Temperature reported by an overheated motor in °C (limit is 70)
+const OVERHEATED_TEMPERATURE: u8 = 75;
+
+// ============================================================================
+// Simulated Motor
+// ============================================================================
+
+/// A single simulated XL330 motor.
+#[derive(Clone)]
+pub struct SimulatedMotor {
+    table: [u8; CONTROL_TABLE_SIZE],
+    /// Continuous position in ticks (the table stores the rounded value)
+    position: f32,
+    /// Whether the motor is plugged into the bus
+    connected: bool,
+}
+
+impl SimulatedMotor {
+    /// Create a motor at the center position (2048 = 0 rad) with torque off.
+    pub fn new(id: u8) -> Self {
+        let mut motor = Self {
+            table: [0; CONTROL_TABLE_SIZE],
+            position: 2048.0,
+            connected: true,
+        };
+        motor.reset(id);
+        motor
+    }
+
+    /// Restore power-on defaults while keeping the physical position.
+    fn reset(&mut self, id: u8) {
+        self.table = [0; CONTROL_TABLE_SIZE];
+        self.set_u16(address::MODEL_NUMBER, MODEL_NUMBER);
+        self.table[address::FIRMWARE_VERSION as usize] = FIRMWARE_VERSION;
+        self.table[address::ID as usize] = id;
+        self.table[address::RETURN_DELAY_TIME as usize] = RETURN_DELAY_TIME;
+        self.set_i32(address::GOAL_POSITION, self.position.round() as i32);
+        self.set_u16(address::PRESENT_INPUT_VOLTAGE, INPUT_VOLTAGE);
+        self.table[address::PRESENT_TEMPERATURE as usize] = AMBIENT_TEMPERATURE;
+        self.sync_present_state(0.0);
+    }
+
+    /// Motor ID as stored in the control table.
+    pub fn id(&self) -> u8 {
+        self.table[address::ID as usize]
+    }
+
+    /// Whether torque is enabled.
+    pub fn torque_enabled(&self) -> bool {
+        self.table[address::TORQUE_ENABLE as usize] != 0
+    }
+
+    /// Goal position in raw ticks.
+    pub fn goal_position(&self) -> i32 {
+        self.get_i32(address::GOAL_POSITION)
+    }
+
+    /// Delay between receiving an instruction and answering it, in µs.
+    pub fn return_delay_us(&self) -> u32 {
+        self.table[address::RETURN_DELAY_TIME as usize] as u32 * 2
+    }
+
+    /// Whether the motor is plugged into the bus.
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Unplug (or plug back) the motor: a disconnected motor neither answers
+    /// nor obeys instructions, but keeps its state.
+    pub fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+    }
+
+    /// Latch `bits` (see [`hardware_error`]) in Hardware Error Status, as the
+    /// firmware does on a fault: torque is switched off and status packets
+    /// carry the alert bit until the motor is rebooted.
+    pub fn raise_hardware_error(&mut self, bits: u8) {
+        self.table[address::HARDWARE_ERROR_STATUS as usize] |= bits;
+        self.table[address::TORQUE_ENABLE as usize] = 0;
+        if bits & hardware_error::OVERHEATING != 0 {
+            self.table[address::PRESENT_TEMPERATURE as usize] = OVERHEATED_TEMPERATURE;
+        }
+        self.sync_present_state(0.0);
+    }
+
+    /// Read `len` bytes at `addr`, or `None` if out of range.
+    pub fn read(&self, addr: u16, len: u16) -> Option<&[u8]> {
+        let start = addr as usize;
+        let end = start + len as usize;
+        if len == 0 || end > CONTROL_TABLE_SIZE {
+            return None;
+        }
+        Some(&self.table[start..end])
+    }
+
+    /// Write `data` at `addr`, enforcing access rules.
+    ///
+    /// Returns the status error code (0 on success).
+    pub fn write(&mut self, addr: u16, data: &[u8]) -> u8 {
+        let start = addr as usize;
+        let end = start + data.len();
+        if data.is_empty() || end > CONTROL_TABLE_SIZE {
+            return status_error::DATA_LENGTH;
+        }
+
+        let writable = if addr < RAM_START {
+            // EEPROM: identity fields are read-only, the rest is locked by torque
+            addr >= address::ID && !self.torque_enabled()
+        } else {
+            end <= RAM_READ_ONLY_START as usize
+        };
+        if !writable {
+            return status_error::ACCESS;
+        }
+
+        self.table[start..end].copy_from_slice(data);
+        0
+    }
+
+    /// Advance the motor dynamics by `dt` seconds.
+    pub fn step(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+        if !self.torque_enabled() {
+            self.sync_present_state(0.0);
+            return;
+        }
+
+        let goal = self.goal_position() as f32;
+        let error = goal - self.position;
+
+        let profile_velocity = self.get_i32(address::PROFILE_VELOCITY);
+        let max_speed = if profile_velocity > 0 {
+            (profile_velocity as f32 * VELOCITY_UNIT_RPM / 60.0 * 4096.0).min(MAX_SPEED_TICKS_PER_S)
+        } else {
+            MAX_SPEED_TICKS_PER_S
+        };
+
+        let max_step = max_speed * dt;
+        let step =
+            (error * (1.0 - (-dt / TRACKING_TIME_CONSTANT_S).exp())).clamp(-max_step, max_step);
+        self.position += step;
+
+        self.sync_present_state(step / dt);
+    }
+
+    /// Mirror the continuous state into the Present* registers.
+    fn sync_present_state(&mut self, velocity_ticks_per_s: f32) {
+        let position = self.position.round() as i32;
+        self.set_i32(address::PRESENT_POSITION, position);
+
+        let velocity_rpm = velocity_ticks_per_s / 4096.0 * 60.0;
+        self.set_i32(
+            address::PRESENT_VELOCITY,
+            (velocity_rpm / VELOCITY_UNIT_RPM).round() as i32,
+        );
+
+        let load = if self.torque_enabled() {
+            ((self.goal_position() - position) as f32 * LOAD_PER_TICK).clamp(-1000.0, 1000.0)
+        } else {
+            0.0
+        };
+        self.set_u16(address::PRESENT_LOAD, load as i16 as u16);
+
+        let moving = self.torque_enabled() && (self.goal_position() - position).abs() > 1;
+        self.table[address::MOVING as usize] = moving as u8;
+    }
+
+    /// Error byte for status packets (alert bit if a hardware error is latched).
+    fn status_error(&self) -> u8 {
+        if self.table[address::HARDWARE_ERROR_STATUS as usize] != 0 {
+            status_error::ALERT
+        } else {
+            0
+        }
+    }
+
+    fn get_i32(&self, addr: u16) -> i32 {
+        let a = addr as usize;
+        i32::from_le_bytes([
+            self.table[a],
+            self.table[a + 1],
+            self.table[a + 2],
+            self.table[a + 3],
+        ])
+    }
+
+    fn set_i32(&mut self, addr: u16, value: i32) {
+        let a = addr as usize;
+        self.table[a..a + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn set_u16(&mut self, addr: u16, value: u16) {
+        let a = addr as usize;
+        self.table[a..a + 2].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+// ============================================================================
+// Simulated Bus
+// ============================================================================
+
+/// A simulated Dynamixel bus answering instruction packets.
+pub struct SimulatedBus {
+    motors: Vec<SimulatedMotor>,
+    last_update_ms: Option<f64>,
+    /// Fraction of status packets sent with a wrong CRC
+    crc_error_rate: f32,
+    /// Xorshift state deciding which packets get corrupted
+    noise: u32,
+}
+
+impl SimulatedBus {
+    /// Create a bus with one motor per ID.
+    pub fn new(motor_ids: &[u8]) -> Self {
+        Self {
+            motors: motor_ids
+                .iter()
+                .map(|&id| SimulatedMotor::new(id))
+                .collect(),
+            last_update_ms: None,
+            crc_error_rate: 0.0,
+            noise: 0x2545_F491,
+        }
+    }
+
+    /// Create the eight-motor Reachy Mini bus (IDs 11-18).
+    #[cfg(test)]
+    pub fn reachy_mini() -> Self {
+        Self::new(&REACHY_MINI_IDS)
+    }
+
+    /// Look up a motor by ID.
+    pub fn motor(&self, id: u8) -> Option<&SimulatedMotor> {
+        self.motors.iter().find(|m| m.id() == id)
+    }
+
+    /// Look up a motor by ID for modification.
+    pub fn motor_mut(&mut self, id: u8) -> Option<&mut SimulatedMotor> {
+        self.motors.iter_mut().find(|m| m.id() == id)
+    }
+
+    /// Corrupt the CRC of this fraction (0 to 1) of status packets, as line
+    /// noise would. The sequence is deterministic for a given bus.
+    pub fn set_crc_error_rate(&mut self, rate: f32) {
+        self.crc_error_rate = rate.clamp(0.0, 1.0);
+    }
+
+    /// A motor plugged into the bus, i.e. one that hears instructions.
+    fn online(&self, id: u8) -> Option<&SimulatedMotor> {
+        self.motor(id).filter(|m| m.connected)
+    }
+
+    fn online_mut(&mut self, id: u8) -> Option<&mut SimulatedMotor> {
+        self.motor_mut(id).filter(|m| m.connected)
+    }
+
+    /// Advance every motor to the timestamp `now_ms` (milliseconds).
+    pub fn advance_to(&mut self, now_ms: f64) {
+        let dt = match self.last_update_ms {
+            Some(last) if now_ms > last => ((now_ms - last) / 1000.0) as f32,
+            Some(_) => return,
+            None => 0.0,
+        };
+        self.last_update_ms = Some(now_ms);
+        for motor in &mut self.motors {
+            motor.step(dt);
+        }
+    }
+
+    /// Process every instruction packet in `data` at time `now_ms`.
+    ///
+    /// Returns the concatenated status packets the motors would send back.
+    pub fn process(&mut self, data: &[u8], now_ms: f64) -> Vec<u8> {
+        self.advance_to(now_ms);
+
+        let mut response = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            match parse_instruction_packet(&data[offset..]) {
+                Ok((packet, used)) => {
+                    self.handle(packet.id, packet.instruction, &packet.params, &mut response);
+                    offset += used;
+                }
+                Err(ParseError::InvalidCrc) => {
+                    // The addressed motor reports the CRC error
+                    let id = data[offset + 4];
+                    if self.online(id).is_some() {
+                        response.extend(build_status_packet(id, status_error::CRC, &[]));
+                    }
+                    let length = u16::from_le_bytes([data[offset + 5], data[offset + 6]]);
+                    offset += 7 + length as usize;
+                }
+                // Noise or truncated packet: resynchronize on the next header
+                Err(_) => offset += 1,
+            }
+        }
+        self.add_noise(&mut response);
+        response
+    }
+
+    /// Flip a CRC byte in some of the status packets of `response`.
+    fn add_noise(&mut self, response: &mut [u8]) {
+        if self.crc_error_rate <= 0.0 {
+            return;
+        }
+        let mut offset = 0;
+        while offset + 7 <= response.len() {
+            let length = u16::from_le_bytes([response[offset + 5], response[offset + 6]]) as usize;
+            offset += 7 + length;
+
+            self.noise ^= self.noise << 13;
+            self.noise ^= self.noise >> 17;
+            self.noise ^= self.noise << 5;
+            if (self.noise as f32 / u32::MAX as f32) < self.crc_error_rate {
+                response[offset - 1] ^= 0xFF;
+            }
+        }
+    }
+
+    fn handle(&mut self, id: u8, instr: u8, params: &[u8], response: &mut Vec<u8>) {
+        match instr {
+            instruction::PING => {
+                let [lo, hi] = MODEL_NUMBER.to_le_bytes();
+                for motor in self
+                    .motors
+                    .iter()
+                    .filter(|m| m.connected && (id == BROADCAST_ID || m.id() == id))
+                {
+                    response.extend(build_status_packet(
+                        motor.id(),
+                        motor.status_error(),
+                        &[lo, hi, FIRMWARE_VERSION],
+                    ));
+                }
+            }
+            instruction::READ if params.len() == 4 => {
+                let addr = u16::from_le_bytes([params[0], params[1]]);
+                let len = u16::from_le_bytes([params[2], params[3]]);
+                if let Some(motor) = self.online(id) {
+                    response.extend(match motor.read(addr, len) {
+                        Some(data) => build_status_packet(id, motor.status_error(), data),
+                        None => build_status_packet(id, status_error::DATA_LENGTH, &[]),
+                    });
+                }
+            }
+            instruction::WRITE if params.len() > 2 => {
+                let addr = u16::from_le_bytes([params[0], params[1]]);
+                if let Some(motor) = self.online_mut(id) {
+                    let error = motor.write(addr, &params[2..]);
+                    response.extend(build_status_packet(id, error | motor.status_error(), &[]));
+                }
+            }
+            instruction::REBOOT => {
+                if let Some(motor) = self.online_mut(id) {
+                    motor.reset(id);
+                    response.extend(build_status_packet(id, 0, &[]));
+                }
+            }
+            instruction::SYNC_READ if params.len() > 4 => {
+                let addr = u16::from_le_bytes([params[0], params[1]]);
+                let len = u16::from_le_bytes([params[2], params[3]]);
+                for &motor_id in &params[4..] {
+                    if let Some(motor) = self.online(motor_id) {
+                        if let Some(data) = motor.read(addr, len) {
+                            response.extend(build_status_packet(
+                                motor_id,
+                                motor.status_error(),
+                                data,
+                            ));
+                        }
+                    }
+                }
+            }
+            instruction::SYNC_WRITE if params.len() > 4 => {
+                let addr = u16::from_le_bytes([params[0], params[1]]);
+                let len = u16::from_le_bytes([params[2], params[3]]) as usize;
+                for chunk in params[4..].chunks_exact(len + 1) {
+                    if let Some(motor) = self.online_mut(chunk[0]) {
+                        motor.write(addr, &chunk[1..]);
+                    }
+                }
+                // Motors never answer a SYNC_WRITE
+            }
+            _ => {
+                if self.online(id).is_some() {
+                    response.extend(build_status_packet(id, status_error::INSTRUCTION, &[]));
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{
+        build_ping_packet, build_read_packet, build_reboot_packet, build_sync_current_position,
+        build_sync_write_position, build_sync_write_torque, parse_1byte_packets,
+        parse_position_packets, parse_status_packets,
+    };
+
+    #[test]
+    fn test_broadcast_ping_answers_every_motor() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let response = bus.process(&build_ping_packet(BROADCAST_ID), 0.0);
+
+        let mut offset = 0;
+        let mut ids = Vec::new();
+        while offset < response.len() {
+            // Status packets share the instruction packet framing and CRC
+            let (status, used) = parse_instruction_packet(&response[offset..]).unwrap();
+            assert_eq!(status.instruction, instruction::STATUS);
+            assert_eq!(status.params, vec![0, 0xB0, 0x04, FIRMWARE_VERSION]);
+            ids.push(status.id);
+            offset += used;
+        }
+        assert_eq!(ids, REACHY_MINI_IDS.to_vec());
+    }
+
+    #[test]
+    fn test_read_temperature() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let response = bus.process(&build_read_packet(17, address::PRESENT_TEMPERATURE, 1), 0.0);
+        assert_eq!(
+            parse_1byte_packets(&response),
+            vec![(17, AMBIENT_TEMPERATURE)]
+        );
+    }
+
+    #[test]
+    fn test_goal_tracking_requires_torque() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let goal = build_sync_write_position(&[11, 12], &[2548, 1548]);
+        assert!(bus.process(&goal, 0.0).is_empty());
+
+        // Torque off: the motors stay where they are
+        bus.advance_to(1000.0);
+        assert_eq!(
+            bus.motor(11).unwrap().get_i32(address::PRESENT_POSITION),
+            2048
+        );
+
+        bus.process(&build_sync_write_torque(&[11, 12], true), 1000.0);
+        bus.advance_to(2000.0);
+
+        let response = bus.process(&build_sync_current_position(&[11, 12]), 2000.0);
+        assert_eq!(
+            parse_position_packets(&response),
+            vec![(11, 2548), (12, 1548)]
+        );
+    }
+
+    #[test]
+    fn test_profile_velocity_limits_speed() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let motor = bus.motor_mut(11).unwrap();
+        // 10 × 0.229 rpm ≈ 156 ticks/s
+        motor.write(address::PROFILE_VELOCITY, &10i32.to_le_bytes());
+        motor.write(address::TORQUE_ENABLE, &[1]);
+        motor.write(address::GOAL_POSITION, &3048i32.to_le_bytes());
+
+        bus.advance_to(0.0);
+        bus.advance_to(1000.0);
+        let moved = bus.motor(11).unwrap().get_i32(address::PRESENT_POSITION) - 2048;
+        assert!((150..=160).contains(&moved), "moved {} ticks", moved);
+    }
+
+    #[test]
+    fn test_eeprom_locked_while_torque_enabled() {
+        let mut motor = SimulatedMotor::new(11);
+        assert_eq!(
+            motor.write(address::MODEL_NUMBER, &[0, 0]),
+            status_error::ACCESS
+        );
+        motor.write(address::TORQUE_ENABLE, &[1]);
+        assert_eq!(motor.write(address::ID, &[20]), status_error::ACCESS);
+        assert_eq!(motor.id(), 11);
+    }
+
+    #[test]
+    fn test_disconnected_motor_is_silent() {
+        let mut bus = SimulatedBus::reachy_mini();
+        bus.motor_mut(12).unwrap().set_connected(false);
+
+        let response = bus.process(&build_sync_current_position(&[11, 12, 13]), 0.0);
+        assert_eq!(
+            parse_position_packets(&response),
+            vec![(11, 2048), (13, 2048)]
+        );
+        bus.process(&build_sync_write_torque(&[12], true), 0.0);
+        assert!(!bus.motor(12).unwrap().torque_enabled());
+    }
+
+    #[test]
+    fn test_hardware_error_until_reboot() {
+        let mut bus = SimulatedBus::reachy_mini();
+        bus.process(&build_sync_write_torque(&[17], true), 0.0);
+        bus.motor_mut(17)
+            .unwrap()
+            .raise_hardware_error(hardware_error::OVERHEATING);
+
+        let motor = bus.motor(17).unwrap();
+        assert!(!motor.torque_enabled());
+        let response = bus.process(&build_read_packet(17, address::PRESENT_TEMPERATURE, 1), 0.0);
+        let (status, _) = parse_instruction_packet(&response).unwrap();
+        assert_eq!(
+            status.params,
+            vec![status_error::ALERT, OVERHEATED_TEMPERATURE]
+        );
+
+        bus.process(&build_reboot_packet(17), 0.0);
+        let response = bus.process(
+            &build_read_packet(17, address::HARDWARE_ERROR_STATUS, 1),
+            0.0,
+        );
+        assert_eq!(parse_1byte_packets(&response), vec![(17, 0)]);
+    }
+
+    #[test]
+    fn test_crc_error_rate() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let request = build_sync_current_position(&REACHY_MINI_IDS);
+        bus.set_crc_error_rate(1.0);
+        let response = bus.process(&request, 0.0);
+        assert_eq!(response.len(), 8 * 15);
+        assert!(parse_status_packets(&response).is_empty());
+
+        bus.set_crc_error_rate(0.25);
+        let corrupted: usize = (0..100)
+            .map(|_| 8 - parse_status_packets(&bus.process(&request, 0.0)).len())
+            .sum();
+        assert!((100..300).contains(&corrupted), "{} corrupted", corrupted);
+    }
+}
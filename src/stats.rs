@@ -0,0 +1,405 @@
+//! # Connection Statistics
+//!
+//! Bus health counters kept by every `GenericPort`: round-trip latency, traffic
+//! volume, CRC failures, timeouts, retries, motors that did not answer and the
+//! rate achieved by streaming loops. A degrading cable shows up here as rising
+//! latency, CRC failures and missing-motor counts well before control fails.
+//!
+//! ## JSON Snapshot
+//!
+//! ```json
+//! {
+//!   "elapsed_ms": 5012.0,
+//!   "transactions": 480,
+//!   "bytes_tx": 8640,
+//!   "bytes_rx": 57600,
+//!   "crc_failures": 1,
+//!   "timeouts": 0,
+//!   "retries": 0,
+//!   "missing_motors": { "17": 3 },
+//!   "latency_ms": {
+//!     "min": 10.0, "max": 14.0, "mean": 11.2,
+//!     "buckets": [ { "le": 5.0, "count": 0 }, ..., { "le": null, "count": 0 } ]
+//!   },
+//!   "loop_rate_hz": 47.8
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use serde_json::{json, Value};
+
+use crate::dynamixel::{expected_responders, instruction, parse_instruction_packet, ParseError};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Upper bounds (inclusive, ms) of the latency histogram buckets.
+/// A final overflow bucket collects everything slower.
+pub const LATENCY_BUCKETS_MS: [f64; 7] = [5.0, 10.0, 15.0, 20.0, 50.0, 100.0, 250.0];
+
+/// Smoothing factor of the loop rate moving average
+const LOOP_RATE_ALPHA: f64 = 0.1;
+
+// ============================================================================
+// Latency Histogram
+// ============================================================================
+
+/// Histogram of transaction round-trip times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHistogram {
+    /// One count per bucket of [`LATENCY_BUCKETS_MS`], plus the overflow bucket
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    min_ms: f64,
+    max_ms: f64,
+    sum_ms: f64,
+    samples: u64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            counts: [0; LATENCY_BUCKETS_MS.len() + 1],
+            min_ms: f64::INFINITY,
+            max_ms: 0.0,
+            sum_ms: 0.0,
+            samples: 0,
+        }
+    }
+
+    /// Add one round-trip time.
+    pub fn record(&mut self, latency_ms: f64) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| latency_ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.min_ms = self.min_ms.min(latency_ms);
+        self.max_ms = self.max_ms.max(latency_ms);
+        self.sum_ms += latency_ms;
+        self.samples += 1;
+    }
+
+    /// Mean round-trip time, or 0 if nothing was recorded.
+    pub fn mean_ms(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.sum_ms / self.samples as f64
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let buckets: Vec<Value> = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| json!({ "le": LATENCY_BUCKETS_MS.get(i), "count": count }))
+            .collect();
+        json!({
+            "min": if self.samples == 0 { 0.0 } else { self.min_ms },
+            "max": self.max_ms,
+            "mean": self.mean_ms(),
+            "buckets": buckets,
+        })
+    }
+}
+
+// ============================================================================
+// Connection Statistics
+// ============================================================================
+
+/// Bus health counters since the connection was opened or last reset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionStats {
+    /// Wall-clock time (ms) the counters started from
+    start_ms: f64,
+    pub transactions: u64,
+    pub bytes_tx: u64,
+    pub bytes_rx: u64,
+    pub crc_failures: u64,
+    pub timeouts: u64,
+    pub retries: u64,
+    /// Number of expected status packets that never arrived, by motor ID
+    pub missing_motors: BTreeMap<u8, u64>,
+    pub latency: LatencyHistogram,
+    /// Smoothed iteration rate of streaming loops (Hz)
+    pub loop_rate_hz: f64,
+    last_loop_ms: Option<f64>,
+}
+
+impl ConnectionStats {
+    /// Start empty counters at wall-clock time `now_ms`.
+    pub fn new(now_ms: f64) -> Self {
+        Self {
+            start_ms: now_ms,
+            transactions: 0,
+            bytes_tx: 0,
+            bytes_rx: 0,
+            crc_failures: 0,
+            timeouts: 0,
+            retries: 0,
+            missing_motors: BTreeMap::new(),
+            latency: LatencyHistogram::new(),
+            loop_rate_hz: 0.0,
+            last_loop_ms: None,
+        }
+    }
+
+    /// Count bytes written to the bus.
+    pub fn record_tx(&mut self, len: usize) {
+        self.bytes_tx += len as u64;
+    }
+
+    /// Count bytes read from the bus.
+    pub fn record_rx(&mut self, len: usize) {
+        self.bytes_rx += len as u64;
+    }
+
+    /// Account for a completed request/response transaction.
+    ///
+    /// Checks the CRC of every status packet in `response` and compares the
+    /// motors that answered with the ones `request` addressed.
+    ///
+    /// # Returns
+    /// `true` if a response was expected but nothing arrived (a timeout).
+    pub fn record_transaction(&mut self, request: &[u8], response: &[u8], latency_ms: f64) -> bool {
+        self.transactions += 1;
+        self.latency.record(latency_ms);
+
+        let mut responders = Vec::new();
+        for offset in header_offsets(response) {
+            match parse_instruction_packet(&response[offset..]) {
+                Ok((packet, _)) if packet.instruction == instruction::STATUS => {
+                    responders.push(packet.id)
+                }
+                Err(ParseError::InvalidCrc) => self.crc_failures += 1,
+                _ => {}
+            }
+        }
+
+        let expected = expected_responders(request);
+        for id in expected.iter().filter(|id| !responders.contains(id)) {
+            *self.missing_motors.entry(*id).or_insert(0) += 1;
+        }
+
+        let timed_out = !expected.is_empty() && response.is_empty();
+        if timed_out {
+            self.timeouts += 1;
+        }
+        timed_out
+    }
+
+    /// Count a transaction that had to be resent.
+    pub fn record_retry(&mut self) {
+        self.retries += 1;
+    }
+
+    /// Mark one iteration of a streaming loop at wall-clock time `now_ms`.
+    pub fn record_loop(&mut self, now_ms: f64) {
+        if let Some(last) = self.last_loop_ms {
+            let dt = now_ms - last;
+            if dt > 0.0 {
+                let rate = 1000.0 / dt;
+                self.loop_rate_hz = if self.loop_rate_hz == 0.0 {
+                    rate
+                } else {
+                    self.loop_rate_hz + LOOP_RATE_ALPHA * (rate - self.loop_rate_hz)
+                };
+            }
+        }
+        self.last_loop_ms = Some(now_ms);
+    }
+
+    /// Serialize a snapshot, with `elapsed_ms` measured up to `now_ms`.
+    pub fn to_json(&self, now_ms: f64) -> String {
+        let missing: BTreeMap<String, u64> = self
+            .missing_motors
+            .iter()
+            .map(|(id, count)| (id.to_string(), *count))
+            .collect();
+        json!({
+            "elapsed_ms": now_ms - self.start_ms,
+            "transactions": self.transactions,
+            "bytes_tx": self.bytes_tx,
+            "bytes_rx": self.bytes_rx,
+            "crc_failures": self.crc_failures,
+            "timeouts": self.timeouts,
+            "retries": self.retries,
+            "missing_motors": missing,
+            "latency_ms": self.latency.to_value(),
+            "loop_rate_hz": self.loop_rate_hz,
+        })
+        .to_string()
+    }
+}
+
+// ============================================================================
+// Round Trips
+// ============================================================================
+
+/// Run `send` then `recv`, timing the round trip with the `now_ms` clock.
+///
+/// The clock starts once the request is sent and stops when `recv` returns
+/// the answer, so any pause the caller takes around the exchange is not
+/// counted.
+///
+/// # Returns
+/// The answer and its round-trip time in milliseconds.
+pub async fn round_trip<T, E>(
+    send: impl Future<Output = Result<(), E>>,
+    recv: impl Future<Output = Result<T, E>>,
+    now_ms: impl Fn() -> f64,
+) -> Result<(T, f64), E> {
+    send.await?;
+    let sent_ms = now_ms();
+    let answer = recv.await?;
+    Ok((answer, now_ms() - sent_ms))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Byte offsets of every packet header (FF FF FD 00) in `data`.
+fn header_offsets(data: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    data.windows(4)
+        .enumerate()
+        .filter(|(_, w)| w == &[0xFF, 0xFF, 0xFD, 0x00])
+        .map(|(i, _)| i)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamixel::{build_sync_current_position, build_sync_write_torque};
+    use crate::simulator::SimulatedBus;
+
+    #[test]
+    fn test_latency_histogram_buckets() {
+        let mut histogram = LatencyHistogram::new();
+        for latency in [3.0, 10.0, 11.0, 400.0] {
+            histogram.record(latency);
+        }
+        assert_eq!(histogram.counts, [1, 1, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(histogram.min_ms, 3.0);
+        assert_eq!(histogram.max_ms, 400.0);
+        assert_eq!(histogram.mean_ms(), 106.0);
+    }
+
+    #[test]
+    fn test_missing_motors_and_crc_failures() {
+        let mut bus = SimulatedBus::reachy_mini();
+        let mut stats = ConnectionStats::new(0.0);
+
+        // Motor 12 is unplugged, motor 13 answers with a corrupted CRC
+        let request = build_sync_current_position(&[11, 12, 13]);
+        let full = bus.process(&request, 0.0);
+        let packet_len = full.len() / 3;
+        let mut response = full[..packet_len].to_vec();
+        let mut corrupted = full[2 * packet_len..].to_vec();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        response.extend(corrupted);
+
+        assert!(!stats.record_transaction(&request, &response, 12.0));
+        assert_eq!(stats.crc_failures, 1);
+        assert_eq!(stats.missing_motors, BTreeMap::from([(12, 1), (13, 1)]));
+
+        assert!(stats.record_transaction(&request, &[], 12.0));
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.missing_motors[&11], 1);
+    }
+
+    #[test]
+    fn test_writes_expect_no_response() {
+        let mut stats = ConnectionStats::new(0.0);
+        assert!(!stats.record_transaction(&build_sync_write_torque(&[11, 12], true), &[], 1.0));
+        assert_eq!(stats.timeouts, 0);
+        assert!(stats.missing_motors.is_empty());
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_round_trip_follows_bus_delay() {
+        use std::cell::RefCell;
+        use std::time::Instant;
+
+        use crate::dynamixel::address;
+        use crate::error::Error;
+        use crate::port::{Port, SimulatedPort, BUS_BAUD_RATE, DEFAULT_TIMEOUT};
+
+        let ids = [11, 12, 13, 14, 15, 16, 17, 18];
+        let request = build_sync_current_position(&ids);
+        let start = Instant::now();
+        let now_ms = || start.elapsed().as_secs_f64() * 1000.0;
+
+        for return_delay_us in [0, 500] {
+            let mut bus = SimulatedBus::new(&ids);
+            for &id in &ids {
+                let motor = bus.motor_mut(id).unwrap();
+                motor.write(address::RETURN_DELAY_TIME, &[(return_delay_us / 2) as u8]);
+            }
+            let port = RefCell::new(SimulatedPort::new(bus).with_timing(BUS_BAUD_RATE));
+            let mut stats = ConnectionStats::new(now_ms());
+            for _ in 0..3 {
+                let send = async { port.borrow_mut().write(&request) };
+                let recv = async {
+                    let mut response = Vec::new();
+                    let mut buf = [0u8; 256];
+                    while response.len() < ids.len() * 15 {
+                        let n = port.borrow_mut().read(&mut buf, DEFAULT_TIMEOUT)?;
+                        if n == 0 {
+                            break;
+                        }
+                        response.extend_from_slice(&buf[..n]);
+                    }
+                    Ok::<_, Error>(response)
+                };
+                let (response, latency_ms) =
+                    futures::executor::block_on(round_trip(send, recv, now_ms)).unwrap();
+                stats.record_transaction(&request, &response, latency_ms);
+            }
+
+            // At 1 Mbps (10 µs per byte): the request, then each motor's
+            // return delay and 15-byte status packet
+            let expected_ms =
+                (request.len() * 10 + ids.len() * (return_delay_us + 15 * 10)) as f64 / 1000.0;
+            assert!(stats.missing_motors.is_empty());
+            assert!(
+                stats.latency.min_ms > expected_ms - 0.1,
+                "{} ms for {} ms",
+                stats.latency.min_ms,
+                expected_ms
+            );
+            assert!(
+                stats.latency.max_ms < expected_ms + 3.0,
+                "{} ms for {} ms",
+                stats.latency.max_ms,
+                expected_ms
+            );
+        }
+    }
+
+    #[test]
+    fn test_loop_rate_and_snapshot() {
+        let mut stats = ConnectionStats::new(1000.0);
+        for i in 0..5 {
+            stats.record_loop(1000.0 + 20.0 * i as f64);
+        }
+        assert!((stats.loop_rate_hz - 50.0).abs() < 1e-9);
+
+        stats.record_tx(14);
+        stats.missing_motors.insert(17, 2);
+        let snapshot: Value = serde_json::from_str(&stats.to_json(1500.0)).unwrap();
+        assert_eq!(snapshot["elapsed_ms"], 500.0);
+        assert_eq!(snapshot["bytes_tx"], 14);
+        assert_eq!(snapshot["missing_motors"]["17"], 2);
+        assert_eq!(snapshot["latency_ms"]["buckets"][7]["le"], Value::Null);
+    }
+}
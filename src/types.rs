@@ -0,0 +1,417 @@
+//! # Typed API Values
+//!
+//! Named-field objects for poses, joints, antennas and diagnostics. They are
+//! exported as JS classes, so the generated `.d.ts` describes every field:
+//!
+//! ```typescript
+//! const pose: HeadPose = await get_pose();
+//! pose.z += 10;
+//! await set_pose(pose);
+//!
+//! const joints: JointState = await get_joint_state();
+//! console.log(joints.head, joints.antennas.left);
+//! ```
+//!
+//! The positional `Vec<f32>` functions (`get_head_pose()`, `get_all_joints()`,
+//! ...) remain available; `from_array()` / `to_array()` convert between the two.
+
+use wasm_bindgen::prelude::*;
+
+use crate::joints::Joint;
+use crate::kinematics::{ForwardSolution, PlatformJacobian, Projection};
+
+// ============================================================================
+// Head Pose
+// ============================================================================
+
+/// Head pose: position in millimeters, orientation in degrees, in the world
+/// frame, with the body yaw in degrees.
+///
+/// `z = 0` is the minimum head height. `body_yaw` is 0 on robots without a
+/// body yaw motor.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HeadPose {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub roll: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub body_yaw: f32,
+}
+
+#[wasm_bindgen]
+impl HeadPose {
+    /// Create a pose from its position (mm), orientation and body yaw
+    /// (degrees, 0 if omitted).
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        x: f32,
+        y: f32,
+        z: f32,
+        roll: f32,
+        pitch: f32,
+        yaw: f32,
+        body_yaw: Option<f32>,
+    ) -> HeadPose {
+        Self {
+            x,
+            y,
+            z,
+            roll,
+            pitch,
+            yaw,
+            body_yaw: body_yaw.unwrap_or(0.0),
+        }
+    }
+
+    /// Create a pose from `[x, y, z, roll, pitch, yaw]`, optionally followed
+    /// by the body yaw.
+    pub fn from_array(values: Vec<f32>) -> Result<HeadPose, JsValue> {
+        match values[..] {
+            [x, y, z, roll, pitch, yaw] => Ok(Self::new(x, y, z, roll, pitch, yaw, None)),
+            [x, y, z, roll, pitch, yaw, body_yaw] => {
+                Ok(Self::new(x, y, z, roll, pitch, yaw, Some(body_yaw)))
+            }
+            _ => Err(JsValue::from_str(
+                "Expected 6 or 7 values: [x, y, z, roll, pitch, yaw, body_yaw?]",
+            )),
+        }
+    }
+
+    /// The pose as `[x, y, z, roll, pitch, yaw, body_yaw]`.
+    pub fn to_array(&self) -> Vec<f32> {
+        let mut values = self.xyzrpy().to_vec();
+        values.push(self.body_yaw);
+        values
+    }
+}
+
+impl HeadPose {
+    /// Build from `[x, y, z, roll, pitch, yaw]` and a body yaw (degrees).
+    pub fn from_xyzrpy(pose: [f32; 6], body_yaw: f32) -> Self {
+        let [x, y, z, roll, pitch, yaw] = pose;
+        Self::new(x, y, z, roll, pitch, yaw, Some(body_yaw))
+    }
+
+    /// The pose as `[x, y, z, roll, pitch, yaw]`.
+    pub fn xyzrpy(&self) -> [f32; 6] {
+        [self.x, self.y, self.z, self.roll, self.pitch, self.yaw]
+    }
+}
+
+/// A head pose and the forward kinematics solve that produced it.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadPoseSolution {
+    pub pose: HeadPose,
+    /// Remaining rod length error in millimeters
+    pub residual_mm: f32,
+    /// Solver iterations, 0 when the previous pose still matched
+    pub iterations: u32,
+    /// Whether the residual is below the solver tolerance
+    pub converged: bool,
+}
+
+impl HeadPoseSolution {
+    /// Combine a pose in `[x, y, z, roll, pitch, yaw]` and the body yaw
+    /// (degrees) with its solve.
+    pub fn new(pose: [f32; 6], body_yaw: f32, solution: &ForwardSolution) -> Self {
+        Self {
+            pose: HeadPose::from_xyzrpy(pose, body_yaw),
+            residual_mm: solution.residual * 1000.0,
+            iterations: solution.iterations as u32,
+            converged: solution.converged,
+        }
+    }
+}
+
+/// The reachable pose closest to a request, and how far it was moved.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadPoseProjection {
+    pub pose: HeadPose,
+    /// Distance the position was moved in millimeters
+    pub translation_mm: f32,
+    /// Angle the orientation was rotated in degrees
+    pub rotation_deg: f32,
+    /// Whether the request was out of reach
+    pub moved: bool,
+}
+
+impl HeadPoseProjection {
+    /// Combine the reachable pose in `[x, y, z, roll, pitch, yaw]` with its
+    /// projection.
+    pub fn new(pose: [f32; 6], projection: &Projection) -> Self {
+        Self {
+            pose: HeadPose::from_xyzrpy(pose, 0.0),
+            translation_mm: projection.translation * 1000.0,
+            rotation_deg: projection.rotation.to_degrees(),
+            moved: projection.moved(),
+        }
+    }
+}
+
+/// Velocity kinematics of the head at one pose.
+///
+/// Twists are `[vx, vy, vz, wx, wy, wz]` in mm/s and degrees/s, in the world
+/// frame; joint velocities are degrees/s for the six head joints.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadJacobian {
+    jacobian: PlatformJacobian,
+}
+
+#[wasm_bindgen]
+impl HeadJacobian {
+    /// Head twist produced by the head joint velocities.
+    ///
+    /// # Errors
+    /// Returns error at a singular pose or without 6 velocities
+    pub fn twist(&self, joint_velocities: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+        if joint_velocities.len() != 6 {
+            return Err(JsValue::from_str("Expected 6 head joint velocities"));
+        }
+        let rad: Vec<f32> = joint_velocities.iter().map(|v| v.to_radians()).collect();
+        let twist = self
+            .jacobian
+            .twist(&rad)
+            .ok_or_else(|| JsValue::from_str("The head pose is singular"))?;
+        Ok(from_si_twist(twist).to_vec())
+    }
+
+    /// Head joint velocities producing the twist.
+    ///
+    /// # Errors
+    /// Returns error at a singular pose or without 6 twist components
+    pub fn joint_velocities(&self, twist: Vec<f32>) -> Result<Vec<f32>, JsValue> {
+        let twist = match twist[..] {
+            [vx, vy, vz, wx, wy, wz] => [vx, vy, vz, wx, wy, wz],
+            _ => {
+                return Err(JsValue::from_str(
+                    "Expected 6 values: [vx, vy, vz, wx, wy, wz]",
+                ))
+            }
+        };
+        let rad = self
+            .jacobian
+            .joint_velocities(to_si_twist(twist))
+            .ok_or_else(|| JsValue::from_str("The head pose is singular"))?;
+        Ok(rad.iter().map(|v| v.to_degrees()).collect())
+    }
+
+    /// Largest over smallest singular value; grows without bound near a
+    /// singularity.
+    #[wasm_bindgen(getter)]
+    pub fn condition_number(&self) -> f32 {
+        self.jacobian.condition_number()
+    }
+
+    /// Yoshikawa manipulability; zero where a joint no longer moves the head.
+    #[wasm_bindgen(getter)]
+    pub fn manipulability(&self) -> f32 {
+        self.jacobian.manipulability()
+    }
+}
+
+impl From<PlatformJacobian> for HeadJacobian {
+    fn from(jacobian: PlatformJacobian) -> Self {
+        Self { jacobian }
+    }
+}
+
+/// A twist in m/s and rad/s from mm/s and degrees/s.
+fn to_si_twist(twist: [f32; 6]) -> [f32; 6] {
+    let [vx, vy, vz, wx, wy, wz] = twist;
+    [
+        vx / 1000.0,
+        vy / 1000.0,
+        vz / 1000.0,
+        wx.to_radians(),
+        wy.to_radians(),
+        wz.to_radians(),
+    ]
+}
+
+/// A twist in mm/s and degrees/s from m/s and rad/s.
+fn from_si_twist(twist: [f32; 6]) -> [f32; 6] {
+    let [vx, vy, vz, wx, wy, wz] = twist;
+    [
+        vx * 1000.0,
+        vy * 1000.0,
+        vz * 1000.0,
+        wx.to_degrees(),
+        wy.to_degrees(),
+        wz.to_degrees(),
+    ]
+}
+
+// ============================================================================
+// Antennas & Joints
+// ============================================================================
+
+/// Antenna angles in degrees.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AntennaState {
+    pub left: f32,
+    pub right: f32,
+}
+
+#[wasm_bindgen]
+impl AntennaState {
+    /// Create an antenna state from left and right angles (degrees).
+    #[wasm_bindgen(constructor)]
+    pub fn new(left: f32, right: f32) -> AntennaState {
+        Self { left, right }
+    }
+}
+
+/// Angles of every joint in degrees.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointState {
+    /// Head motors 11-16, in motor order
+    #[wasm_bindgen(getter_with_clone)]
+    pub head: Vec<f32>,
+    pub antennas: AntennaState,
+}
+
+#[wasm_bindgen]
+impl JointState {
+    /// Create a joint state from 6 head angles and the antennas (degrees).
+    #[wasm_bindgen(constructor)]
+    pub fn new(head: Vec<f32>, antennas: &AntennaState) -> Result<JointState, JsValue> {
+        if head.len() != 6 {
+            return Err(JsValue::from_str("Expected 6 head joint angles"));
+        }
+        Ok(Self {
+            head,
+            antennas: *antennas,
+        })
+    }
+
+    /// Create a joint state from the 8 angles of `get_all_joints()`.
+    pub fn from_array(values: Vec<f32>) -> Result<JointState, JsValue> {
+        Self::from_all_joints(&values)
+            .ok_or_else(|| JsValue::from_str("Expected 8 joint angles (6 head + 2 antennas)"))
+    }
+
+    /// The 8 angles in `get_all_joints()` order: head 11-16, left, right.
+    pub fn to_array(&self) -> Vec<f32> {
+        let mut values = self.head.clone();
+        values.extend([self.antennas.left, self.antennas.right]);
+        values
+    }
+}
+
+impl JointState {
+    /// Build from 8 angles (head 11-16, left antenna, right antenna).
+    pub fn from_all_joints(values: &[f32]) -> Option<Self> {
+        if values.len() != 8 {
+            return None;
+        }
+        Some(Self {
+            head: values[0..6].to_vec(),
+            antennas: AntennaState::new(values[6], values[7]),
+        })
+    }
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// Health readings of one motor.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotorDiagnostics {
+    /// Joint name, e.g. `"l_antenna"`
+    #[wasm_bindgen(getter_with_clone)]
+    pub name: String,
+    /// Dynamixel ID
+    pub id: u8,
+    /// Temperature in °C
+    pub temperature: u8,
+    /// Load from -1000 to 1000 (percentage of max torque × 10)
+    pub load: i16,
+    /// Whether the motor answered both the temperature and the load read
+    pub responding: bool,
+}
+
+impl MotorDiagnostics {
+    /// Combine parsed temperature and load readings, one entry per motor in
+    /// `joints` order. Motors missing from either reading report zeros.
+    pub fn collect(joints: &[Joint], temperatures: &[(u8, u8)], loads: &[(u8, i16)]) -> Vec<Self> {
+        joints
+            .iter()
+            .map(|joint| {
+                let id = joint.id;
+                let temperature = temperatures.iter().find(|(m, _)| *m == id).map(|t| t.1);
+                let load = loads.iter().find(|(m, _)| *m == id).map(|l| l.1);
+                Self {
+                    name: joint.name.clone(),
+                    id,
+                    temperature: temperature.unwrap_or(0),
+                    load: load.unwrap_or(0),
+                    responding: temperature.is_some() && load.is_some(),
+                }
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joints::JointTable;
+
+    #[test]
+    fn test_head_pose_array_roundtrip() {
+        let pose = HeadPose::from_array(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(pose.z, 3.0);
+        assert_eq!(pose.yaw, 6.0);
+        assert_eq!(pose.body_yaw, 0.0);
+        assert_eq!(pose.to_array(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 0.0]);
+        assert_eq!(HeadPose::from_xyzrpy(pose.xyzrpy(), 0.0), pose);
+
+        let turned = HeadPose::from_array(pose.to_array()).unwrap();
+        assert_eq!(turned, pose);
+        let turned = HeadPose::from_array(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 30.0]).unwrap();
+        assert_eq!(turned.body_yaw, 30.0);
+    }
+
+    #[test]
+    fn test_joint_state_layout() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 45.0, -45.0];
+        let joints = JointState::from_all_joints(&values).unwrap();
+        assert_eq!(joints.head, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(joints.antennas, AntennaState::new(45.0, -45.0));
+        assert_eq!(joints.to_array(), values);
+        assert!(JointState::from_all_joints(&values[0..6]).is_none());
+    }
+
+    #[test]
+    fn test_diagnostics_collect() {
+        let head = JointTable::reachy_mini().head();
+        let diagnostics =
+            MotorDiagnostics::collect(&head[0..2], &[(12, 40), (11, 35)], &[(11, -20)]);
+        assert_eq!(
+            diagnostics[0],
+            MotorDiagnostics {
+                name: "stewart_1".to_string(),
+                id: 11,
+                temperature: 35,
+                load: -20,
+                responding: true
+            }
+        );
+        assert_eq!(diagnostics[1].temperature, 40);
+        assert!(!diagnostics[1].responding);
+    }
+}
@@ -0,0 +1,370 @@
+//! # Head Workspace
+//!
+//! Offline map of the head poses the Stewart platform can reach. A
+//! [`WorkspaceGrid`] spans ranges of `[x, y, z, roll, pitch, yaw]` (mm,
+//! degrees); every grid pose is classified by inverse kinematics, including
+//! the interference model and the head joint limits.
+//!
+//! ```
+//! use reachy_mini::config::RobotConfig;
+//! use reachy_mini::workspace::WorkspaceGrid;
+//!
+//! let workspace = RobotConfig::lite().workspace(&WorkspaceGrid::translation(10.0));
+//! assert!(workspace.contains([0.0; 6]));
+//! let z = workspace.axis_limits(2, [0.0; 6]); // z range above the center
+//! let cloud = workspace.to_ply();
+//! ```
+//!
+//! ## Exports
+//!
+//! - [`Workspace::to_json`]: the grid, every reachable pose and the limits of
+//!   each axis for every combination of the other axes
+//! - [`Workspace::to_ply`]: reachable poses as an ASCII point cloud (position
+//!   in mm, orientation as extra `roll`/`pitch`/`yaw` properties)
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::kinematics::{xyzrpy_to_transform, Kinematics};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Pose axis names, in `[x, y, z, roll, pitch, yaw]` order
+pub const AXES: [&str; 6] = ["x", "y", "z", "roll", "pitch", "yaw"];
+
+/// Translation range sampled by [`WorkspaceGrid::translation`] (mm)
+const TRANSLATION_RANGE_MM: f32 = 40.0;
+
+/// Orientation range sampled by [`WorkspaceGrid::orientation`] (degrees)
+const ORIENTATION_RANGE_DEG: f32 = 45.0;
+
+/// How far a pose may be from an axis sampled at a single value (mm, degrees)
+const SNAP_TOLERANCE: f32 = 1e-3;
+
+// ============================================================================
+// Grid
+// ============================================================================
+
+/// Sampled values of one pose axis: `min`, `min + step`, ... up to `max`.
+///
+/// A zero step (or `min == max`) samples `min` only.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AxisRange {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl AxisRange {
+    pub fn new(min: f32, max: f32, step: f32) -> Self {
+        Self { min, max, step }
+    }
+
+    /// A single value.
+    pub fn fixed(value: f32) -> Self {
+        Self::new(value, value, 0.0)
+    }
+
+    /// The sampled values, in increasing order.
+    pub fn values(&self) -> Vec<f32> {
+        if self.step <= 0.0 || self.max <= self.min {
+            return vec![self.min];
+        }
+        let count = ((self.max - self.min) / self.step + 1e-3).floor() as usize + 1;
+        (0..count)
+            .map(|i| self.min + i as f32 * self.step)
+            .collect()
+    }
+}
+
+/// Ranges of `[x, y, z, roll, pitch, yaw]` (mm, degrees) to sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceGrid {
+    pub axes: [AxisRange; 6],
+}
+
+impl WorkspaceGrid {
+    /// Positions within ±40 mm of the center every `step_mm`, head level.
+    pub fn translation(step_mm: f32) -> Self {
+        let range = AxisRange::new(-TRANSLATION_RANGE_MM, TRANSLATION_RANGE_MM, step_mm);
+        let level = AxisRange::fixed(0.0);
+        Self {
+            axes: [range, range, range, level, level, level],
+        }
+    }
+
+    /// Orientations within ±45° every `step_deg`, head centered.
+    pub fn orientation(step_deg: f32) -> Self {
+        let range = AxisRange::new(-ORIENTATION_RANGE_DEG, ORIENTATION_RANGE_DEG, step_deg);
+        let center = AxisRange::fixed(0.0);
+        Self {
+            axes: [center, center, center, range, range, range],
+        }
+    }
+
+    /// Number of poses in the grid.
+    pub fn len(&self) -> usize {
+        self.axes.iter().map(|axis| axis.values().len()).product()
+    }
+
+    /// Whether the grid has no poses (never true: every axis has a value).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// ============================================================================
+// Workspace
+// ============================================================================
+
+/// Reachability of every pose of a [`WorkspaceGrid`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    grid: WorkspaceGrid,
+    /// Sampled values per axis
+    values: [Vec<f32>; 6],
+    /// One flag per grid pose, last axis varying fastest
+    reachable: Vec<bool>,
+}
+
+/// Range of one axis at a fixed value of the other axes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AxisLimits {
+    /// The pose the limits were taken at (the limited axis is ignored)
+    pub at: [f32; 6],
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Kinematics {
+    /// Classify every pose of `grid` as reachable or not.
+    ///
+    /// # Arguments
+    /// * `grid` - Poses to sample (mm, degrees)
+    /// * `head_z_offset_mm` - Head height reported as `z = 0`
+    /// * `joint_limits` - `[min, max]` (radians) per branch; empty for none
+    pub fn compute_workspace(
+        &mut self,
+        grid: &WorkspaceGrid,
+        head_z_offset_mm: f32,
+        joint_limits: &[[f32; 2]],
+    ) -> Workspace {
+        let values = grid.axes.map(|axis| axis.values());
+        let reachable = (0..grid.len())
+            .map(|index| {
+                let pose = pose_at(&values, index);
+                match self.inverse_kinematics(xyzrpy_to_transform(pose, head_z_offset_mm), None) {
+                    Ok(angles) => angles
+                        .iter()
+                        .zip(joint_limits)
+                        .all(|(angle, [min, max])| (*min..=*max).contains(angle)),
+                    Err(_) => false,
+                }
+            })
+            .collect();
+        Workspace {
+            grid: *grid,
+            values,
+            reachable,
+        }
+    }
+}
+
+impl Workspace {
+    /// The sampled grid.
+    pub fn grid(&self) -> &WorkspaceGrid {
+        &self.grid
+    }
+
+    /// Whether the grid pose nearest to `pose` is reachable. Poses outside
+    /// the grid are not.
+    pub fn contains(&self, pose: [f32; 6]) -> bool {
+        self.index_of(pose)
+            .is_some_and(|index| self.reachable[index])
+    }
+
+    /// Every reachable grid pose.
+    pub fn reachable_poses(&self) -> Vec<[f32; 6]> {
+        (0..self.reachable.len())
+            .filter(|&index| self.reachable[index])
+            .map(|index| pose_at(&self.values, index))
+            .collect()
+    }
+
+    /// Fraction of the grid that is reachable.
+    pub fn coverage(&self) -> f32 {
+        let count = self.reachable.iter().filter(|r| **r).count();
+        count as f32 / self.reachable.len() as f32
+    }
+
+    /// Reachable range of `axis` (0-5, see [`AXES`]) with the other axes at
+    /// their grid values nearest to `pose`, `None` if nothing is reachable
+    /// there.
+    pub fn axis_limits(&self, axis: usize, pose: [f32; 6]) -> Option<[f32; 2]> {
+        let mut at = pose;
+        at[axis] = self.values[axis][0];
+        let start = self.index_of(at)?;
+        let stride = self.stride(axis);
+
+        let mut reachable = self.values[axis]
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.reachable[start + i * stride])
+            .map(|(_, value)| *value);
+        let min = reachable.next()?;
+        let max = reachable.next_back().unwrap_or(min);
+        Some([min, max])
+    }
+
+    /// Limits of `axis` for every combination of the other axes where some
+    /// pose is reachable.
+    pub fn limits(&self, axis: usize) -> Vec<AxisLimits> {
+        (0..self.reachable.len())
+            .map(|index| pose_at(&self.values, index))
+            .filter(|pose| pose[axis] == self.values[axis][0])
+            .filter_map(|at| {
+                let [min, max] = self.axis_limits(axis, at)?;
+                Some(AxisLimits { at, min, max })
+            })
+            .collect()
+    }
+
+    /// JSON document with the grid, the reachable poses and the limits of
+    /// every sampled axis.
+    pub fn to_json(&self) -> String {
+        let limits: serde_json::Map<String, serde_json::Value> = (0..6)
+            .filter(|&axis| self.values[axis].len() > 1)
+            .map(|axis| (AXES[axis].to_string(), json!(self.limits(axis))))
+            .collect();
+        json!({
+            "axes": AXES,
+            "grid": self.grid,
+            "coverage": self.coverage(),
+            "reachable": self.reachable_poses(),
+            "limits": limits,
+        })
+        .to_string()
+    }
+
+    /// ASCII PLY point cloud of the reachable poses.
+    pub fn to_ply(&self) -> String {
+        let poses = self.reachable_poses();
+        let mut ply = format!(
+            "ply\nformat ascii 1.0\ncomment reachy-mini head workspace (mm, degrees)\n\
+             element vertex {}\n",
+            poses.len()
+        );
+        for axis in AXES {
+            ply.push_str(&format!("property float {}\n", axis));
+        }
+        ply.push_str("end_header\n");
+        for pose in poses {
+            let values: Vec<String> = pose.iter().map(|v| v.to_string()).collect();
+            ply.push_str(&values.join(" "));
+            ply.push('\n');
+        }
+        ply
+    }
+
+    /// Flat index of the grid pose nearest to `pose`, `None` outside the grid.
+    fn index_of(&self, pose: [f32; 6]) -> Option<usize> {
+        let mut index = 0;
+        for (axis, values) in self.values.iter().enumerate() {
+            let range = &self.grid.axes[axis];
+            let i = if values.len() == 1 {
+                0.0
+            } else {
+                ((pose[axis] - range.min) / range.step).round()
+            };
+            let snapped = range.min + i * range.step.max(0.0);
+            if i < 0.0
+                || i as usize >= values.len()
+                || (pose[axis] - snapped).abs() > SNAP_TOLERANCE.max(range.step / 2.0)
+            {
+                return None;
+            }
+            index = index * values.len() + i as usize;
+        }
+        Some(index)
+    }
+
+    /// Distance between neighbours along `axis` in the flat index.
+    fn stride(&self, axis: usize) -> usize {
+        self.values[axis + 1..].iter().map(Vec::len).product()
+    }
+}
+
+/// Pose of the flat grid `index`.
+fn pose_at(values: &[Vec<f32>; 6], mut index: usize) -> [f32; 6] {
+    let mut pose = [0.0; 6];
+    for axis in (0..6).rev() {
+        let count = values[axis].len();
+        pose[axis] = values[axis][index % count];
+        index /= count;
+    }
+    pose
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RobotConfig;
+
+    #[test]
+    fn test_axis_values() {
+        assert_eq!(AxisRange::new(-10.0, 10.0, 5.0).values().len(), 5);
+        assert_eq!(AxisRange::fixed(3.0).values(), vec![3.0]);
+        assert_eq!(WorkspaceGrid::translation(10.0).len(), 9 * 9 * 9);
+    }
+
+    #[test]
+    fn test_translation_workspace() {
+        let workspace = RobotConfig::lite().workspace(&WorkspaceGrid::translation(10.0));
+        assert!(workspace.contains([0.0; 6]));
+        assert!(workspace.contains([1.0, -2.0, 3.0, 0.0, 0.0, 0.0]));
+        assert!(!workspace.contains([0.0, 0.0, 100.0, 0.0, 0.0, 0.0]));
+        assert!(!workspace.contains([0.0, 0.0, 0.0, 10.0, 0.0, 0.0]));
+
+        let coverage = workspace.coverage();
+        assert!(coverage > 0.05 && coverage < 1.0, "coverage {}", coverage);
+        let [min, max] = workspace.axis_limits(2, [0.0; 6]).unwrap();
+        assert!(min < 0.0 && max > 0.0, "z in [{}, {}]", min, max);
+        for limits in workspace.limits(2) {
+            assert!(workspace.contains([limits.at[0], limits.at[1], limits.max, 0.0, 0.0, 0.0]));
+        }
+    }
+
+    #[test]
+    fn test_joint_limits_shrink_workspace() {
+        let mut config = RobotConfig::lite();
+        let grid = WorkspaceGrid::orientation(15.0);
+        let free = config.workspace(&grid);
+        for joint in config.joints.iter_mut().take(6) {
+            joint.limits_deg = [-35.0, 35.0];
+        }
+        let limited = config.workspace(&grid);
+        assert!(limited.coverage() < free.coverage());
+        assert!(limited.contains([0.0; 6]));
+    }
+
+    #[test]
+    fn test_exports() {
+        let workspace = RobotConfig::lite().workspace(&WorkspaceGrid::translation(20.0));
+        let count = workspace.reachable_poses().len();
+
+        let ply = workspace.to_ply();
+        assert!(ply.contains(&format!("element vertex {}\n", count)));
+        assert_eq!(ply.lines().count(), 11 + count);
+
+        let json: serde_json::Value = serde_json::from_str(&workspace.to_json()).unwrap();
+        assert_eq!(json["reachable"].as_array().unwrap().len(), count);
+        assert!(!json["limits"]["z"].as_array().unwrap().is_empty());
+        assert!(json["limits"].get("roll").is_none());
+    }
+}